@@ -0,0 +1,180 @@
+//! A runtime-wide pause switch, for incident response.
+//!
+//! A guardian, or the admin, can pause the whole chain, for instance while a bridge exploit is
+//! investigated. The module only stores the switch: the runtime checks it before dispatching a
+//! call, or executing one for a governance proposal, and rejects every call while the chain is
+//! paused, except the calls of this module and of the governance module, so the chain can still
+//! be fixed and unpaused.
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Context, EventEmitter, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec,
+    StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+
+/// Genesis configuration of the [`ChainPause`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "ChainPauseConfig")]
+pub struct ChainPauseConfig<S: Spec> {
+    /// The account allowed to replace the admin and the guardian.
+    pub admin: S::Address,
+    /// The account allowed to pause and unpause the chain.
+    pub guardian: S::Address,
+}
+
+/// The pause switch of the chain.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct ChainPause<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The account allowed to replace the admin and the guardian.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The account allowed to pause the chain.
+    #[state]
+    pub guardian: StateValue<S::Address>,
+
+    /// Whether the chain is paused.
+    #[state]
+    pub paused: StateValue<bool>,
+}
+
+impl<S: Spec> ChainPause<S> {
+    /// Returns true if the chain is paused.
+    pub fn is_paused(&self, state: &mut impl StateReader<User>) -> Result<bool> {
+        Ok(self.paused.get(state)?.unwrap_or(false))
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .context("The chain pause admin is not set")?;
+        if &admin != context.sender() {
+            bail!("Only the admin {admin} can replace the chain pause admin or guardian");
+        }
+        Ok(())
+    }
+
+    /// The admin can do everything the guardian can.
+    fn ensure_guardian(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let guardian = self
+            .guardian
+            .get(state)?
+            .context("The chain pause guardian is not set")?;
+        if &guardian != context.sender() && self.ensure_admin(context, state).is_err() {
+            bail!("Only the guardian {guardian} or the admin can pause the chain");
+        }
+        Ok(())
+    }
+}
+
+impl<S: Spec> Module for ChainPause<S> {
+    type Spec = S;
+
+    type Config = ChainPauseConfig<S>;
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.admin.set(&config.admin, state)?;
+        self.guardian.set(&config.guardian, state)?;
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::Pause => {
+                self.ensure_guardian(context, state)?;
+                if self.is_paused(state)? {
+                    bail!("The chain is already paused");
+                }
+                self.paused.set(&true, state)?;
+                self.emit_event(
+                    state,
+                    Event::Paused {
+                        by: context.sender().clone(),
+                    },
+                );
+                Ok(())
+            }
+            CallMessage::Unpause => {
+                self.ensure_guardian(context, state)?;
+                if !self.is_paused(state)? {
+                    bail!("The chain is not paused");
+                }
+                self.paused.set(&false, state)?;
+                self.emit_event(
+                    state,
+                    Event::Unpaused {
+                        by: context.sender().clone(),
+                    },
+                );
+                Ok(())
+            }
+            CallMessage::SetGuardian { guardian } => {
+                self.ensure_admin(context, state)?;
+                self.guardian.set(&guardian, state)?;
+                self.emit_event(state, Event::GuardianSet { guardian });
+                Ok(())
+            }
+            CallMessage::SetAdmin { admin } => {
+                self.ensure_admin(context, state)?;
+                self.admin.set(&admin, state)?;
+                self.emit_event(state, Event::AdminSet { admin });
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Rejects every call but the chain pause and governance calls. Guardian or admin only.
+    Pause,
+    /// Accepts every call again. Guardian or admin only.
+    Unpause,
+    /// Replaces the guardian. Admin only.
+    SetGuardian { guardian: S::Address },
+    /// Replaces the admin. Admin only.
+    SetAdmin { admin: S::Address },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// The chain was paused by `by`.
+    Paused { by: S::Address },
+    /// The chain was unpaused by `by`.
+    Unpaused { by: S::Address },
+    /// The guardian was replaced.
+    GuardianSet { guardian: S::Address },
+    /// The admin was replaced.
+    AdminSet { admin: S::Address },
+}
@@ -0,0 +1,148 @@
+use chain_pause::{CallMessage, ChainPause, ChainPauseConfig, Event};
+use sov_modules_api::Spec;
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    chain_pause: ChainPause<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
+    pub guardian: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(3);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
+    let guardian = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        ChainPauseConfig {
+            admin: admin.address(),
+            guardian: guardian.address(),
+        },
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (
+        TestData {
+            admin,
+            guardian,
+            other_user,
+        },
+        runner,
+    )
+}
+
+#[test]
+fn test_guardian_pauses_and_unpauses() {
+    let (test_data, mut runner) = setup();
+
+    let guardian = test_data.guardian.address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .guardian
+            .create_plain_message::<TestRuntime<S>, ChainPause<S>>(CallMessage::Pause),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::ChainPause(Event::Paused { by: guardian })]
+            );
+            assert!(ChainPause::<S>::default().is_paused(state).unwrap());
+        }),
+    });
+
+    // Pausing twice is a mistake.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .guardian
+            .create_plain_message::<TestRuntime<S>, ChainPause<S>>(CallMessage::Pause),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    // The admin can do everything the guardian can.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, ChainPause<S>>(CallMessage::Unpause),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(!ChainPause::<S>::default().is_paused(state).unwrap());
+        }),
+    });
+}
+
+#[test]
+fn test_only_guardian_or_admin_pauses() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, ChainPause<S>>(CallMessage::Pause),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            assert!(!ChainPause::<S>::default().is_paused(state).unwrap());
+        }),
+    });
+}
+
+#[test]
+fn test_only_admin_replaces_guardian() {
+    let (test_data, mut runner) = setup();
+    let other_user = test_data.other_user.address();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .guardian
+            .create_plain_message::<TestRuntime<S>, ChainPause<S>>(CallMessage::SetGuardian {
+                guardian: other_user.clone(),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, ChainPause<S>>(CallMessage::SetGuardian {
+                guardian: other_user.clone(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                ChainPause::<S>::default().guardian.get(state).unwrap(),
+                Some(other_user)
+            );
+        }),
+    });
+
+    // The previous guardian can no longer pause the chain.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .guardian
+            .create_plain_message::<TestRuntime<S>, ChainPause<S>>(CallMessage::Pause),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
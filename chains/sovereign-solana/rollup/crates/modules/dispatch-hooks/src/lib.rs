@@ -0,0 +1,325 @@
+//! Composable post-dispatch hooks for the Hyperlane mailbox.
+//!
+//! The mailbox always posts dispatched messages to the merkle tree hook and the interchain gas
+//! paymaster. This module adds hooks on top of them, configured per Hyperlane sender (a warp
+//! route ID or the interchain accounts router). The runtime runs them after every dispatch of the
+//! sender, in the same transaction, so a failing hook reverts the dispatch.
+//!
+//! A sender's [`HookConfig`] maps destination domains to lists of hooks, with a fallback list for
+//! the other domains. Every hook of the selected list runs, in order. Senders without a config
+//! only go through the mailbox's required hooks.
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_bank::{Bank, Coins};
+use sov_hyperlane_integration::HexHash;
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, EventEmitter, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec,
+    StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+
+/// The maximum number of hooks in a single list.
+pub const MAX_HOOKS: usize = 8;
+
+/// The maximum number of destination domains with their own hooks.
+pub const MAX_DESTINATIONS: usize = 64;
+
+/// A hook run after a message is dispatched.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Hook")]
+pub enum Hook<S: Spec> {
+    /// Charges `fee` gas tokens to the account dispatching the message, paid to `beneficiary`.
+    ProtocolFee {
+        fee: Amount,
+        beneficiary: S::Address,
+    },
+    /// Rejects the message while the sender is paused.
+    Pausable,
+}
+
+/// The hooks run for messages to `domain`.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "DestinationHooks")]
+pub struct DestinationHooks<S: Spec> {
+    /// The Hyperlane domain of the destination.
+    pub domain: u32,
+    /// The hooks run, in order, for messages to the domain.
+    pub hooks: Vec<Hook<S>>,
+}
+
+/// The post-dispatch hooks of a sender.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "HookConfig")]
+pub struct HookConfig<S: Spec> {
+    /// The hooks of specific destinations, ordered by domain.
+    pub destinations: Vec<DestinationHooks<S>>,
+    /// The hooks run for destinations without an entry in `destinations`.
+    pub fallback: Vec<Hook<S>>,
+}
+
+impl<S: Spec> HookConfig<S> {
+    /// Returns the hooks run for messages to `destination`.
+    pub fn hooks(&self, destination: u32) -> &[Hook<S>] {
+        match self
+            .destinations
+            .binary_search_by_key(&destination, |entry| entry.domain)
+        {
+            Ok(index) => &self.destinations[index].hooks,
+            Err(_) => &self.fallback,
+        }
+    }
+
+    /// Returns the total protocol fee charged for a message to `destination`.
+    pub fn protocol_fee(&self, destination: u32) -> Result<Amount> {
+        self.hooks(destination)
+            .iter()
+            .try_fold(Amount::ZERO, |total, hook| match hook {
+                Hook::ProtocolFee { fee, .. } => total
+                    .checked_add(*fee)
+                    .context("The protocol fees overflow"),
+                Hook::Pausable => Ok(total),
+            })
+    }
+}
+
+/// Genesis configuration of the [`DispatchHooks`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "DispatchHooksConfig")]
+pub struct DispatchHooksConfig<S: Spec> {
+    /// The account allowed to configure hooks and to pause senders.
+    pub admin: S::Address,
+}
+
+/// Stores the post-dispatch hooks of Hyperlane senders and runs them.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct DispatchHooks<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The account allowed to manage hooks.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The hooks of each sender with a config.
+    #[state]
+    pub configs: StateMap<HexHash, HookConfig<S>>,
+
+    /// Senders paused by the admin. Only affects senders with a [`Hook::Pausable`] hook.
+    #[state]
+    pub paused: StateMap<HexHash, bool>,
+
+    /// Used to collect protocol fees.
+    #[module]
+    pub bank: Bank<S>,
+}
+
+impl<S: Spec> DispatchHooks<S> {
+    /// Returns true if `sender` is paused.
+    pub fn is_paused(&self, sender: &HexHash, state: &mut impl StateReader<User>) -> Result<bool> {
+        Ok(self.paused.get(sender, state)?.unwrap_or(false))
+    }
+
+    /// Returns the protocol fee the hooks of `sender` charge for a message to `destination`.
+    pub fn quote_fee(
+        &self,
+        sender: &HexHash,
+        destination: u32,
+        state: &mut impl StateReader<User>,
+    ) -> Result<Amount> {
+        match self.configs.get(sender, state)? {
+            Some(config) => config.protocol_fee(destination),
+            None => Ok(Amount::ZERO),
+        }
+    }
+
+    /// Runs the hooks of `sender` for a message just dispatched to `destination` by `payer`.
+    pub fn post_dispatch(
+        &mut self,
+        sender: &HexHash,
+        destination: u32,
+        payer: &S::Address,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let Some(config) = self.configs.get(sender, state)? else {
+            return Ok(());
+        };
+
+        for hook in config.hooks(destination) {
+            match hook {
+                Hook::ProtocolFee { fee, beneficiary } => {
+                    let coins = Coins {
+                        amount: *fee,
+                        token_id: config_value!("GAS_TOKEN_ID"),
+                    };
+                    self.bank
+                        .transfer_from(payer, beneficiary, coins, state)
+                        .with_context(|| format!("Failed to pay the protocol fee of {sender}"))?;
+                    self.emit_event(
+                        state,
+                        Event::ProtocolFeePaid {
+                            sender: sender.clone(),
+                            payer: payer.clone(),
+                            fee: *fee,
+                        },
+                    );
+                }
+                Hook::Pausable => {
+                    if self.is_paused(sender, state)? {
+                        bail!("Dispatching from {sender} is paused");
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .context("The dispatch hooks admin is not set")?;
+        if &admin != context.sender() {
+            bail!("Only the admin {admin} can manage dispatch hooks");
+        }
+        Ok(())
+    }
+}
+
+fn validate<S: Spec>(config: &HookConfig<S>) -> Result<()> {
+    if config.destinations.len() > MAX_DESTINATIONS {
+        bail!("A hook config can route at most {MAX_DESTINATIONS} destinations");
+    }
+    if config
+        .destinations
+        .windows(2)
+        .any(|pair| pair[0].domain >= pair[1].domain)
+    {
+        bail!("Destination hooks must be ordered by domain, without duplicates");
+    }
+    let lists = config
+        .destinations
+        .iter()
+        .map(|entry| &entry.hooks)
+        .chain([&config.fallback]);
+    for hooks in lists {
+        if hooks.len() > MAX_HOOKS {
+            bail!("A hook list can contain at most {MAX_HOOKS} hooks");
+        }
+    }
+    Ok(())
+}
+
+impl<S: Spec> Module for DispatchHooks<S> {
+    type Spec = S;
+
+    type Config = DispatchHooksConfig<S>;
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.admin.set(&config.admin, state)?;
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        self.ensure_admin(context, state)?;
+
+        match msg {
+            CallMessage::SetHooks { sender, config } => {
+                validate(&config)?;
+                self.configs.set(&sender, &config, state)?;
+                self.emit_event(state, Event::HooksSet { sender, config });
+            }
+            CallMessage::RemoveHooks { sender } => {
+                if self.configs.get(&sender, state)?.is_none() {
+                    bail!("{sender} has no dispatch hooks");
+                }
+                self.configs.remove(&sender, state)?;
+                self.emit_event(state, Event::HooksRemoved { sender });
+            }
+            CallMessage::Pause { sender } => {
+                self.paused.set(&sender, &true, state)?;
+                self.emit_event(state, Event::Paused { sender });
+            }
+            CallMessage::Unpause { sender } => {
+                self.paused.remove(&sender, state)?;
+                self.emit_event(state, Event::Unpaused { sender });
+            }
+            CallMessage::SetAdmin { admin } => {
+                self.admin.set(&admin, state)?;
+                self.emit_event(state, Event::AdminSet { admin });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Sets or replaces the hooks of `sender`. Admin only.
+    SetHooks {
+        sender: HexHash,
+        config: HookConfig<S>,
+    },
+    /// Removes the hooks of `sender`. Admin only.
+    RemoveHooks { sender: HexHash },
+    /// Makes the [`Hook::Pausable`] hooks of `sender` reject messages. Admin only.
+    Pause { sender: HexHash },
+    /// Lifts a pause. Admin only.
+    Unpause { sender: HexHash },
+    /// Replaces the admin. Admin only.
+    SetAdmin { admin: S::Address },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// The hooks of a sender were set or replaced.
+    HooksSet {
+        sender: HexHash,
+        config: HookConfig<S>,
+    },
+    /// The hooks of a sender were removed.
+    HooksRemoved { sender: HexHash },
+    /// A sender was paused.
+    Paused { sender: HexHash },
+    /// A sender was unpaused.
+    Unpaused { sender: HexHash },
+    /// The admin was replaced.
+    AdminSet { admin: S::Address },
+    /// A protocol fee hook charged `payer` for a message of `sender`.
+    ProtocolFeePaid {
+        sender: HexHash,
+        payer: S::Address,
+        fee: Amount,
+    },
+}
@@ -0,0 +1,382 @@
+use dispatch_hooks::{
+    CallMessage, DestinationHooks, DispatchHooks, DispatchHooksConfig, Event, Hook, HookConfig,
+};
+use schemars::JsonSchema;
+use sov_bank::Bank;
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec,
+    TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+/// Runs the hooks of [`sender`] the way the runtime does after a dispatch.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct Dispatcher<S: Spec> {
+    #[id]
+    pub id: ModuleId,
+
+    #[module]
+    pub dispatch_hooks: DispatchHooks<S>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "DispatchCall")]
+pub struct DispatchCall<S: Spec> {
+    pub destination: u32,
+    pub payer: S::Address,
+}
+
+impl<S: Spec> Module for Dispatcher<S> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = DispatchCall<S>;
+
+    type Event = ();
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        _config: &Self::Config,
+        _state: &mut impl GenesisState<S>,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        _context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        self.dispatch_hooks
+            .post_dispatch(&sender(), msg.destination, &msg.payer, state)
+    }
+}
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    dispatch_hooks: DispatchHooks<S>,
+    dispatcher: Dispatcher<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        DispatchHooksConfig {
+            admin: admin.address(),
+        },
+        (),
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { admin, other_user }, runner)
+}
+
+fn sender() -> HexHash {
+    HexString([3; 32])
+}
+
+fn config(beneficiary: <S as Spec>::Address) -> HookConfig<S> {
+    HookConfig {
+        destinations: vec![DestinationHooks {
+            domain: 1337,
+            hooks: vec![
+                Hook::ProtocolFee {
+                    fee: Amount::new(1_000),
+                    beneficiary,
+                },
+                Hook::Pausable,
+            ],
+        }],
+        fallback: vec![Hook::Pausable],
+    }
+}
+
+#[test]
+fn test_admin_sets_hooks() {
+    let (test_data, mut runner) = setup();
+    let config = config(test_data.admin.address());
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, DispatchHooks<S>>(CallMessage::SetHooks {
+                sender: sender(),
+                config: config.clone(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::DispatchHooks(Event::HooksSet {
+                    sender: sender(),
+                    config: config.clone(),
+                })]
+            );
+            assert_eq!(
+                DispatchHooks::<S>::default()
+                    .configs
+                    .get(&sender(), state)
+                    .unwrap(),
+                Some(config)
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_other_users_cannot_set_hooks() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, DispatchHooks<S>>(CallMessage::SetHooks {
+                sender: sender(),
+                config: config(test_data.other_user.address()),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_destinations_must_be_ordered() {
+    let (test_data, mut runner) = setup();
+    let mut config = config(test_data.admin.address());
+    config.destinations.push(DestinationHooks {
+        domain: 1,
+        hooks: Vec::new(),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, DispatchHooks<S>>(CallMessage::SetHooks {
+                sender: sender(),
+                config,
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_admin_pauses_and_unpauses_sender() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, DispatchHooks<S>>(CallMessage::Pause {
+                sender: sender(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(DispatchHooks::<S>::default()
+                .is_paused(&sender(), state)
+                .unwrap());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, DispatchHooks<S>>(CallMessage::Unpause {
+                sender: sender(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(!DispatchHooks::<S>::default()
+                .is_paused(&sender(), state)
+                .unwrap());
+        }),
+    });
+}
+
+#[test]
+fn test_hooks_fall_back_for_other_destinations() {
+    let (test_data, _runner) = setup();
+    let config = config(test_data.admin.address());
+
+    assert_eq!(config.hooks(1337).len(), 2);
+    assert_eq!(config.hooks(1), &[Hook::Pausable]);
+}
+
+#[test]
+fn test_protocol_fee_sums_fee_hooks() {
+    let (test_data, _runner) = setup();
+    let mut config = config(test_data.admin.address());
+    config.fallback.push(Hook::ProtocolFee {
+        fee: Amount::new(5),
+        beneficiary: test_data.admin.address(),
+    });
+    config.fallback.push(Hook::ProtocolFee {
+        fee: Amount::new(7),
+        beneficiary: test_data.other_user.address(),
+    });
+
+    assert_eq!(config.protocol_fee(1337).unwrap(), Amount::new(1_000));
+    assert_eq!(config.protocol_fee(1).unwrap(), Amount::new(12));
+}
+
+/// An account without funds, paid the protocol fees in the tests.
+fn beneficiary() -> <S as Spec>::Address {
+    CredentialId([9; 32]).into()
+}
+
+fn balance(runner: &mut TestRunner<TestRuntime<S>, S>, address: <S as Spec>::Address) -> Amount {
+    runner.query_state(|state| {
+        Bank::<S>::default()
+            .get_balance_of(&address, config_value!("GAS_TOKEN_ID"), state)
+            .unwrap()
+            .unwrap_or(Amount::ZERO)
+    })
+}
+
+fn set_hooks(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>) {
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, DispatchHooks<S>>(CallMessage::SetHooks {
+                sender: sender(),
+                config: config(beneficiary()),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+}
+
+fn dispatch(destination: u32, payer: <S as Spec>::Address) -> DispatchCall<S> {
+    DispatchCall { destination, payer }
+}
+
+#[test]
+fn test_post_dispatch_charges_protocol_fee() {
+    let (test_data, mut runner) = setup();
+    set_hooks(&test_data, &mut runner);
+    let payer = test_data.other_user.address();
+    let payer_balance = balance(&mut runner, payer.clone());
+
+    let expected_payer = payer.clone();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Dispatcher<S>>(dispatch(1337, payer.clone())),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::DispatchHooks(Event::ProtocolFeePaid {
+                    sender: sender(),
+                    payer: expected_payer,
+                    fee: Amount::new(1_000),
+                })]
+            );
+        }),
+    });
+
+    assert_eq!(balance(&mut runner, beneficiary()), Amount::new(1_000));
+    // The admin sent the transaction, so the payer only paid the protocol fee.
+    assert_eq!(
+        balance(&mut runner, payer),
+        payer_balance.checked_sub(Amount::new(1_000)).unwrap()
+    );
+
+    // The fallback hooks of other destinations charge nothing.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Dispatcher<S>>(dispatch(
+                1,
+                test_data.other_user.address(),
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result.events.is_empty());
+        }),
+    });
+    assert_eq!(balance(&mut runner, beneficiary()), Amount::new(1_000));
+}
+
+#[test]
+fn test_post_dispatch_reverts_while_paused() {
+    let (test_data, mut runner) = setup();
+    set_hooks(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, DispatchHooks<S>>(CallMessage::Pause {
+                sender: sender(),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    for destination in [1337, 1] {
+        runner.execute_transaction(TransactionTestCase {
+            input: test_data
+                .admin
+                .create_plain_message::<TestRuntime<S>, Dispatcher<S>>(dispatch(
+                    destination,
+                    test_data.other_user.address(),
+                )),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_reverted());
+            }),
+        });
+    }
+    // The fee charged before the pause hook ran is reverted with the dispatch.
+    assert_eq!(balance(&mut runner, beneficiary()), Amount::ZERO);
+}
+
+#[test]
+fn test_post_dispatch_reverts_for_unfunded_payer() {
+    let (test_data, mut runner) = setup();
+    set_hooks(&test_data, &mut runner);
+
+    let unfunded: <S as Spec>::Address = CredentialId([8; 32]).into();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Dispatcher<S>>(dispatch(1337, unfunded)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+    assert_eq!(balance(&mut runner, beneficiary()), Amount::ZERO);
+}
@@ -0,0 +1,351 @@
+//! A rate-limited faucet for devnets and testnets.
+//!
+//! The faucet dispenses the gas token and other tokens, such as warp route synthetic tokens, from
+//! its own account, [`Faucet::address`], which is funded like any other account. Each token has
+//! two limits: an address can claim it once every `cooldown` rollup heights, and at most
+//! `max_per_height` of it is dispensed in a single rollup height, across all addresses.
+//!
+//! A claim is a transaction whose gas someone has to pay. Accounts without gas tokens claim
+//! through a paymaster, or have a funded account claim for them, since anyone can claim for any
+//! recipient.
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_bank::{Bank, Coins, TokenId};
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, CryptoSpec, EventEmitter, GenesisState, Module, ModuleId,
+    ModuleInfo, ModuleRestApi, Spec, StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+
+/// The maximum number of tokens the faucet dispenses.
+pub const MAX_TOKENS: usize = 8;
+
+/// A token dispensed by the faucet, and its limits.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct FaucetToken {
+    /// The token dispensed.
+    pub token_id: TokenId,
+    /// The amount dispensed by a claim.
+    pub amount: Amount,
+    /// The number of rollup heights between two claims of the token by the same address.
+    pub cooldown: u64,
+    /// The maximum amount dispensed in a single rollup height.
+    pub max_per_height: Amount,
+}
+
+impl FaucetToken {
+    fn validate(&self) -> Result<()> {
+        if self.amount == Amount::ZERO {
+            bail!("The faucet can't dispense zero {}", self.token_id);
+        }
+        if self.amount > self.max_per_height {
+            bail!(
+                "A claim of {} can't exceed its limit per height",
+                self.token_id
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The last claim of a token by an address.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct LastClaim {
+    /// The token claimed.
+    pub token_id: TokenId,
+    /// The rollup height of the claim.
+    pub height: u64,
+}
+
+/// The amount of a token dispensed at a rollup height.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct Dispensed {
+    /// The rollup height.
+    pub height: u64,
+    /// The amount dispensed at `height`.
+    pub amount: Amount,
+}
+
+/// Genesis configuration of the [`Faucet`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "FaucetConfig")]
+pub struct FaucetConfig<S: Spec> {
+    /// The account allowed to change the dispensed tokens.
+    pub admin: S::Address,
+    /// The tokens dispensed at genesis.
+    pub tokens: Vec<FaucetToken>,
+}
+
+/// Dispenses tokens to anyone, within limits.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct Faucet<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The account allowed to change the dispensed tokens.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The dispensed tokens.
+    #[state]
+    pub tokens: StateValue<Vec<FaucetToken>>,
+
+    /// The last claim of every token claimed by an address.
+    #[state]
+    pub last_claims: StateMap<S::Address, Vec<LastClaim>>,
+
+    /// The amount of each token dispensed at the last height it was claimed.
+    #[state]
+    pub dispensed: StateMap<TokenId, Dispensed>,
+
+    /// Used to read the current rollup height.
+    #[module]
+    pub chain_state: sov_chain_state::ChainState<S>,
+
+    /// Used to dispense tokens.
+    #[module]
+    pub bank: Bank<S>,
+}
+
+impl<S: Spec> Faucet<S> {
+    /// The account holding the dispensed tokens. The faucet is funded by sending tokens to it.
+    pub fn address() -> S::Address {
+        use sov_modules_api::digest::Digest;
+
+        let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+        hasher.update(b"faucet");
+        CredentialId(hasher.finalize().into()).into()
+    }
+
+    /// Returns the first rollup height at which `recipient` can claim `token_id` again.
+    pub fn next_claim_height(
+        &self,
+        recipient: &S::Address,
+        token_id: &TokenId,
+        state: &mut impl StateReader<User>,
+    ) -> Result<u64> {
+        let token = self.token(token_id, state)?;
+        let last_claims = self.last_claims.get(recipient, state)?.unwrap_or_default();
+        Ok(last_claims
+            .iter()
+            .find(|claim| &claim.token_id == token_id)
+            .map_or(0, |claim| claim.height.saturating_add(token.cooldown)))
+    }
+
+    fn token(&self, token_id: &TokenId, state: &mut impl StateReader<User>) -> Result<FaucetToken> {
+        self.tokens
+            .get(state)?
+            .unwrap_or_default()
+            .into_iter()
+            .find(|token| &token.token_id == token_id)
+            .with_context(|| format!("The faucet doesn't dispense {token_id}"))
+    }
+
+    fn claim(
+        &mut self,
+        token_id: TokenId,
+        recipient: S::Address,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let token = self.token(&token_id, state)?;
+        let height = self.chain_state.rollup_height(state)?.get();
+
+        let next_claim_height = self.next_claim_height(&recipient, &token_id, state)?;
+        if height < next_claim_height {
+            bail!(
+                "{recipient} can't claim {token_id} again before rollup height {next_claim_height}"
+            );
+        }
+
+        let dispensed = match self.dispensed.get(&token_id, state)? {
+            Some(dispensed) if dispensed.height == height => dispensed.amount,
+            _ => Amount::ZERO,
+        };
+        let dispensed = dispensed
+            .checked_add(token.amount)
+            .filter(|total| *total <= token.max_per_height)
+            .with_context(|| {
+                format!("The faucet has dispensed all the {token_id} it can at this height")
+            })?;
+
+        let coins = Coins {
+            amount: token.amount,
+            token_id,
+        };
+        self.bank
+            .transfer_from(&Self::address(), &recipient, coins, state)
+            .with_context(|| format!("The faucet is out of {token_id}"))?;
+
+        self.dispensed.set(
+            &token_id,
+            &Dispensed {
+                height,
+                amount: dispensed,
+            },
+            state,
+        )?;
+        let mut last_claims = self.last_claims.get(&recipient, state)?.unwrap_or_default();
+        last_claims.retain(|claim| claim.token_id != token_id);
+        last_claims.push(LastClaim { token_id, height });
+        self.last_claims.set(&recipient, &last_claims, state)?;
+
+        self.emit_event(
+            state,
+            Event::Claimed {
+                recipient,
+                token_id,
+                amount: token.amount,
+            },
+        );
+        Ok(())
+    }
+
+    fn set_token(&mut self, token: FaucetToken, state: &mut impl TxState<S>) -> Result<()> {
+        token.validate()?;
+        let mut tokens = self.tokens.get(state)?.unwrap_or_default();
+        match tokens.iter_mut().find(|t| t.token_id == token.token_id) {
+            Some(existing) => *existing = token.clone(),
+            None if tokens.len() >= MAX_TOKENS => {
+                bail!("The faucet can dispense at most {MAX_TOKENS} tokens")
+            }
+            None => tokens.push(token.clone()),
+        }
+        self.tokens.set(&tokens, state)?;
+        self.emit_event(state, Event::TokenSet { token });
+        Ok(())
+    }
+
+    fn remove_token(&mut self, token_id: TokenId, state: &mut impl TxState<S>) -> Result<()> {
+        let mut tokens = self.tokens.get(state)?.unwrap_or_default();
+        let count = tokens.len();
+        tokens.retain(|token| token.token_id != token_id);
+        if tokens.len() == count {
+            bail!("The faucet doesn't dispense {token_id}");
+        }
+        self.tokens.set(&tokens, state)?;
+        self.emit_event(state, Event::TokenRemoved { token_id });
+        Ok(())
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .context("The faucet admin is not set")?;
+        if &admin != context.sender() {
+            bail!("Only the admin {admin} can change the faucet tokens");
+        }
+        Ok(())
+    }
+}
+
+impl<S: Spec> Module for Faucet<S> {
+    type Spec = S;
+
+    type Config = FaucetConfig<S>;
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        if config.tokens.len() > MAX_TOKENS {
+            bail!("The faucet can dispense at most {MAX_TOKENS} tokens");
+        }
+        for (index, token) in config.tokens.iter().enumerate() {
+            token.validate()?;
+            if config.tokens[..index]
+                .iter()
+                .any(|other| other.token_id == token.token_id)
+            {
+                bail!("{} is listed twice", token.token_id);
+            }
+        }
+        self.admin.set(&config.admin, state)?;
+        self.tokens.set(&config.tokens, state)?;
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::Claim {
+                token_id,
+                recipient,
+            } => self.claim(token_id, recipient, state),
+            CallMessage::SetToken { token } => {
+                self.ensure_admin(context, state)?;
+                self.set_token(token, state)
+            }
+            CallMessage::RemoveToken { token_id } => {
+                self.ensure_admin(context, state)?;
+                self.remove_token(token_id, state)
+            }
+            CallMessage::SetAdmin { admin } => {
+                self.ensure_admin(context, state)?;
+                self.admin.set(&admin, state)?;
+                self.emit_event(state, Event::AdminSet { admin });
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Sends the claim amount of `token_id` to `recipient`, within the limits of the token.
+    /// Anyone can claim, for any recipient; the limits apply to the recipient.
+    Claim {
+        token_id: TokenId,
+        recipient: S::Address,
+    },
+    /// Dispenses a token, or replaces its limits. Admin only.
+    SetToken { token: FaucetToken },
+    /// Stops dispensing `token_id`. Admin only.
+    RemoveToken { token_id: TokenId },
+    /// Replaces the admin. Admin only.
+    SetAdmin { admin: S::Address },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// `amount` of `token_id` was sent to `recipient`.
+    Claimed {
+        recipient: S::Address,
+        token_id: TokenId,
+        amount: Amount,
+    },
+    /// A token is dispensed, with new limits.
+    TokenSet { token: FaucetToken },
+    /// A token is no longer dispensed.
+    TokenRemoved { token_id: TokenId },
+    /// The admin was replaced.
+    AdminSet { admin: S::Address },
+}
@@ -0,0 +1,240 @@
+use faucet::{CallMessage, Event, Faucet, FaucetConfig, FaucetToken};
+use sov_bank::{Bank, Coins, TokenId};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::{Amount, CredentialId, Spec};
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    faucet: Faucet<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+fn gas_token() -> TokenId {
+    config_value!("GAS_TOKEN_ID")
+}
+
+fn claim_amount() -> Amount {
+    Amount::new(100)
+}
+
+fn gas_faucet() -> FaucetToken {
+    FaucetToken {
+        token_id: gas_token(),
+        amount: claim_amount(),
+        cooldown: 100,
+        max_per_height: Amount::new(1_000),
+    }
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        FaucetConfig {
+            admin: admin.address(),
+            tokens: vec![gas_faucet()],
+        },
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { admin, other_user }, runner)
+}
+
+/// Sends gas tokens from the admin to the faucet account.
+fn fund(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>, amount: Amount) {
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Bank<S>>(sov_bank::CallMessage::Transfer {
+                to: Faucet::<S>::address(),
+                coins: Coins {
+                    amount,
+                    token_id: gas_token(),
+                },
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+}
+
+fn claim(recipient: <S as Spec>::Address) -> CallMessage<S> {
+    CallMessage::Claim {
+        token_id: gas_token(),
+        recipient,
+    }
+}
+
+#[test]
+fn test_claim_respects_cooldown() {
+    let (test_data, mut runner) = setup();
+    fund(&test_data, &mut runner, Amount::new(10_000));
+
+    // Anyone can claim for a recipient, for instance for a new account without gas tokens.
+    let recipient = <S as Spec>::Address::from(CredentialId([7; 32]));
+    let expected_recipient = recipient.clone();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(claim(recipient.clone())),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result
+                .events
+                .contains(&TestRuntimeEvent::Faucet(Event::Claimed {
+                    recipient: expected_recipient.clone(),
+                    token_id: gas_token(),
+                    amount: claim_amount(),
+                })));
+            assert_eq!(
+                Bank::<S>::default()
+                    .get_balance_of(&expected_recipient, gas_token(), state)
+                    .unwrap(),
+                Some(claim_amount())
+            );
+        }),
+    });
+
+    // The recipient has to wait for the cooldown to claim again.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(claim(recipient.clone())),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    // Other recipients are not affected.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(claim(
+                test_data.other_user.address(),
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+}
+
+#[test]
+fn test_empty_faucet_cannot_dispense() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(claim(
+                test_data.other_user.address(),
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_admin_manages_tokens() {
+    let (test_data, mut runner) = setup();
+    fund(&test_data, &mut runner, Amount::new(10_000));
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(CallMessage::RemoveToken {
+                token_id: gas_token(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                Faucet::<S>::default().tokens.get(state).unwrap(),
+                Some(Vec::new())
+            );
+        }),
+    });
+
+    // Tokens that are not dispensed can't be claimed.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(claim(
+                test_data.other_user.address(),
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(CallMessage::SetToken {
+                token: gas_faucet(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                Faucet::<S>::default().tokens.get(state).unwrap(),
+                Some(vec![gas_faucet()])
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_only_admin_manages_tokens() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(CallMessage::SetToken {
+                token: FaucetToken {
+                    amount: Amount::new(1_000),
+                    ..gas_faucet()
+                },
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_claim_amount_must_fit_limit_per_height() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Faucet<S>>(CallMessage::SetToken {
+                token: FaucetToken {
+                    max_per_height: Amount::new(10),
+                    ..gas_faucet()
+                },
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
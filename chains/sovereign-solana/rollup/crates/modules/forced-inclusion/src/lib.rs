@@ -0,0 +1,94 @@
+//! The allow-list of runtime calls that unregistered senders may post directly to the DA layer.
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use sov_modules_api::{
+    Context, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, NotInstantiable, Spec,
+    StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+use std::marker::PhantomData;
+
+/// Genesis configuration of the [`ForcedInclusion`] module.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize, JsonSchema,
+)]
+pub struct ForcedInclusionConfig {
+    /// Calls that unregistered senders may submit. Each entry is either a module (`bank`)
+    /// or a `module.call` pair (`warp.transfer_remote`). The runtime checks that the names
+    /// exist.
+    pub allowed_calls: Vec<String>,
+}
+
+/// Stores the forced inclusion allow-list set at genesis.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct ForcedInclusion<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The calls unregistered senders may submit.
+    #[state]
+    pub allowed_calls: StateValue<Vec<String>>,
+
+    #[phantom]
+    phantom: PhantomData<S>,
+}
+
+impl<S: Spec> ForcedInclusion<S> {
+    /// Returns true if the call `call` of `module` may be submitted by an unregistered sender.
+    /// `call` is `None` for calls that can't be allowed on their own.
+    pub fn is_allowed(
+        &self,
+        module: &str,
+        call: Option<&str>,
+        state: &mut impl StateReader<User>,
+    ) -> Result<bool> {
+        let allowed_calls = self.allowed_calls.get(state)?.unwrap_or_default();
+        Ok(allowed_calls
+            .iter()
+            .any(|allowed| match allowed.split_once('.') {
+                Some((allowed_module, allowed_call)) => {
+                    allowed_module == module && Some(allowed_call) == call
+                }
+                None => allowed == module,
+            }))
+    }
+}
+
+impl<S: Spec> Module for ForcedInclusion<S> {
+    type Spec = S;
+
+    type Config = ForcedInclusionConfig;
+
+    type CallMessage = NotInstantiable;
+
+    type Event = ();
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        for allowed in &config.allowed_calls {
+            if allowed.is_empty() || allowed.split('.').count() > 2 {
+                bail!("Invalid forced inclusion entry `{allowed}`, expected `module` or `module.call`");
+            }
+        }
+
+        self.allowed_calls.set(&config.allowed_calls, state)?;
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        _context: &Context<Self::Spec>,
+        _state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {}
+    }
+}
@@ -0,0 +1,257 @@
+//! Gas oracle for the interchain gas paymaster.
+//!
+//! The interchain gas paymaster quotes delivery fees from the oracle data each relayer sets in
+//! its own config, which is static until the relayer sends a new config. A relayer registers a
+//! feed here to delegate price updates to an updater account, typically a bot pushing prices from
+//! an off-chain source on a schedule. Every update is written to the relayer's paymaster config
+//! through the runtime, so quotes follow destination gas prices and exchange rates.
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Context, EventEmitter, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec, StateMap, TxState,
+};
+use std::marker::PhantomData;
+
+/// The maximum number of destination domains a feed can price.
+pub const MAX_DOMAINS: usize = 64;
+
+/// Runtime hooks used by [`GasOracle`]. The module can't depend on the runtime it is part of,
+/// so writing the relayer config of the interchain gas paymaster is delegated.
+pub trait GasOracleRuntime<S: Spec>: Clone + Default + Send + Sync + 'static {
+    /// Replaces the paymaster config of `context.sender()` with `feed` and `quotes`.
+    fn set_relayer_config(
+        feed: &OracleFeed<S>,
+        quotes: &[GasQuote],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()>;
+}
+
+/// The default gas amount of a destination domain.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct DomainGas {
+    /// The Hyperlane domain of the destination.
+    pub domain: u32,
+    /// The gas amount quoted when a message doesn't specify one.
+    pub default_gas: u128,
+}
+
+/// The gas price and exchange rate of a destination domain, as pushed by an updater.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct DomainGasData {
+    /// The Hyperlane domain of the destination.
+    pub domain: u32,
+    /// The gas price on the destination, in its native token.
+    pub gas_price: u128,
+    /// The exchange rate from the destination native token to the rollup gas token.
+    pub token_exchange_rate: u128,
+}
+
+/// The latest gas data of a destination domain.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct GasQuote {
+    /// The pushed gas data.
+    pub data: DomainGasData,
+    /// The rollup height of the update.
+    pub updated_at: u64,
+}
+
+/// The parts of a relayer's paymaster config that don't come from the feed, and the account
+/// allowed to push prices.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "OracleFeed")]
+pub struct OracleFeed<S: Spec> {
+    /// The account allowed to push prices for the relayer.
+    pub updater: S::Address,
+    /// The account receiving the relayer's gas payments.
+    pub beneficiary: S::Address,
+    /// The gas amount quoted for domains without a default.
+    pub default_gas: u128,
+    /// Per-domain default gas amounts.
+    pub domain_default_gas: Vec<DomainGas>,
+}
+
+/// Keeps the price feeds of relayers and their latest quotes.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct GasOracle<S: Spec, R: GasOracleRuntime<S>> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The feed registered by each relayer.
+    #[state]
+    pub feeds: StateMap<S::Address, OracleFeed<S>>,
+
+    /// The latest quote of every domain priced by each relayer, ordered by domain.
+    #[state]
+    pub quotes: StateMap<S::Address, Vec<GasQuote>>,
+
+    /// Used to read the current rollup height.
+    #[module]
+    pub chain_state: sov_chain_state::ChainState<S>,
+
+    #[phantom]
+    phantom: PhantomData<R>,
+}
+
+impl<S: Spec, R: GasOracleRuntime<S>> GasOracle<S, R> {
+    fn update_prices(
+        &mut self,
+        relayer: S::Address,
+        prices: Vec<DomainGasData>,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let Some(feed) = self.feeds.get(&relayer, state)? else {
+            bail!("Relayer {relayer} has no gas oracle feed");
+        };
+        if &feed.updater != context.sender() {
+            bail!(
+                "Only the updater {} can push prices for {relayer}",
+                feed.updater
+            );
+        }
+        if prices.is_empty() {
+            bail!("A price update must contain at least one domain");
+        }
+
+        let height = self.chain_state.rollup_height(state)?.get();
+        let mut quotes = self.quotes.get(&relayer, state)?.unwrap_or_default();
+        for data in prices {
+            let quote = GasQuote {
+                data,
+                updated_at: height,
+            };
+            match quotes.binary_search_by_key(&quote.data.domain, |quote| quote.data.domain) {
+                Ok(index) => quotes[index] = quote,
+                Err(index) => quotes.insert(index, quote),
+            }
+        }
+        if quotes.len() > MAX_DOMAINS {
+            bail!("A feed can price at most {MAX_DOMAINS} domains");
+        }
+
+        let relayer_context = Context::new(
+            relayer.clone(),
+            context.get_sender_credentials().clone(),
+            context.sequencer().clone(),
+            context.sequencer_da_address().clone(),
+        );
+        R::set_relayer_config(&feed, &quotes, &relayer_context, state)?;
+        self.quotes.set(&relayer, &quotes, state)?;
+
+        self.emit_event(
+            state,
+            Event::PricesUpdated {
+                relayer,
+                quotes: quotes.len() as u32,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl<S: Spec, R: GasOracleRuntime<S>> Module for GasOracle<S, R> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::RegisterFeed {
+                updater,
+                beneficiary,
+                default_gas,
+                domain_default_gas,
+            } => {
+                if domain_default_gas.len() > MAX_DOMAINS {
+                    bail!("A feed can set the default gas of at most {MAX_DOMAINS} domains");
+                }
+
+                let relayer = context.sender().clone();
+                let feed = OracleFeed {
+                    updater: updater.clone(),
+                    beneficiary,
+                    default_gas,
+                    domain_default_gas,
+                };
+                self.feeds.set(&relayer, &feed, state)?;
+                self.emit_event(state, Event::FeedRegistered { relayer, updater });
+                Ok(())
+            }
+            CallMessage::RemoveFeed => {
+                let relayer = context.sender().clone();
+                if self.feeds.get(&relayer, state)?.is_none() {
+                    bail!("Relayer {relayer} has no gas oracle feed");
+                }
+                // The paymaster config keeps the last pushed prices until the relayer replaces it.
+                self.feeds.remove(&relayer, state)?;
+                self.quotes.remove(&relayer, state)?;
+                self.emit_event(state, Event::FeedRemoved { relayer });
+                Ok(())
+            }
+            CallMessage::UpdatePrices { relayer, prices } => {
+                self.update_prices(relayer, prices, context, state)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Lets `updater` push prices for the sender, which must be a relayer. The other fields
+    /// are copied into the sender's paymaster config on every update. Registering again
+    /// replaces the feed and keeps the latest quotes.
+    RegisterFeed {
+        updater: S::Address,
+        beneficiary: S::Address,
+        default_gas: u128,
+        domain_default_gas: Vec<DomainGas>,
+    },
+    /// Stops accepting prices for the sender.
+    RemoveFeed,
+    /// Updates the gas data of some domains priced by `relayer`. Updater only.
+    UpdatePrices {
+        relayer: S::Address,
+        prices: Vec<DomainGasData>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// A relayer registered or replaced its feed.
+    FeedRegistered {
+        relayer: S::Address,
+        updater: S::Address,
+    },
+    /// A relayer removed its feed.
+    FeedRemoved { relayer: S::Address },
+    /// An updater pushed prices; `quotes` is the number of domains now priced by the relayer.
+    PricesUpdated { relayer: S::Address, quotes: u32 },
+}
@@ -0,0 +1,178 @@
+use gas_oracle::{
+    CallMessage, DomainGasData, Event, GasOracle, GasOracleRuntime, GasQuote, OracleFeed,
+};
+use sov_modules_api::{Context, Spec, TxState};
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+/// The module tests don't include the interchain gas paymaster, so configs are not written.
+#[derive(Clone, Default)]
+pub struct NoopPaymaster;
+
+impl GasOracleRuntime<S> for NoopPaymaster {
+    fn set_relayer_config(
+        _feed: &OracleFeed<S>,
+        _quotes: &[GasQuote],
+        _context: &Context<S>,
+        _state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+type Oracle = GasOracle<S, NoopPaymaster>;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    gas_oracle: Oracle
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+const SEALEVEL_DOMAIN: u32 = 1337;
+const ETHTEST_DOMAIN: u32 = 3133790210;
+
+pub struct TestData<S: Spec> {
+    pub relayer: TestUser<S>,
+    pub updater: TestUser<S>,
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let relayer = users.pop().unwrap();
+    let updater = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(genesis_config.into(), ());
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { relayer, updater }, runner)
+}
+
+fn gas_data(domain: u32, gas_price: u128) -> DomainGasData {
+    DomainGasData {
+        domain,
+        gas_price,
+        token_exchange_rate: 1,
+    }
+}
+
+fn register_feed(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>) {
+    let relayer = test_data.relayer.address();
+    let updater = test_data.updater.address();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .relayer
+            .create_plain_message::<TestRuntime<S>, Oracle>(CallMessage::RegisterFeed {
+                updater: updater.clone(),
+                beneficiary: relayer.clone(),
+                default_gas: 2_000,
+                domain_default_gas: Vec::new(),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::GasOracle(Event::FeedRegistered {
+                    relayer,
+                    updater,
+                })]
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_updater_pushes_prices() {
+    let (test_data, mut runner) = setup();
+    register_feed(&test_data, &mut runner);
+    let relayer = test_data.relayer.address();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .updater
+            .create_plain_message::<TestRuntime<S>, Oracle>(CallMessage::UpdatePrices {
+                relayer: relayer.clone(),
+                prices: vec![gas_data(ETHTEST_DOMAIN, 7), gas_data(SEALEVEL_DOMAIN, 5)],
+            }),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .updater
+            .create_plain_message::<TestRuntime<S>, Oracle>(CallMessage::UpdatePrices {
+                relayer: relayer.clone(),
+                prices: vec![gas_data(SEALEVEL_DOMAIN, 9)],
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+
+            let quotes = Oracle::default()
+                .quotes
+                .get(&relayer, state)
+                .unwrap()
+                .unwrap();
+            let data: Vec<_> = quotes.into_iter().map(|quote| quote.data).collect();
+            assert_eq!(
+                data,
+                vec![gas_data(SEALEVEL_DOMAIN, 9), gas_data(ETHTEST_DOMAIN, 7)]
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_only_updater_pushes_prices() {
+    let (test_data, mut runner) = setup();
+    register_feed(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .relayer
+            .create_plain_message::<TestRuntime<S>, Oracle>(CallMessage::UpdatePrices {
+                relayer: test_data.relayer.address(),
+                prices: vec![gas_data(SEALEVEL_DOMAIN, 5)],
+            }),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_removed_feed_rejects_prices() {
+    let (test_data, mut runner) = setup();
+    register_feed(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .relayer
+            .create_plain_message::<TestRuntime<S>, Oracle>(CallMessage::RemoveFeed),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .updater
+            .create_plain_message::<TestRuntime<S>, Oracle>(CallMessage::UpdatePrices {
+                relayer: test_data.relayer.address(),
+                prices: vec![gas_data(SEALEVEL_DOMAIN, 5)],
+            }),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
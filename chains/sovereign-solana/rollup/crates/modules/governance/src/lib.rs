@@ -0,0 +1,618 @@
+//! On-chain governance of the rollup's admin actions.
+//!
+//! A council proposes batches of runtime calls, such as paymaster policy updates or warp route
+//! admin changes. A proposal is queued once the council has approved it, and is executed at the
+//! end of the first rollup block after a timelock, measured in rollup heights, has passed. Its
+//! calls then run in order from the governance account, [`Governance::address`], so a module is
+//! governed by handing its admin role over to that account.
+//!
+//! The council is either a multisig of members with one vote each, or a token-weighted council,
+//! where the vote of a member is its balance of a token. Approvals are counted against the
+//! current council and balances, both when a proposal is queued and when it is executed, so a
+//! proposal that lost its support in the meantime can't be executed.
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_bank::{Bank, TokenId};
+use sov_hyperlane_integration::HexString;
+use sov_modules_api::capabilities::Credentials;
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, CryptoSpec, EventEmitter, GenesisState, Module, ModuleId,
+    ModuleInfo, ModuleRestApi, Spec, StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+use std::marker::PhantomData;
+
+/// The maximum number of council members.
+pub const MAX_MEMBERS: usize = 32;
+
+/// The maximum number of calls in a proposal.
+pub const MAX_CALLS: usize = 16;
+
+/// The maximum number of proposals queued at the same time. Due proposals are executed at the
+/// end of every rollup block, so this bounds the work of a single block.
+pub const MAX_QUEUED: usize = 16;
+
+/// Runtime hooks used by [`Governance`]. The module can't depend on the runtime it is part of, so
+/// executing runtime calls is delegated.
+pub trait GovernanceRuntime<S: Spec>: Clone + Default + Send + Sync + 'static {
+    /// Executes a borsh encoded runtime call from `context.sender()`.
+    fn execute(call: &[u8], context: &Context<S>, state: &mut impl TxState<S>) -> Result<()>;
+
+    /// Returns false if a borsh encoded runtime call must run in a transaction, so proposals
+    /// making it are left to [`CallMessage::Execute`] instead of running at the end of a block.
+    fn runs_at_end_of_block(_call: &[u8]) -> bool {
+        true
+    }
+}
+
+/// The accounts allowed to propose and approve proposals.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Council")]
+pub enum Council<S: Spec> {
+    /// A proposal passes once `threshold` members have approved it.
+    Multisig {
+        members: Vec<S::Address>,
+        threshold: u8,
+    },
+    /// A proposal passes once the members who approved it hold at least `threshold` of
+    /// `token_id` between them.
+    TokenWeighted {
+        members: Vec<S::Address>,
+        token_id: TokenId,
+        threshold: Amount,
+    },
+}
+
+impl<S: Spec> Council<S> {
+    /// The council members.
+    pub fn members(&self) -> &[S::Address] {
+        match self {
+            Council::Multisig { members, .. } | Council::TokenWeighted { members, .. } => members,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let members = self.members();
+        if members.is_empty() || members.len() > MAX_MEMBERS {
+            bail!(
+                "A council must have between 1 and {MAX_MEMBERS} members, got {}",
+                members.len()
+            );
+        }
+        for (index, member) in members.iter().enumerate() {
+            if members[..index].contains(member) {
+                bail!("Council members must be unique, {member} is listed twice");
+            }
+        }
+
+        match self {
+            Council::Multisig { threshold, .. } => {
+                if *threshold == 0 || usize::from(*threshold) > members.len() {
+                    bail!(
+                        "Invalid threshold {threshold} for a council with {} members",
+                        members.len()
+                    );
+                }
+            }
+            Council::TokenWeighted { threshold, .. } => {
+                if *threshold == Amount::ZERO {
+                    bail!("The threshold of a token-weighted council can't be zero");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The lifecycle of a proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    /// Waiting for approvals.
+    Pending,
+    /// Approved by the council, executable from rollup height `eta`.
+    Queued { eta: u64 },
+    /// Executed at rollup height `height`.
+    Executed { height: u64 },
+    /// Its automatic execution at rollup height `height` failed.
+    Failed { height: u64 },
+    /// Cancelled by its proposer, the council or the guardian.
+    Cancelled,
+}
+
+/// A batch of runtime calls proposed to the council.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Proposal")]
+pub struct Proposal<S: Spec> {
+    /// The council member who made the proposal.
+    pub proposer: S::Address,
+    /// The borsh encoded runtime calls, executed in order.
+    pub calls: Vec<HexString>,
+    /// The council members who approved the proposal, including the proposer.
+    pub approvals: Vec<S::Address>,
+    /// Where the proposal is in its lifecycle.
+    pub status: ProposalStatus,
+}
+
+/// Genesis configuration of the [`Governance`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "GovernanceConfig")]
+pub struct GovernanceConfig<S: Spec> {
+    /// The initial council.
+    pub council: Council<S>,
+    /// The number of rollup heights between the approval of a proposal and its execution.
+    pub timelock: u64,
+    /// An account allowed to cancel queued proposals without waiting for a proposal, for
+    /// example a security multisig.
+    #[serde(default)]
+    pub guardian: Option<S::Address>,
+}
+
+/// Executes the runtime calls approved by the council, after a timelock.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct Governance<S: Spec, R: GovernanceRuntime<S>> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The accounts allowed to propose and approve proposals.
+    #[state]
+    pub council: StateValue<Council<S>>,
+
+    /// The number of rollup heights between the approval of a proposal and its execution.
+    #[state]
+    pub timelock: StateValue<u64>,
+
+    /// Every proposal, by ID.
+    #[state]
+    pub proposals: StateMap<u64, Proposal<S>>,
+
+    /// The ID of the next proposal.
+    #[state]
+    pub next_proposal_id: StateValue<u64>,
+
+    /// The IDs of the queued proposals, in the order they were queued.
+    #[state]
+    pub queued: StateValue<Vec<u64>>,
+
+    /// The account allowed to cancel queued proposals, if any.
+    #[state]
+    pub guardian: StateValue<S::Address>,
+
+    /// Used to read the current rollup height.
+    #[module]
+    pub chain_state: sov_chain_state::ChainState<S>,
+
+    /// Used to read the balances of a token-weighted council.
+    #[module]
+    pub bank: Bank<S>,
+
+    #[phantom]
+    phantom: PhantomData<R>,
+}
+
+impl<S: Spec, R: GovernanceRuntime<S>> Governance<S, R> {
+    /// The account executing the calls of proposals. Modules are governed by making it their
+    /// admin.
+    pub fn address() -> S::Address {
+        use sov_modules_api::digest::Digest;
+
+        let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+        hasher.update(b"governance");
+        CredentialId(hasher.finalize().into()).into()
+    }
+
+    /// Returns true if the council members who approved `proposal` are enough to pass it.
+    pub fn passes(
+        &self,
+        proposal: &Proposal<S>,
+        state: &mut impl StateReader<User>,
+    ) -> Result<bool> {
+        let council = self.council(state)?;
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|member| council.members().contains(member));
+
+        Ok(match &council {
+            Council::Multisig { threshold, .. } => approvals.count() >= usize::from(*threshold),
+            Council::TokenWeighted {
+                token_id,
+                threshold,
+                ..
+            } => {
+                let mut weight = Amount::ZERO;
+                for member in approvals {
+                    let balance = self
+                        .bank
+                        .get_balance_of(member, *token_id, state)?
+                        .unwrap_or(Amount::ZERO);
+                    weight = weight.saturating_add(balance);
+                }
+                weight >= *threshold
+            }
+        })
+    }
+
+    fn council(&self, state: &mut impl StateReader<User>) -> Result<Council<S>> {
+        self.council
+            .get(state)?
+            .context("The governance council is not set")
+    }
+
+    fn proposal(&self, id: u64, state: &mut impl StateReader<User>) -> Result<Proposal<S>> {
+        self.proposals
+            .get(&id, state)?
+            .with_context(|| format!("Proposal {id} doesn't exist"))
+    }
+
+    fn ensure_member(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        if !self.council(state)?.members().contains(context.sender()) {
+            bail!("Only council members can propose and approve proposals");
+        }
+        Ok(())
+    }
+
+    fn ensure_governance(&self, context: &Context<S>) -> Result<()> {
+        if context.sender() != &Self::address() {
+            bail!("Governance settings can only be changed by a proposal");
+        }
+        Ok(())
+    }
+
+    /// Queues `proposal` if the council has approved it.
+    fn queue_if_passed(
+        &mut self,
+        id: u64,
+        proposal: &mut Proposal<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        if !self.passes(proposal, state)? {
+            return Ok(());
+        }
+        let mut queued = self.queued.get(state)?.unwrap_or_default();
+        if queued.len() >= MAX_QUEUED {
+            bail!("At most {MAX_QUEUED} proposals can be queued at the same time");
+        }
+        queued.push(id);
+        self.queued.set(&queued, state)?;
+
+        let height = self.chain_state.rollup_height(state)?.get();
+        let timelock = self.timelock.get(state)?.unwrap_or_default();
+        let eta = height.saturating_add(timelock);
+        proposal.status = ProposalStatus::Queued { eta };
+        self.emit_event(state, Event::ProposalQueued { id, eta });
+        Ok(())
+    }
+
+    fn propose(
+        &mut self,
+        calls: Vec<HexString>,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        self.ensure_member(context, state)?;
+        if calls.is_empty() || calls.len() > MAX_CALLS {
+            bail!(
+                "A proposal must have between 1 and {MAX_CALLS} calls, got {}",
+                calls.len()
+            );
+        }
+
+        let id = self.next_proposal_id.get(state)?.unwrap_or_default();
+        self.next_proposal_id.set(&(id + 1), state)?;
+        let mut proposal = Proposal {
+            proposer: context.sender().clone(),
+            calls,
+            approvals: vec![context.sender().clone()],
+            status: ProposalStatus::Pending,
+        };
+        self.emit_event(
+            state,
+            Event::ProposalCreated {
+                id,
+                proposer: proposal.proposer.clone(),
+                calls: proposal.calls.len() as u32,
+            },
+        );
+        self.queue_if_passed(id, &mut proposal, state)?;
+        self.proposals.set(&id, &proposal, state)?;
+        Ok(())
+    }
+
+    fn approve(
+        &mut self,
+        id: u64,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        self.ensure_member(context, state)?;
+        let mut proposal = self.proposal(id, state)?;
+        if proposal.status != ProposalStatus::Pending {
+            bail!("Proposal {id} is not pending approval");
+        }
+        if proposal.approvals.contains(context.sender()) {
+            bail!("{} already approved proposal {id}", context.sender());
+        }
+
+        proposal.approvals.push(context.sender().clone());
+        self.emit_event(
+            state,
+            Event::ProposalApproved {
+                id,
+                member: context.sender().clone(),
+            },
+        );
+        self.queue_if_passed(id, &mut proposal, state)?;
+        self.proposals.set(&id, &proposal, state)?;
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        id: u64,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let mut proposal = self.proposal(id, state)?;
+        let height = self.chain_state.rollup_height(state)?.get();
+        match proposal.status {
+            ProposalStatus::Queued { eta } if eta <= height => {}
+            ProposalStatus::Queued { eta } => {
+                bail!("Proposal {id} can't be executed before rollup height {eta}")
+            }
+            _ => bail!("Proposal {id} is not queued"),
+        }
+        self.run(id, &mut proposal, height, context, state)
+    }
+
+    /// Returns the queued proposals whose timelock has passed and whose calls can all run at the
+    /// end of a block, in the order they were queued. The runtime executes them at the end of
+    /// every rollup block with [`Governance::execute_due_proposal`].
+    pub fn due_proposals(&self, state: &mut impl TxState<S>) -> Result<Vec<u64>> {
+        let height = self.chain_state.rollup_height(state)?.get();
+        let mut due = Vec::new();
+        for id in self.queued.get(state)?.unwrap_or_default() {
+            let proposal = self.proposal(id, state)?;
+            match proposal.status {
+                ProposalStatus::Queued { eta } if eta <= height => {}
+                _ => continue,
+            }
+            if proposal
+                .calls
+                .iter()
+                .all(|call| R::runs_at_end_of_block(&call.0))
+            {
+                due.push(id);
+            }
+        }
+        Ok(due)
+    }
+
+    /// Executes the due proposal `id` at the end of a block, with a `context` whose sequencer is
+    /// the preferred sequencer.
+    ///
+    /// Block hooks don't revert the changes of a failing call, so the runtime runs each proposal
+    /// in a scratchpad, and reverts it if this fails. The proposal is then marked as failed with
+    /// [`Governance::fail`]. [`CallMessage::Execute`] runs a due proposal in a transaction instead,
+    /// which reverts as a whole.
+    pub fn execute_due_proposal(
+        &mut self,
+        id: u64,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let height = self.chain_state.rollup_height(state)?.get();
+        let mut proposal = self.proposal(id, state)?;
+        self.run(id, &mut proposal, height, context, state)
+    }
+
+    /// Marks the queued proposal `id`, whose execution failed with `reason`, as
+    /// [`ProposalStatus::Failed`]. It isn't retried.
+    pub fn fail(&mut self, id: u64, reason: String, state: &mut impl TxState<S>) -> Result<()> {
+        let height = self.chain_state.rollup_height(state)?.get();
+        let mut proposal = self.proposal(id, state)?;
+        proposal.status = ProposalStatus::Failed { height };
+        self.proposals.set(&id, &proposal, state)?;
+        self.unqueue(id, state)?;
+        self.emit_event(state, Event::ProposalFailed { id, height, reason });
+        Ok(())
+    }
+
+    /// Runs the calls of a due proposal from the governance account.
+    fn run(
+        &mut self,
+        id: u64,
+        proposal: &mut Proposal<S>,
+        height: u64,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        if !self.passes(proposal, state)? {
+            bail!("Proposal {id} no longer has the approval of the council");
+        }
+
+        // The proposal is marked as executed first, so that its calls can't execute it again.
+        proposal.status = ProposalStatus::Executed { height };
+        self.proposals.set(&id, proposal, state)?;
+        self.unqueue(id, state)?;
+
+        let governance_context = Context::new(
+            Self::address(),
+            Credentials::new(Self::address()),
+            context.sequencer().clone(),
+            context.sequencer_da_address().clone(),
+        );
+        for (index, call) in proposal.calls.iter().enumerate() {
+            R::execute(&call.0, &governance_context, state)
+                .with_context(|| format!("Call {index} of proposal {id} failed"))?;
+        }
+
+        self.emit_event(state, Event::ProposalExecuted { id, height });
+        Ok(())
+    }
+
+    fn unqueue(&mut self, id: u64, state: &mut impl TxState<S>) -> Result<()> {
+        let mut queued = self.queued.get(state)?.unwrap_or_default();
+        queued.retain(|queued_id| *queued_id != id);
+        self.queued.set(&queued, state)?;
+        Ok(())
+    }
+
+    fn cancel(&mut self, id: u64, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let mut proposal = self.proposal(id, state)?;
+        // The council acts through proposals, from the governance account.
+        let by_council_or_guardian = context.sender() == &Self::address()
+            || self.guardian.get(state)?.as_ref() == Some(context.sender());
+        match proposal.status {
+            ProposalStatus::Pending => {
+                if &proposal.proposer != context.sender() && !by_council_or_guardian {
+                    bail!(
+                        "Only the proposer {}, the council or the guardian can cancel proposal {id}",
+                        proposal.proposer
+                    );
+                }
+            }
+            // Once the council has approved a proposal, its proposer alone can't withdraw it.
+            ProposalStatus::Queued { .. } => {
+                if !by_council_or_guardian {
+                    bail!("Only the council or the guardian can cancel queued proposal {id}");
+                }
+                self.unqueue(id, state)?;
+            }
+            _ => bail!("Proposal {id} can no longer be cancelled"),
+        }
+
+        proposal.status = ProposalStatus::Cancelled;
+        self.proposals.set(&id, &proposal, state)?;
+        self.emit_event(state, Event::ProposalCancelled { id });
+        Ok(())
+    }
+}
+
+impl<S: Spec, R: GovernanceRuntime<S>> Module for Governance<S, R> {
+    type Spec = S;
+
+    type Config = GovernanceConfig<S>;
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        config.council.validate()?;
+        self.council.set(&config.council, state)?;
+        self.timelock.set(&config.timelock, state)?;
+        if let Some(guardian) = &config.guardian {
+            self.guardian.set(guardian, state)?;
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::Propose { calls } => self.propose(calls, context, state),
+            CallMessage::Approve { id } => self.approve(id, context, state),
+            CallMessage::Execute { id } => self.execute(id, context, state),
+            CallMessage::Cancel { id } => self.cancel(id, context, state),
+            CallMessage::SetCouncil { council } => {
+                self.ensure_governance(context)?;
+                council.validate()?;
+                self.council.set(&council, state)?;
+                self.emit_event(state, Event::CouncilChanged { council });
+                Ok(())
+            }
+            CallMessage::SetTimelock { timelock } => {
+                self.ensure_governance(context)?;
+                self.timelock.set(&timelock, state)?;
+                self.emit_event(state, Event::TimelockChanged { timelock });
+                Ok(())
+            }
+            CallMessage::SetGuardian { guardian } => {
+                self.ensure_governance(context)?;
+                match &guardian {
+                    Some(guardian) => self.guardian.set(guardian, state)?,
+                    None => self.guardian.remove(state)?,
+                }
+                self.emit_event(state, Event::GuardianChanged { guardian });
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Proposes borsh encoded runtime calls, approved by the sender. Council only.
+    Propose { calls: Vec<HexString> },
+    /// Approves a pending proposal. Council only.
+    Approve { id: u64 },
+    /// Executes a queued proposal once its timelock has passed, without waiting for the end of
+    /// the block. Anyone can execute proposals; the caller pays for their execution.
+    Execute { id: u64 },
+    /// Cancels a proposal that wasn't executed. The proposer can cancel a pending proposal, and
+    /// the council, through a proposal, or the guardian can cancel any pending or queued one.
+    Cancel { id: u64 },
+    /// Replaces the council. Only through a proposal.
+    SetCouncil { council: Council<S> },
+    /// Replaces the timelock of the proposals queued from now on. Only through a proposal.
+    SetTimelock { timelock: u64 },
+    /// Replaces or removes the guardian. Only through a proposal.
+    SetGuardian { guardian: Option<S::Address> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// A proposal was made.
+    ProposalCreated {
+        id: u64,
+        proposer: S::Address,
+        calls: u32,
+    },
+    /// A council member approved a proposal.
+    ProposalApproved { id: u64, member: S::Address },
+    /// A proposal was approved by the council, and can be executed from rollup height `eta`.
+    ProposalQueued { id: u64, eta: u64 },
+    /// The calls of a proposal were executed.
+    ProposalExecuted { id: u64, height: u64 },
+    /// The automatic execution of a proposal failed.
+    ProposalFailed {
+        id: u64,
+        height: u64,
+        reason: String,
+    },
+    /// A proposal was cancelled.
+    ProposalCancelled { id: u64 },
+    /// The council was replaced.
+    CouncilChanged { council: Council<S> },
+    /// The timelock was replaced.
+    TimelockChanged { timelock: u64 },
+    /// The guardian was replaced or removed.
+    GuardianChanged { guardian: Option<S::Address> },
+}
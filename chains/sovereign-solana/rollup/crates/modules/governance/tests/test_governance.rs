@@ -0,0 +1,614 @@
+use governance::{
+    CallMessage, Council, Event, GovernanceConfig, GovernanceRuntime, ProposalStatus,
+};
+use schemars::JsonSchema;
+use sov_hyperlane_integration::HexString;
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+/// The module tests don't have a full runtime, so proposals can only call the governance module
+/// itself.
+#[derive(Clone, Default)]
+pub struct SelfGoverned;
+
+impl<S: Spec> GovernanceRuntime<S> for SelfGoverned {
+    fn execute(
+        call: &[u8],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        let call: CallMessage<S> = borsh::from_slice(call)?;
+        governance::Governance::<S, SelfGoverned>::default().call(call, context, state)
+    }
+
+    /// Guardian changes stand in for the runtime calls that must run in a transaction.
+    fn runs_at_end_of_block(call: &[u8]) -> bool {
+        !matches!(
+            borsh::from_slice::<CallMessage<S>>(call),
+            Ok(CallMessage::SetGuardian { .. })
+        )
+    }
+}
+
+type Governance = governance::Governance<S, SelfGoverned>;
+
+/// Executes the due proposals the way the runtime does at the end of every rollup block, except
+/// that the calls of a failing proposal are reverted with the whole transaction.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct EndOfBlock<S: Spec> {
+    #[id]
+    pub id: ModuleId,
+
+    #[module]
+    pub governance: governance::Governance<S, SelfGoverned>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum EndOfBlockCall {
+    ExecuteDue,
+}
+
+impl<S: Spec> Module for EndOfBlock<S> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = EndOfBlockCall;
+
+    type Event = ();
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        _config: &Self::Config,
+        _state: &mut impl GenesisState<S>,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        match msg {
+            EndOfBlockCall::ExecuteDue => {
+                for id in self.governance.due_proposals(state)? {
+                    if let Err(e) = self.governance.execute_due_proposal(id, context, state) {
+                        self.governance.fail(id, format!("{e:#}"), state)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    governance: Governance,
+    end_of_block: EndOfBlock<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub members: Vec<TestUser<S>>,
+    /// Not a council member, but the guardian.
+    pub outsider: TestUser<S>,
+}
+
+/// Sets up a runtime with three council members, built by `council`, and an outsider who is the
+/// guardian.
+pub fn setup(
+    council: impl FnOnce(Vec<<S as Spec>::Address>) -> Council<S>,
+    timelock: u64,
+) -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(4);
+
+    let mut members = genesis_config.additional_accounts().to_vec();
+    let outsider = members.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        GovernanceConfig {
+            council: council(members.iter().map(|member| member.address()).collect()),
+            timelock,
+            guardian: Some(outsider.address()),
+        },
+        (),
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { members, outsider }, runner)
+}
+
+fn two_of_three(members: Vec<<S as Spec>::Address>) -> Council<S> {
+    Council::Multisig {
+        members,
+        threshold: 2,
+    }
+}
+
+/// Proposes to change the timelock to `timelock` from `members[0]`, and approves it from
+/// `members[1]`, so that it is queued.
+fn queue_set_timelock(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>) {
+    for (member, msg) in [
+        (&test_data.members[0], set_timelock(5)),
+        (&test_data.members[1], CallMessage::Approve { id: 0 }),
+    ] {
+        runner.execute_transaction(TransactionTestCase {
+            input: member.create_plain_message::<TestRuntime<S>, Governance>(msg),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_successful());
+            }),
+        });
+    }
+}
+
+/// A proposal changing the timelock to `timelock`.
+fn set_timelock(timelock: u64) -> CallMessage<S> {
+    CallMessage::Propose {
+        calls: vec![HexString(
+            borsh::to_vec(&CallMessage::<S>::SetTimelock { timelock }).unwrap(),
+        )],
+    }
+}
+
+fn status(runner: &mut TestRunner<TestRuntime<S>, S>, id: u64) -> ProposalStatus {
+    runner.query_state(|state| {
+        Governance::default()
+            .proposals
+            .get(&id, state)
+            .unwrap()
+            .unwrap()
+            .status
+    })
+}
+
+#[test]
+fn test_approved_proposal_is_executed() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(set_timelock(5)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(result.events.len(), 1);
+        }),
+    });
+    assert_eq!(status(&mut runner, 0), ProposalStatus::Pending);
+
+    // The second approval queues the proposal.
+    let member = test_data.members[1].address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[1]
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Approve { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+            let [TestRuntimeEvent::Governance(Event::ProposalApproved {
+                id: 0,
+                member: approver,
+            }), TestRuntimeEvent::Governance(Event::ProposalQueued { id: 0, .. })] =
+                result.events.as_slice()
+            else {
+                panic!("Unexpected events: {:?}", result.events);
+            };
+            assert_eq!(approver, &member);
+        }),
+    });
+
+    // Anyone can execute it.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Execute { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result.events.contains(&TestRuntimeEvent::Governance(
+                Event::TimelockChanged { timelock: 5 }
+            )));
+            assert_eq!(Governance::default().timelock.get(state).unwrap(), Some(5));
+        }),
+    });
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Executed { .. }
+    ));
+
+    // A proposal is executed only once.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Execute { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_proposal_waits_for_timelock() {
+    let (test_data, mut runner) = setup(two_of_three, 100);
+
+    queue_set_timelock(&test_data, &mut runner);
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Queued { .. }
+    ));
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Execute { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            assert_eq!(
+                Governance::default().timelock.get(state).unwrap(),
+                Some(100)
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_pending_proposal_cannot_be_executed() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(set_timelock(5)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Execute { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_only_members_propose_and_approve() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(set_timelock(5)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(set_timelock(5)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Approve { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    // Approving twice doesn't count twice.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Approve { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_settings_change_only_through_proposals() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0].create_plain_message::<TestRuntime<S>, Governance>(
+            CallMessage::SetTimelock { timelock: 5 },
+        ),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_proposer_cancels_proposal() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(set_timelock(5)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[1]
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Cancel { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Cancel { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+    assert_eq!(status(&mut runner, 0), ProposalStatus::Cancelled);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[1]
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Approve { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_due_proposals_are_executed_at_the_end_of_the_block() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    queue_set_timelock(&test_data, &mut runner);
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, EndOfBlock<S>>(EndOfBlockCall::ExecuteDue),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result.events.contains(&TestRuntimeEvent::Governance(
+                Event::TimelockChanged { timelock: 5 }
+            )));
+            assert_eq!(Governance::default().timelock.get(state).unwrap(), Some(5));
+            assert_eq!(
+                Governance::default().queued.get(state).unwrap(),
+                Some(Vec::new())
+            );
+        }),
+    });
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Executed { .. }
+    ));
+}
+
+#[test]
+fn test_proposals_are_not_executed_before_their_timelock() {
+    let (test_data, mut runner) = setup(two_of_three, 100);
+
+    queue_set_timelock(&test_data, &mut runner);
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, EndOfBlock<S>>(EndOfBlockCall::ExecuteDue),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                Governance::default().timelock.get(state).unwrap(),
+                Some(100)
+            );
+        }),
+    });
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Queued { .. }
+    ));
+}
+
+#[test]
+fn test_proposals_left_to_transactions_wait_for_execute() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    for (member, msg) in [
+        (
+            &test_data.members[0],
+            CallMessage::Propose {
+                calls: vec![HexString(
+                    borsh::to_vec(&CallMessage::<S>::SetGuardian { guardian: None }).unwrap(),
+                )],
+            },
+        ),
+        (&test_data.members[1], CallMessage::Approve { id: 0 }),
+    ] {
+        runner.execute_transaction(TransactionTestCase {
+            input: member.create_plain_message::<TestRuntime<S>, Governance>(msg),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_successful());
+            }),
+        });
+    }
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, EndOfBlock<S>>(EndOfBlockCall::ExecuteDue),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Queued { .. }
+    ));
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Execute { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(Governance::default().guardian.get(state).unwrap(), None);
+        }),
+    });
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Executed { .. }
+    ));
+}
+
+#[test]
+fn test_failed_proposal_is_not_retried() {
+    let (test_data, mut runner) = setup(two_of_three, 0);
+
+    // Setting the council to an empty one fails validation.
+    let calls = vec![HexString(
+        borsh::to_vec(&CallMessage::<S>::SetCouncil {
+            council: Council::Multisig {
+                members: Vec::new(),
+                threshold: 1,
+            },
+        })
+        .unwrap(),
+    )];
+    for (member, msg) in [
+        (&test_data.members[0], CallMessage::Propose { calls }),
+        (&test_data.members[1], CallMessage::Approve { id: 0 }),
+    ] {
+        runner.execute_transaction(TransactionTestCase {
+            input: member.create_plain_message::<TestRuntime<S>, Governance>(msg),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_successful());
+            }),
+        });
+    }
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, EndOfBlock<S>>(EndOfBlockCall::ExecuteDue),
+        assert: Box::new(move |result, _state| {
+            // A failed proposal doesn't stop the block.
+            assert!(result.tx_receipt.is_successful());
+            assert!(result.events.iter().any(|event| matches!(
+                event,
+                TestRuntimeEvent::Governance(Event::ProposalFailed { id: 0, .. })
+            )));
+        }),
+    });
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Failed { .. }
+    ));
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Execute { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_queued_proposal_is_cancelled_by_the_guardian_only() {
+    let (test_data, mut runner) = setup(two_of_three, 100);
+
+    queue_set_timelock(&test_data, &mut runner);
+
+    // The council approved the proposal, so its proposer alone can't withdraw it.
+    for member in &test_data.members {
+        runner.execute_transaction(TransactionTestCase {
+            input: member
+                .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Cancel { id: 0 }),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_reverted());
+            }),
+        });
+    }
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .outsider
+            .create_plain_message::<TestRuntime<S>, Governance>(CallMessage::Cancel { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                Governance::default().queued.get(state).unwrap(),
+                Some(Vec::new())
+            );
+        }),
+    });
+    assert_eq!(status(&mut runner, 0), ProposalStatus::Cancelled);
+}
+
+#[test]
+fn test_token_weighted_council() {
+    // Every member holds some gas tokens, but none holds this many.
+    let (test_data, mut runner) = setup(
+        |members| Council::TokenWeighted {
+            members,
+            token_id: config_value!("GAS_TOKEN_ID"),
+            threshold: Amount::new(u128::MAX),
+        },
+        0,
+    );
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(set_timelock(5)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+    assert_eq!(status(&mut runner, 0), ProposalStatus::Pending);
+
+    // With a threshold of one token, the proposer's approval is enough.
+    let (test_data, mut runner) = setup(
+        |members| Council::TokenWeighted {
+            members,
+            token_id: config_value!("GAS_TOKEN_ID"),
+            threshold: Amount::new(1),
+        },
+        0,
+    );
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.members[0]
+            .create_plain_message::<TestRuntime<S>, Governance>(set_timelock(5)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+    assert!(matches!(
+        status(&mut runner, 0),
+        ProposalStatus::Queued { .. }
+    ));
+}
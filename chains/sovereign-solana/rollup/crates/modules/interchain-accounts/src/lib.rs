@@ -0,0 +1,364 @@
+//! Hyperlane Interchain Accounts.
+//!
+//! An owner on a remote chain controls an account on the rollup by sending Hyperlane messages
+//! through the interchain accounts router of its chain. The account is derived from the origin
+//! domain and the owner, so nobody else can act on its behalf. Each message carries a list of
+//! borsh encoded runtime calls, which are executed atomically with the derived account as sender.
+//!
+//! The reverse direction works the same way: [`CallMessage::CallRemote`] sends a message to the
+//! router enrolled for the destination domain, which executes the calls from the account it
+//! derives for the rollup sender.
+use anyhow::{bail, Context as _, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use schemars::JsonSchema;
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress, Ism};
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, CryptoSpec, EventEmitter, GenesisState, Module, ModuleId,
+    ModuleInfo, ModuleRestApi, Spec, StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+use std::marker::PhantomData;
+
+/// The maximum number of runtime calls a single message can carry.
+pub const MAX_CALLS: usize = 16;
+
+/// Runtime hooks used by [`InterchainAccounts`]. The module can't depend on the runtime it is
+/// part of, so decoding and dispatching runtime calls and Hyperlane messages is delegated.
+pub trait IcaRuntime<S: Spec>: Clone + Default + Send + Sync + 'static {
+    /// Decodes a borsh encoded runtime call and executes it with `context.sender()` as sender.
+    fn execute(call: &[u8], context: &Context<S>, state: &mut impl TxState<S>) -> Result<()>;
+
+    /// Dispatches a Hyperlane message from `sender`, paying for delivery from `context.sender()`.
+    /// Returns the message ID.
+    fn dispatch(
+        destination: u32,
+        sender: HexHash,
+        recipient: HexHash,
+        body: HexString,
+        gas_payment_limit: Amount,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<HexHash>;
+}
+
+/// The body of an interchain accounts message, in both directions.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct IcaMessage {
+    /// The account on the origin chain that controls the interchain account.
+    pub owner: HexHash,
+    /// The calls to execute, encoded as expected by the destination chain. On the rollup, each
+    /// entry is a borsh encoded `RuntimeCall`.
+    pub calls: Vec<Vec<u8>>,
+}
+
+/// A remote interchain accounts router.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct RemoteRouter {
+    /// The Hyperlane domain of the remote chain.
+    pub domain: u32,
+    /// The address of the router on the remote chain.
+    pub router: HexHash,
+}
+
+/// The remote owner of an interchain account.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct RemoteOwner {
+    /// The Hyperlane domain the owner lives on.
+    pub origin: u32,
+    /// The router the owner's messages were sent through.
+    pub router: HexHash,
+    /// The owner's address on that domain.
+    pub owner: HexHash,
+}
+
+/// Genesis configuration of the [`InterchainAccounts`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "InterchainAccountsConfig")]
+pub struct InterchainAccountsConfig<S: Spec> {
+    /// The account allowed to enroll and unenroll remote routers.
+    pub admin: S::Address,
+    /// The remote routers trusted at genesis.
+    pub routers: Vec<RemoteRouter>,
+    /// The ISM verifying inbound interchain accounts messages.
+    pub ism: Ism,
+}
+
+/// Executes calls from remote owners through derived rollup accounts, and sends calls to
+/// accounts on remote chains.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct InterchainAccounts<S: Spec, R: IcaRuntime<S>> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The account allowed to manage remote routers.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The trusted router of each remote domain.
+    #[state]
+    pub routers: StateMap<u32, HexHash>,
+
+    /// The ISM verifying inbound messages.
+    #[state]
+    pub ism: StateValue<Ism>,
+
+    /// The remote owner of every interchain account that has executed calls.
+    #[state]
+    pub accounts: StateMap<S::Address, RemoteOwner>,
+
+    #[phantom]
+    phantom: PhantomData<R>,
+}
+
+impl<S: Spec, R: IcaRuntime<S>> InterchainAccounts<S, R>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// The Hyperlane address of the local router. Remote routers must send messages to it and
+    /// outbound messages are sent from it.
+    pub fn router_address() -> HexHash {
+        HexString(hash::<S>(&[b"interchain_accounts_router"]))
+    }
+
+    /// Returns the rollup account controlled by `owner` on the `origin` domain, through `router`.
+    ///
+    /// The router is part of the derivation, so enrolling a different router for a domain doesn't
+    /// hand the accounts of the previous one over to it.
+    pub fn derived_account(origin: u32, router: &HexHash, owner: &HexHash) -> S::Address {
+        CredentialId(hash::<S>(&[
+            b"interchain_accounts",
+            &origin.to_le_bytes(),
+            &router.0,
+            &owner.0,
+        ]))
+        .into()
+    }
+
+    /// Returns the ISM that must verify messages sent to the local router.
+    pub fn ism(&self, state: &mut impl StateReader<User>) -> Result<Option<Ism>> {
+        Ok(self.ism.get(state)?)
+    }
+
+    /// Handles an inbound message: checks that it comes from the router enrolled for `origin`,
+    /// then executes its calls from the account derived for the owner. Any failing call reverts
+    /// the whole message.
+    pub fn handle(
+        &mut self,
+        origin: u32,
+        sender: HexHash,
+        body: &[u8],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match self.routers.get(&origin, state)? {
+            Some(router) if router == sender => {}
+            Some(_) => bail!("Sender {sender} is not the router enrolled for domain {origin}"),
+            None => bail!("No interchain accounts router enrolled for domain {origin}"),
+        }
+
+        let message = IcaMessage::try_from_slice(body)
+            .context("Failed to decode interchain accounts message")?;
+        if message.calls.len() > MAX_CALLS {
+            bail!(
+                "A message can carry at most {MAX_CALLS} calls, got {}",
+                message.calls.len()
+            );
+        }
+
+        let account = Self::derived_account(origin, &sender, &message.owner);
+        let owner = RemoteOwner {
+            origin,
+            router: sender,
+            owner: message.owner,
+        };
+        if self.accounts.get(&account, state)?.is_none() {
+            self.accounts.set(&account, &owner, state)?;
+        }
+
+        let account_context = Context::new(
+            account.clone(),
+            context.get_sender_credentials().clone(),
+            context.sequencer().clone(),
+            context.sequencer_da_address().clone(),
+        );
+        for (index, call) in message.calls.iter().enumerate() {
+            R::execute(call, &account_context, state)
+                .with_context(|| format!("Interchain account call {index} failed"))?;
+        }
+
+        self.emit_event(
+            state,
+            Event::CallsExecuted {
+                account,
+                origin: owner.origin,
+                owner: owner.owner,
+                calls: message.calls.len() as u32,
+            },
+        );
+        Ok(())
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .context("The interchain accounts admin is not set")?;
+        if &admin != context.sender() {
+            bail!("Only the admin {admin} can manage interchain accounts routers");
+        }
+        Ok(())
+    }
+}
+
+fn hash<S: Spec>(parts: &[&[u8]]) -> [u8; 32] {
+    use sov_modules_api::digest::Digest;
+
+    let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+impl<S: Spec, R: IcaRuntime<S>> Module for InterchainAccounts<S, R>
+where
+    S::Address: HyperlaneAddress,
+{
+    type Spec = S;
+
+    type Config = InterchainAccountsConfig<S>;
+
+    type CallMessage = CallMessage;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.admin.set(&config.admin, state)?;
+        self.ism.set(&config.ism, state)?;
+        for RemoteRouter { domain, router } in &config.routers {
+            self.routers.set(domain, router, state)?;
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::EnrollRemoteRouter(RemoteRouter { domain, router }) => {
+                self.ensure_admin(context, state)?;
+                self.routers.set(&domain, &router, state)?;
+                self.emit_event(state, Event::RouterEnrolled { domain, router });
+                Ok(())
+            }
+            CallMessage::UnenrollRemoteRouter { domain } => {
+                self.ensure_admin(context, state)?;
+                if self.routers.get(&domain, state)?.is_none() {
+                    bail!("No interchain accounts router enrolled for domain {domain}");
+                }
+                self.routers.remove(&domain, state)?;
+                self.emit_event(state, Event::RouterUnenrolled { domain });
+                Ok(())
+            }
+            CallMessage::CallRemote {
+                destination,
+                calls,
+                gas_payment_limit,
+            } => {
+                if calls.is_empty() || calls.len() > MAX_CALLS {
+                    bail!(
+                        "A message must carry between 1 and {MAX_CALLS} calls, got {}",
+                        calls.len()
+                    );
+                }
+                let Some(router) = self.routers.get(&destination, state)? else {
+                    bail!("No interchain accounts router enrolled for domain {destination}");
+                };
+
+                let owner = context.sender().to_sender();
+                let body = IcaMessage {
+                    owner: owner.clone(),
+                    calls: calls.into_iter().map(|call| call.0).collect(),
+                };
+                let message_id = R::dispatch(
+                    destination,
+                    Self::router_address(),
+                    router,
+                    HexString(borsh::to_vec(&body)?),
+                    gas_payment_limit,
+                    context,
+                    state,
+                )?;
+
+                self.emit_event(
+                    state,
+                    Event::RemoteCallDispatched {
+                        destination,
+                        owner,
+                        message_id,
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum CallMessage {
+    /// Trusts `router` to relay calls from owners on `domain`. Admin only.
+    EnrollRemoteRouter(RemoteRouter),
+    /// Stops accepting calls from `domain`. Admin only.
+    UnenrollRemoteRouter { domain: u32 },
+    /// Executes `calls` on `destination` from the sender's interchain account there.
+    /// The calls are encoded as expected by the remote router.
+    CallRemote {
+        destination: u32,
+        calls: Vec<HexString>,
+        gas_payment_limit: Amount,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// A remote router was enrolled or replaced.
+    RouterEnrolled { domain: u32, router: HexHash },
+    /// A remote router was removed.
+    RouterUnenrolled { domain: u32 },
+    /// An inbound message was executed by an interchain account.
+    CallsExecuted {
+        account: S::Address,
+        origin: u32,
+        owner: HexHash,
+        calls: u32,
+    },
+    /// Calls were sent to the sender's interchain account on a remote chain.
+    RemoteCallDispatched {
+        destination: u32,
+        owner: HexHash,
+        message_id: HexHash,
+    },
+}
@@ -0,0 +1,195 @@
+use interchain_accounts::{
+    CallMessage, Event, IcaRuntime, InterchainAccounts, InterchainAccountsConfig, RemoteRouter,
+};
+use sov_hyperlane_integration::{HexHash, HexString, Ism};
+use sov_modules_api::{Amount, Context, Spec, TxState};
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+/// The module tests don't exercise execution and dispatch, which need a full runtime.
+#[derive(Clone, Default)]
+pub struct UnreachableIca;
+
+impl IcaRuntime<S> for UnreachableIca {
+    fn execute(
+        _call: &[u8],
+        _context: &Context<S>,
+        _state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("Not available in module tests")
+    }
+
+    fn dispatch(
+        _destination: u32,
+        _sender: HexHash,
+        _recipient: HexHash,
+        _body: HexString,
+        _gas_payment_limit: Amount,
+        _context: &Context<S>,
+        _state: &mut impl TxState<S>,
+    ) -> anyhow::Result<HexHash> {
+        anyhow::bail!("Not available in module tests")
+    }
+}
+
+type Ica = InterchainAccounts<S, UnreachableIca>;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    interchain_accounts: Ica
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+const SOLANA_DOMAIN: u32 = 1337;
+
+pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+fn ism() -> Ism {
+    serde_json::from_value(serde_json::json!({
+        "MessageIdMultisig": {
+            "threshold": 1,
+            "validators": ["0x2c25Ab04F9cD2beC3D98921b02AFBE54B792cad0"]
+        }
+    }))
+    .unwrap()
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        InterchainAccountsConfig {
+            admin: admin.address(),
+            routers: Vec::new(),
+            ism: ism(),
+        },
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { admin, other_user }, runner)
+}
+
+fn router() -> HexHash {
+    HexString([9; 32])
+}
+
+#[test]
+fn test_admin_enrolls_and_unenrolls_router() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.admin.create_plain_message::<TestRuntime<S>, Ica>(
+            CallMessage::EnrollRemoteRouter(RemoteRouter {
+                domain: SOLANA_DOMAIN,
+                router: router(),
+            }),
+        ),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::InterchainAccounts(
+                    Event::RouterEnrolled {
+                        domain: SOLANA_DOMAIN,
+                        router: router(),
+                    }
+                )]
+            );
+            assert_eq!(
+                Ica::default().routers.get(&SOLANA_DOMAIN, state).unwrap(),
+                Some(router())
+            );
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data.admin.create_plain_message::<TestRuntime<S>, Ica>(
+            CallMessage::UnenrollRemoteRouter {
+                domain: SOLANA_DOMAIN,
+            },
+        ),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                Ica::default().routers.get(&SOLANA_DOMAIN, state).unwrap(),
+                None
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_only_admin_enrolls_routers() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Ica>(CallMessage::EnrollRemoteRouter(
+                RemoteRouter {
+                    domain: SOLANA_DOMAIN,
+                    router: router(),
+                },
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_call_remote_requires_enrolled_router() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Ica>(CallMessage::CallRemote {
+                destination: SOLANA_DOMAIN,
+                calls: vec![HexString(vec![1, 2, 3])],
+                gas_payment_limit: Amount::new(1_000),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_derived_account_depends_on_origin_router_and_owner() {
+    let router = HexString([3; 32]);
+    let owner = HexString([1; 32]);
+    let account = Ica::derived_account(SOLANA_DOMAIN, &router, &owner);
+
+    assert_eq!(
+        account,
+        Ica::derived_account(SOLANA_DOMAIN, &router, &owner)
+    );
+    assert_ne!(
+        account,
+        Ica::derived_account(SOLANA_DOMAIN + 1, &router, &owner)
+    );
+    assert_ne!(
+        account,
+        Ica::derived_account(SOLANA_DOMAIN, &HexString([4; 32]), &owner)
+    );
+    assert_ne!(
+        account,
+        Ica::derived_account(SOLANA_DOMAIN, &router, &HexString([2; 32]))
+    );
+}
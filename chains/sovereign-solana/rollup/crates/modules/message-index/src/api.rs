@@ -0,0 +1,227 @@
+//! REST endpoints serving the message index.
+use sov_hyperlane_integration::{HexHash, HyperlaneAddress};
+use sov_modules_api::prelude::axum::extract::{Path, Query, State};
+use sov_modules_api::prelude::axum::http::StatusCode;
+use sov_modules_api::prelude::axum::routing::get;
+use sov_modules_api::prelude::axum::{self, Json};
+use sov_modules_api::rest::ApiState;
+use sov_modules_api::Spec;
+
+use crate::merkle::{CheckpointWithMessageId, MerkleProof};
+use crate::{MessageIndex, MessageRecord, MirrorMismatch};
+
+/// The path of a message, by message ID.
+pub const MESSAGE_PATH: &str = "/hyperlane/messages/:id";
+
+/// The path of the messages dispatched by the transactions of a sender.
+pub const SENDER_MESSAGES_PATH: &str = "/hyperlane/senders/:sender/messages";
+
+/// The path of the root and the leaf count of the `MerkleTreeHook`.
+pub const MERKLE_TREE_PATH: &str = "/hyperlane/merkle-tree";
+
+/// The path of the latest checkpoint of the merkle tree.
+pub const LATEST_CHECKPOINT_PATH: &str = "/hyperlane/merkle-tree/checkpoints/latest";
+
+/// The path of the inclusion proof of a leaf, by leaf index.
+pub const PROOF_PATH: &str = "/hyperlane/merkle-tree/proofs/:index";
+
+/// The maximum number of messages returned by a listing.
+pub const MAX_LIMIT: u64 = 100;
+
+/// An indexed message and its ID.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MessageResponse {
+    /// The message ID.
+    pub id: HexHash,
+    /// The indexed message.
+    #[serde(flatten)]
+    pub record: MessageRecord,
+}
+
+/// The range of a listing.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ListingQuery {
+    /// The number of messages skipped, oldest first.
+    #[serde(default)]
+    pub offset: u64,
+    /// The number of messages returned, at most [`MAX_LIMIT`].
+    pub limit: Option<u64>,
+}
+
+/// A page of the messages dispatched by the transactions of a sender.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SenderMessagesResponse {
+    /// The number of messages dispatched by the transactions of the sender.
+    pub total: u64,
+    /// The requested messages, oldest first.
+    pub messages: Vec<MessageResponse>,
+}
+
+/// The root and the leaf count of the `MerkleTreeHook`, and of the mirror of the index.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MerkleTreeResponse {
+    /// The current root of the `MerkleTreeHook`.
+    pub root: HexHash,
+    /// The number of leaves of the `MerkleTreeHook`, one per dispatched message.
+    pub count: u32,
+    /// The number of leaves of the mirror. Proofs are served while it holds the same leaves as
+    /// the `MerkleTreeHook`.
+    pub mirrored_count: u32,
+    /// Whether the mirror holds the same leaves as the `MerkleTreeHook`.
+    pub mirrored: bool,
+}
+
+/// An inclusion proof and the root it leads to.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProofResponse {
+    /// The current root of the `MerkleTreeHook`.
+    pub root: HexHash,
+    /// The proof of the leaf against `root`.
+    #[serde(flatten)]
+    pub proof: MerkleProof,
+}
+
+/// Returns an axum router serving the messages and the merkle tree of the index.
+pub fn axum_router<S: Spec>(api_state: ApiState<S>) -> axum::Router<()>
+where
+    S::Address: HyperlaneAddress,
+{
+    axum::Router::new()
+        .route(MESSAGE_PATH, get(message::<S>))
+        .route(SENDER_MESSAGES_PATH, get(sender_messages::<S>))
+        .route(MERKLE_TREE_PATH, get(merkle_tree::<S>))
+        .route(LATEST_CHECKPOINT_PATH, get(latest_checkpoint::<S>))
+        .route(PROOF_PATH, get(proof::<S>))
+        .with_state(api_state)
+}
+
+fn internal_error(e: anyhow::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"))
+}
+
+/// Maps a mirror that doesn't hold the leaves of the `MerkleTreeHook` to a 503.
+fn mirror_error(e: anyhow::Error) -> (StatusCode, String) {
+    match e.downcast_ref::<MirrorMismatch>() {
+        Some(mismatch) => (StatusCode::SERVICE_UNAVAILABLE, mismatch.to_string()),
+        None => internal_error(e),
+    }
+}
+
+async fn message<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+    Path(id): Path<HexHash>,
+) -> Result<Json<MessageResponse>, (StatusCode, String)> {
+    let mut state = api_state.default_api_state_accessor();
+    let record = MessageIndex::<S>::default()
+        .messages
+        .get(&id, &mut state)
+        .map_err(|e| internal_error(e.into()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Message {id} is not indexed"),
+            )
+        })?;
+    Ok(Json(MessageResponse { id, record }))
+}
+
+/// The sender is either a rollup address or its 32 byte Hyperlane form.
+async fn sender_messages<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+    Path(sender): Path<String>,
+    Query(query): Query<ListingQuery>,
+) -> Result<Json<SenderMessagesResponse>, (StatusCode, String)>
+where
+    S::Address: HyperlaneAddress,
+{
+    let sender_hash = match sender.parse::<S::Address>() {
+        Ok(address) => address.to_sender(),
+        Err(_) => serde_json::from_value::<HexHash>(serde_json::Value::String(sender.clone()))
+            .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid sender {sender}")))?,
+    };
+    let limit = query.limit.unwrap_or(MAX_LIMIT).min(MAX_LIMIT);
+
+    let mut state = api_state.default_api_state_accessor();
+    let index = MessageIndex::<S>::default();
+    let (total, ids) = index
+        .sender_messages(&sender_hash, query.offset, limit, &mut state)
+        .map_err(internal_error)?;
+
+    let mut messages = Vec::with_capacity(ids.len());
+    for id in ids {
+        let record = index
+            .messages
+            .get(&id, &mut state)
+            .map_err(|e| internal_error(e.into()))?;
+        if let Some(record) = record {
+            messages.push(MessageResponse { id, record });
+        }
+    }
+    Ok(Json(SenderMessagesResponse { total, messages }))
+}
+
+async fn merkle_tree<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+) -> Result<Json<MerkleTreeResponse>, (StatusCode, String)>
+where
+    S::Address: HyperlaneAddress,
+{
+    let mut state = api_state.default_api_state_accessor();
+    let index = MessageIndex::<S>::default();
+    let (root, count) = index.hook_tree(&mut state).map_err(internal_error)?;
+    let mirrored_count = index.tree_count(&mut state).map_err(internal_error)?;
+    let mirrored_root = index.tree_root(&mut state).map_err(internal_error)?;
+    Ok(Json(MerkleTreeResponse {
+        mirrored: mirrored_count == count && mirrored_root == root,
+        root,
+        count,
+        mirrored_count,
+    }))
+}
+
+/// The message ID of the checkpoint is read from the mirror, so it is only served while the
+/// mirror holds the leaves of the `MerkleTreeHook`.
+async fn latest_checkpoint<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+) -> Result<Json<CheckpointWithMessageId>, (StatusCode, String)>
+where
+    S::Address: HyperlaneAddress,
+{
+    let mut state = api_state.default_api_state_accessor();
+    let index = MessageIndex::<S>::default();
+    let (root, count) = index.mirrored_hook_tree(&mut state).map_err(mirror_error)?;
+    index
+        .checkpoint(root, count, &mut state)
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                "No message was dispatched".to_string(),
+            )
+        })
+}
+
+/// Proofs are built from the mirror, so they are only served while the mirror holds the leaves of
+/// the `MerkleTreeHook`, and they prove the leaf against the hook's root.
+async fn proof<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+    Path(leaf_index): Path<u32>,
+) -> Result<Json<ProofResponse>, (StatusCode, String)>
+where
+    S::Address: HyperlaneAddress,
+{
+    let mut state = api_state.default_api_state_accessor();
+    let index = MessageIndex::<S>::default();
+    let (root, _) = index.mirrored_hook_tree(&mut state).map_err(mirror_error)?;
+    let proof = index
+        .tree_proof(leaf_index, &mut state)
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Leaf {leaf_index} is not in the merkle tree"),
+            )
+        })?;
+    Ok(Json(ProofResponse { root, proof }))
+}
@@ -0,0 +1,614 @@
+//! A node-local index of the Hyperlane messages dispatched and delivered by the rollup, so that
+//! support tools can answer "did my transfer arrive?" without reading logs.
+//!
+//! The runtime records messages in a [`MessageJournal`] while it executes transactions: the
+//! messages dispatched by a call, and the message delivered by a mailbox `process` call, together
+//! with the rollup height. An inbound warp transfer held back by the warp limits module is
+//! recorded as queued under its transfer ID, and as delivered once the transfer is released. The messages of a transaction are only committed once the transaction
+//! is, so reverted transactions are not indexed. Accessory state can only be written once a slot
+//! is executed, so the runtime's `FinalizeHook` writes the committed messages with
+//! [`MessageIndex::flush`]. The index is not part of the state root: it covers the slots executed
+//! by the node itself.
+//!
+//! The index also mirrors the merkle tree of the `MerkleTreeHook`, which stores only the branch
+//! needed to append leaves, so that it can serve inclusion proofs for any message the rollup
+//! dispatched. The leaves are keyed by the nonce of their message, and the root and the leaf count
+//! are read from the `MerkleTreeHook` itself: the mirror only serves the proofs, and only while it
+//! holds the same leaves as the hook. See [`merkle`].
+use std::marker::PhantomData;
+
+use anyhow::Result;
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress, MerkleTreeHook};
+use sov_modules_api::macros::{config_value, serialize};
+use sov_modules_api::{
+    AccessoryStateMap, AccessoryStateReaderAndWriter, AccessoryStateValue, Context, GenesisState,
+    Module, ModuleId, ModuleInfo, ModuleRestApi, NotInstantiable, Spec, StateReader, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::{Accessory, User};
+
+#[cfg(feature = "native")]
+pub mod api;
+pub mod merkle;
+
+use merkle::{Checkpoint, CheckpointWithMessageId, MerkleProof, MERKLE_TREE_HOOK_ADDRESS};
+
+/// The maximum number of committed messages buffered between two flushes. Runtimes that execute
+/// transactions without finalizing slots, such as the sequencer's, drop the messages beyond it.
+pub const MAX_PENDING_REPORTS: usize = 10_000;
+
+/// Whether a message was sent or received by the rollup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Dispatched by the rollup.
+    Outbound,
+    /// Dispatched by a remote chain and delivered to the rollup.
+    Inbound,
+}
+
+/// Whether a message was processed by the rollup's mailbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageStatus {
+    /// Dispatched, and not delivered to the rollup. Outbound messages are delivered on their
+    /// destination chain, unless they are sent to the rollup itself.
+    Dispatched,
+    /// Processed by the mailbox at rollup height `height`, and queued by the warp limits module
+    /// as the transfer `transfer_id` of its route.
+    Queued { height: u64, transfer_id: u64 },
+    /// Delivered by a mailbox `process` call, or by the release of its queued transfer, at
+    /// rollup height `height`.
+    Delivered { height: u64 },
+    /// Queued, then cancelled by the warp limits guardian at rollup height `height`.
+    Cancelled { height: u64 },
+}
+
+/// An indexed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct MessageRecord {
+    /// Whether the message was sent or received by the rollup.
+    pub direction: Direction,
+    /// The domain of the chain that dispatched the message.
+    pub origin: u32,
+    /// The domain of the chain the message is sent to.
+    pub destination: u32,
+    /// The nonce of the message in the mailbox of its origin.
+    pub nonce: u32,
+    /// The sender of the message on its origin chain.
+    pub sender: HexHash,
+    /// The recipient of the message on its destination chain.
+    pub recipient: HexHash,
+    /// The rollup height the message was dispatched at, for outbound messages.
+    pub dispatch_height: Option<u64>,
+    /// Whether the message was processed by the rollup's mailbox.
+    pub status: MessageStatus,
+}
+
+/// The header fields of a Hyperlane message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageHeader {
+    /// The message ID, the keccak256 hash of the encoded message.
+    pub id: HexHash,
+    /// The nonce of the message in the mailbox of its origin.
+    pub nonce: u32,
+    /// The domain of the chain that dispatched the message.
+    pub origin: u32,
+    /// The sender of the message on its origin chain.
+    pub sender: HexHash,
+    /// The domain of the chain the message is sent to.
+    pub destination: u32,
+    /// The recipient of the message on its destination chain.
+    pub recipient: HexHash,
+}
+
+impl MessageHeader {
+    /// The length of an encoded message without its body.
+    pub const LEN: usize = 77;
+
+    /// Decodes the header of a message in the Hyperlane wire format: a version byte, followed by
+    /// the nonce, the origin, the sender, the destination, the recipient and the body.
+    pub fn decode(message: &[u8]) -> Option<Self> {
+        if message.len() < Self::LEN {
+            return None;
+        }
+        let u32_at = |at: usize| u32::from_be_bytes(message[at..at + 4].try_into().unwrap());
+        let hash_at = |at: usize| HexString(message[at..at + 32].try_into().unwrap());
+        Some(Self {
+            id: HexString(alloy_primitives::keccak256(message).0),
+            nonce: u32_at(1),
+            origin: u32_at(5),
+            sender: hash_at(9),
+            destination: u32_at(41),
+            recipient: hash_at(45),
+        })
+    }
+}
+
+/// A message recorded by the runtime and not written to the index yet.
+#[derive(Debug, Clone)]
+enum Report {
+    Dispatched {
+        header: MessageHeader,
+        height: u64,
+        tx_sender: HexHash,
+    },
+    Delivered {
+        header: MessageHeader,
+        height: u64,
+    },
+    Queued {
+        header: MessageHeader,
+        height: u64,
+        transfer_id: u64,
+    },
+    Released {
+        transfer_id: u64,
+        height: u64,
+    },
+    Cancelled {
+        transfer_id: u64,
+        height: u64,
+    },
+}
+
+/// The messages of the transaction being executed, and the messages of the committed
+/// transactions that are not written to the index yet.
+///
+/// The runtime owns the journal. It opens a transaction before dispatching it, records the
+/// messages of its call, and commits them once the transaction can no longer be reverted.
+#[derive(Debug, Clone, Default)]
+pub struct MessageJournal {
+    open: Vec<Report>,
+    committed: Vec<Report>,
+}
+
+impl MessageJournal {
+    /// Opens a transaction, dropping the messages of the previous one if it wasn't committed.
+    pub fn begin(&mut self) {
+        self.open.clear();
+    }
+
+    /// Records an encoded message dispatched at rollup height `height` by a transaction of
+    /// `tx_sender`. Returns false if the message can't be decoded.
+    pub fn dispatched(&mut self, message: &[u8], height: u64, tx_sender: HexHash) -> bool {
+        let Some(header) = MessageHeader::decode(message) else {
+            return false;
+        };
+        self.open.push(Report::Dispatched {
+            header,
+            height,
+            tx_sender,
+        });
+        true
+    }
+
+    /// Records an encoded message delivered by a mailbox `process` call at rollup height
+    /// `height`. Returns false if the message can't be decoded.
+    pub fn delivered(&mut self, message: &[u8], height: u64) -> bool {
+        let Some(header) = MessageHeader::decode(message) else {
+            return false;
+        };
+        self.open.push(Report::Delivered { header, height });
+        true
+    }
+
+    /// Records an encoded message processed by a mailbox `process` call at rollup height
+    /// `height`, whose transfer was queued by the warp limits module as `transfer_id`. Returns
+    /// false if the message can't be decoded.
+    pub fn queued(&mut self, message: &[u8], height: u64, transfer_id: u64) -> bool {
+        let Some(header) = MessageHeader::decode(message) else {
+            return false;
+        };
+        self.open.push(Report::Queued {
+            header,
+            height,
+            transfer_id,
+        });
+        true
+    }
+
+    /// Records the release of the queued warp transfer `transfer_id` at rollup height `height`.
+    /// Transfers that don't belong to a queued message, such as outbound ones, are ignored.
+    pub fn released(&mut self, transfer_id: u64, height: u64) {
+        self.open.push(Report::Released {
+            transfer_id,
+            height,
+        });
+    }
+
+    /// Records the cancellation of the queued warp transfer `transfer_id` at rollup height
+    /// `height`. Transfers that don't belong to a queued message are ignored.
+    pub fn cancelled(&mut self, transfer_id: u64, height: u64) {
+        self.open.push(Report::Cancelled {
+            transfer_id,
+            height,
+        });
+    }
+
+    /// Commits the messages of the open transaction.
+    pub fn commit(&mut self) {
+        let room = MAX_PENDING_REPORTS.saturating_sub(self.committed.len());
+        let open = std::mem::take(&mut self.open);
+        self.committed.extend(open.into_iter().take(room));
+    }
+
+    /// Drops the messages of the open transaction.
+    pub fn discard(&mut self) {
+        self.open.clear();
+    }
+}
+
+/// The mirrored merkle tree doesn't hold the leaves of the `MerkleTreeHook`, because the node
+/// didn't execute every slot since genesis, or because a message couldn't be added to the mirror.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "The message index mirrors {mirrored_count} leaves with root {mirrored_root}, but the MerkleTreeHook has {hook_count} leaves with root {hook_root}"
+)]
+pub struct MirrorMismatch {
+    /// The root of the `MerkleTreeHook`.
+    pub hook_root: HexHash,
+    /// The number of leaves of the `MerkleTreeHook`.
+    pub hook_count: u32,
+    /// The root of the mirror.
+    pub mirrored_root: HexHash,
+    /// The number of leaves of the mirror.
+    pub mirrored_count: u32,
+}
+
+/// An index of Hyperlane messages, by message ID and by the sender of the dispatching transaction.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct MessageIndex<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The indexed messages, by message ID.
+    #[state]
+    pub messages: AccessoryStateMap<HexHash, MessageRecord>,
+
+    /// The number of messages dispatched by the transactions of each sender, which is given in
+    /// its Hyperlane form.
+    #[state]
+    pub sender_message_counts: AccessoryStateMap<HexHash, u64>,
+
+    /// The IDs of the messages dispatched by the transactions of each sender, in order.
+    #[state]
+    pub sender_messages: AccessoryStateMap<(HexHash, u64), HexHash>,
+
+    /// The non-empty nodes of the merkle tree of dispatched messages, by height and by index
+    /// among the nodes of the same height. The leaves have height 0 and the root height 32.
+    #[state]
+    pub tree_nodes: AccessoryStateMap<(u8, u32), HexHash>,
+
+    /// The number of leaves of the merkle tree, which is the nonce of the next dispatched message.
+    #[state]
+    pub tree_count: AccessoryStateValue<u32>,
+
+    /// The IDs of the inbound messages whose transfer is queued by the warp limits module, by
+    /// transfer ID.
+    #[state]
+    pub queued_messages: AccessoryStateMap<u64, HexHash>,
+
+    #[phantom]
+    phantom: PhantomData<S>,
+}
+
+impl<S: Spec> MessageIndex<S> {
+    /// Writes the messages committed to `journal` since the last flush.
+    pub fn flush(
+        &mut self,
+        journal: &mut MessageJournal,
+        state: &mut impl AccessoryStateReaderAndWriter,
+    ) -> Result<()> {
+        let reports = std::mem::take(&mut journal.committed);
+        let zeros = merkle::zero_hashes();
+        // A message that can't be added to the tree is still indexed, and the error is returned
+        // once every message is written.
+        let mut tree_error = None;
+        for report in reports {
+            match report {
+                Report::Dispatched {
+                    header,
+                    height,
+                    tx_sender,
+                } => {
+                    let record = MessageRecord {
+                        direction: Direction::Outbound,
+                        origin: header.origin,
+                        destination: header.destination,
+                        nonce: header.nonce,
+                        sender: header.sender,
+                        recipient: header.recipient,
+                        dispatch_height: Some(height),
+                        status: MessageStatus::Dispatched,
+                    };
+                    self.messages.set(&header.id, &record, state)?;
+                    if let Err(e) = self.insert_leaf(header.nonce, &header.id, &zeros, state) {
+                        tree_error.get_or_insert(e);
+                    }
+
+                    let count = self
+                        .sender_message_counts
+                        .get(&tx_sender, state)?
+                        .unwrap_or_default();
+                    self.sender_messages
+                        .set(&(tx_sender.clone(), count), &header.id, state)?;
+                    self.sender_message_counts
+                        .set(&tx_sender, &(count + 1), state)?;
+                }
+                Report::Delivered { header, height } => {
+                    self.set_processed(header, MessageStatus::Delivered { height }, state)?;
+                }
+                Report::Queued {
+                    header,
+                    height,
+                    transfer_id,
+                } => {
+                    self.queued_messages.set(&transfer_id, &header.id, state)?;
+                    let status = MessageStatus::Queued {
+                        height,
+                        transfer_id,
+                    };
+                    self.set_processed(header, status, state)?;
+                }
+                Report::Released {
+                    transfer_id,
+                    height,
+                } => {
+                    self.set_unqueued(transfer_id, MessageStatus::Delivered { height }, state)?;
+                }
+                Report::Cancelled {
+                    transfer_id,
+                    height,
+                } => {
+                    self.set_unqueued(transfer_id, MessageStatus::Cancelled { height }, state)?;
+                }
+            }
+        }
+        tree_error.map_or(Ok(()), Err)
+    }
+
+    /// Sets the status of a message processed by the mailbox.
+    fn set_processed(
+        &mut self,
+        header: MessageHeader,
+        status: MessageStatus,
+        state: &mut impl AccessoryStateReaderAndWriter,
+    ) -> Result<()> {
+        // Messages sent by the rollup to itself are already indexed.
+        let record = match self.messages.get(&header.id, state)? {
+            Some(record) => MessageRecord { status, ..record },
+            None => MessageRecord {
+                direction: Direction::Inbound,
+                origin: header.origin,
+                destination: header.destination,
+                nonce: header.nonce,
+                sender: header.sender,
+                recipient: header.recipient,
+                dispatch_height: None,
+                status,
+            },
+        };
+        self.messages.set(&header.id, &record, state)?;
+        Ok(())
+    }
+
+    /// Sets the status of the message queued as the warp transfer `transfer_id`, if any.
+    fn set_unqueued(
+        &mut self,
+        transfer_id: u64,
+        status: MessageStatus,
+        state: &mut impl AccessoryStateReaderAndWriter,
+    ) -> Result<()> {
+        let Some(id) = self.queued_messages.get(&transfer_id, state)? else {
+            return Ok(());
+        };
+        self.queued_messages.remove(&transfer_id, state)?;
+        if let Some(record) = self.messages.get(&id, state)? {
+            self.messages
+                .set(&id, &MessageRecord { status, ..record }, state)?;
+        }
+        Ok(())
+    }
+
+    fn tree_node(
+        &self,
+        height: usize,
+        index: u32,
+        zeros: &[HexHash],
+        state: &mut impl StateReader<Accessory>,
+    ) -> Result<HexHash> {
+        Ok(self
+            .tree_nodes
+            .get(&(height as u8, index), state)?
+            .unwrap_or_else(|| zeros[height].clone()))
+    }
+
+    /// Appends the ID of the message with nonce `nonce` to the merkle tree, and updates the nodes
+    /// on its path to the root. Fails if the tree already holds the nonce, or if it is missing the
+    /// nonces before it.
+    fn insert_leaf(
+        &mut self,
+        nonce: u32,
+        leaf: &HexHash,
+        zeros: &[HexHash],
+        state: &mut impl AccessoryStateReaderAndWriter,
+    ) -> Result<()> {
+        let count = self.tree_count.get(state)?.unwrap_or_default();
+        anyhow::ensure!(count < merkle::MAX_LEAVES, "The merkle tree is full");
+        anyhow::ensure!(
+            nonce >= count,
+            "The merkle tree already holds the message with nonce {nonce}"
+        );
+        anyhow::ensure!(
+            nonce == count,
+            "The merkle tree is missing the messages with nonces {count} to {}",
+            nonce - 1
+        );
+
+        let mut index = count;
+        let mut node = leaf.clone();
+        for height in 0..merkle::TREE_DEPTH {
+            self.tree_nodes.set(&(height as u8, index), &node, state)?;
+            let sibling = self.tree_node(height, index ^ 1, zeros, state)?;
+            node = if index & 1 == 1 {
+                merkle::hash_pair(&sibling, &node)
+            } else {
+                merkle::hash_pair(&node, &sibling)
+            };
+            index >>= 1;
+        }
+        self.tree_nodes
+            .set(&(merkle::TREE_DEPTH as u8, 0), &node, state)?;
+        self.tree_count.set(&(count + 1), state)?;
+        Ok(())
+    }
+
+    /// Returns the number of leaves of the mirrored merkle tree.
+    pub fn tree_count(&self, state: &mut impl StateReader<Accessory>) -> Result<u32> {
+        Ok(self.tree_count.get(state)?.unwrap_or_default())
+    }
+
+    /// Returns the root of the mirrored merkle tree.
+    pub fn tree_root(&self, state: &mut impl StateReader<Accessory>) -> Result<HexHash> {
+        self.tree_node(merkle::TREE_DEPTH, 0, &merkle::zero_hashes(), state)
+    }
+
+    /// Returns a proof of the leaf at `index` against the current root, or `None` if the tree
+    /// has no such leaf.
+    pub fn tree_proof(
+        &self,
+        index: u32,
+        state: &mut impl StateReader<Accessory>,
+    ) -> Result<Option<MerkleProof>> {
+        if index >= self.tree_count(state)? {
+            return Ok(None);
+        }
+        let zeros = merkle::zero_hashes();
+        let leaf = self.tree_node(0, index, &zeros, state)?;
+        let branch = (0..merkle::TREE_DEPTH)
+            .map(|height| self.tree_node(height, (index >> height) ^ 1, &zeros, state))
+            .collect::<Result<_>>()?;
+        Ok(Some(MerkleProof {
+            leaf,
+            index,
+            branch,
+        }))
+    }
+
+    /// Returns the checkpoint of the tree with root `root` and `count` leaves, along with the ID
+    /// of its last message read from the mirror, or `None` if the tree is empty.
+    pub fn checkpoint(
+        &self,
+        root: HexHash,
+        count: u32,
+        state: &mut impl StateReader<Accessory>,
+    ) -> Result<Option<CheckpointWithMessageId>> {
+        let Some(index) = count.checked_sub(1) else {
+            return Ok(None);
+        };
+        Ok(Some(CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook_address: MERKLE_TREE_HOOK_ADDRESS,
+                mailbox_domain: config_value!("HYPERLANE_BRIDGE_DOMAIN"),
+                root,
+                index,
+            },
+            message_id: self.tree_node(0, index, &merkle::zero_hashes(), state)?,
+        }))
+    }
+
+    /// Returns the IDs of the messages dispatched by the transactions of `sender`, skipping the
+    /// first `offset`, along with the total number of messages of `sender`.
+    pub fn sender_messages(
+        &self,
+        sender: &HexHash,
+        offset: u64,
+        limit: u64,
+        state: &mut impl StateReader<Accessory>,
+    ) -> Result<(u64, Vec<HexHash>)> {
+        let total = self
+            .sender_message_counts
+            .get(sender, state)?
+            .unwrap_or_default();
+        let mut ids = Vec::new();
+        for index in offset..total.min(offset.saturating_add(limit)) {
+            if let Some(id) = self.sender_messages.get(&(sender.clone(), index), state)? {
+                ids.push(id);
+            }
+        }
+        Ok((total, ids))
+    }
+}
+
+impl<S: Spec> MessageIndex<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// Returns the root and the leaf count of the tree of the `MerkleTreeHook`, read from the
+    /// rollup state.
+    pub fn hook_tree(&self, state: &mut impl StateReader<User>) -> Result<(HexHash, u32)> {
+        let tree = MerkleTreeHook::<S>::default()
+            .tree
+            .get(state)?
+            .unwrap_or_default();
+        Ok((tree.root(), tree.count()))
+    }
+
+    /// Returns the root and the leaf count of the tree of the `MerkleTreeHook`, if the mirror
+    /// holds the same leaves, so that its proofs and message IDs hold against the hook's root.
+    /// Fails with a [`MirrorMismatch`] otherwise.
+    pub fn mirrored_hook_tree<St>(&self, state: &mut St) -> Result<(HexHash, u32)>
+    where
+        St: StateReader<User> + StateReader<Accessory>,
+    {
+        let (hook_root, hook_count) = self.hook_tree(state)?;
+        let mirrored_count = self.tree_count(state)?;
+        let mirrored_root = self.tree_root(state)?;
+        if mirrored_count != hook_count || mirrored_root != hook_root {
+            return Err(MirrorMismatch {
+                hook_root,
+                hook_count,
+                mirrored_root,
+                mirrored_count,
+            }
+            .into());
+        }
+        Ok((hook_root, hook_count))
+    }
+}
+
+impl<S: Spec> Module for MessageIndex<S> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = NotInstantiable;
+
+    type Event = ();
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        _config: &Self::Config,
+        _state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        _context: &Context<Self::Spec>,
+        _state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {}
+    }
+}
@@ -0,0 +1,170 @@
+//! The incremental merkle tree of Hyperlane's `MerkleTreeHook`, and its inclusion proofs.
+//!
+//! The tree has a fixed depth of 32. Its leaves are the IDs of the dispatched messages, in
+//! dispatch order, and the empty leaves are zero. A node is the keccak256 hash of its two
+//! children, so the root of a partially filled tree is the root of the full tree padded with
+//! zero leaves.
+use alloy_primitives::keccak256;
+use sov_hyperlane_integration::{HexHash, HexString};
+
+/// The depth of the tree.
+pub const TREE_DEPTH: usize = 32;
+
+/// The maximum number of leaves, as enforced by the Solidity `MerkleLib`.
+pub const MAX_LEAVES: u32 = u32::MAX;
+
+/// The address checkpoints commit to. The rollup's merkle tree hook is a module rather than a
+/// contract, and the agent configs list it at the zero address.
+pub const MERKLE_TREE_HOOK_ADDRESS: HexHash = HexString([0; 32]);
+
+/// Returns the hash of a node from its children.
+pub fn hash_pair(left: &HexHash, right: &HexHash) -> HexHash {
+    let mut pair = [0; 64];
+    pair[..32].copy_from_slice(&left.0);
+    pair[32..].copy_from_slice(&right.0);
+    HexString(keccak256(pair).0)
+}
+
+/// Returns the root of an empty subtree of each height, from a zero leaf up to an empty tree.
+pub fn zero_hashes() -> Vec<HexHash> {
+    let mut zeros = Vec::with_capacity(TREE_DEPTH + 1);
+    zeros.push(HexString([0; 32]));
+    for height in 0..TREE_DEPTH {
+        let zero = hash_pair(&zeros[height], &zeros[height]);
+        zeros.push(zero);
+    }
+    zeros
+}
+
+/// A proof that `leaf` is the leaf at `index` of a tree.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MerkleProof {
+    /// The proven leaf, a message ID.
+    pub leaf: HexHash,
+    /// The index of the leaf, the nonce of the message in the rollup's mailbox.
+    pub index: u32,
+    /// The siblings of the path from the leaf to the root, starting from the leaf's sibling.
+    pub branch: Vec<HexHash>,
+}
+
+impl MerkleProof {
+    /// Returns the root the proof leads to, or `None` if its branch doesn't have
+    /// [`TREE_DEPTH`] siblings.
+    pub fn root(&self) -> Option<HexHash> {
+        if self.branch.len() != TREE_DEPTH {
+            return None;
+        }
+        let mut node = self.leaf.clone();
+        for (height, sibling) in self.branch.iter().enumerate() {
+            node = if (self.index >> height) & 1 == 1 {
+                hash_pair(sibling, &node)
+            } else {
+                hash_pair(&node, sibling)
+            };
+        }
+        Some(node)
+    }
+
+    /// Returns true if the proof leads to `root`.
+    pub fn verify(&self, root: &HexHash) -> bool {
+        self.root().as_ref() == Some(root)
+    }
+}
+
+/// A merkle tree that only stores the branch needed to append leaves and compute its root, as
+/// the `MerkleTreeHook` does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncrementalMerkle {
+    branch: Vec<HexHash>,
+    count: u32,
+}
+
+impl Default for IncrementalMerkle {
+    fn default() -> Self {
+        Self {
+            branch: vec![HexString([0; 32]); TREE_DEPTH],
+            count: 0,
+        }
+    }
+}
+
+impl IncrementalMerkle {
+    /// Returns the number of leaves.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Appends a leaf. Fails if the tree holds [`MAX_LEAVES`] leaves.
+    pub fn insert(&mut self, leaf: HexHash) -> anyhow::Result<()> {
+        anyhow::ensure!(self.count < MAX_LEAVES, "The merkle tree is full");
+        self.count += 1;
+        let mut size = self.count;
+        let mut node = leaf;
+        for sibling in self.branch.iter_mut() {
+            if size & 1 == 1 {
+                *sibling = node;
+                return Ok(());
+            }
+            node = hash_pair(sibling, &node);
+            size >>= 1;
+        }
+        unreachable!("A tree of fewer than 2^32 leaves has room for one more")
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> HexHash {
+        let zeros = zero_hashes();
+        let mut node = zeros[0].clone();
+        for (height, sibling) in self.branch.iter().enumerate() {
+            node = if (self.count >> height) & 1 == 1 {
+                hash_pair(sibling, &node)
+            } else {
+                hash_pair(&node, &zeros[height])
+            };
+        }
+        node
+    }
+}
+
+/// A Hyperlane checkpoint: the root of the tree once the leaf at `index` is inserted.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Checkpoint {
+    /// The address of the merkle tree hook, [`MERKLE_TREE_HOOK_ADDRESS`].
+    pub merkle_tree_hook_address: HexHash,
+    /// The domain of the rollup's mailbox.
+    pub mailbox_domain: u32,
+    /// The root of the tree.
+    pub root: HexHash,
+    /// The index of the last inserted leaf.
+    pub index: u32,
+}
+
+/// A checkpoint and the ID of the message inserted at its index.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CheckpointWithMessageId {
+    /// The checkpoint.
+    pub checkpoint: Checkpoint,
+    /// The ID of the message at the index of the checkpoint.
+    pub message_id: HexHash,
+}
+
+impl CheckpointWithMessageId {
+    /// `keccak256(keccak256(mailbox_domain ‖ merkle_tree_hook_address ‖ "HYPERLANE") ‖ root ‖
+    /// index ‖ message_id)`, as computed by the Solidity `CheckpointLib`. Validators sign it with
+    /// an EIP-191 prefix.
+    pub fn digest(&self) -> [u8; 32] {
+        let checkpoint = &self.checkpoint;
+        let mut domain = Vec::with_capacity(4 + 32 + 9);
+        domain.extend_from_slice(&checkpoint.mailbox_domain.to_be_bytes());
+        domain.extend_from_slice(&checkpoint.merkle_tree_hook_address.0);
+        domain.extend_from_slice(b"HYPERLANE");
+        let domain_hash = keccak256(&domain);
+
+        let mut digest = Vec::with_capacity(32 + 32 + 4 + 32);
+        digest.extend_from_slice(domain_hash.as_slice());
+        digest.extend_from_slice(&checkpoint.root.0);
+        digest.extend_from_slice(&checkpoint.index.to_be_bytes());
+        digest.extend_from_slice(&self.message_id.0);
+        keccak256(&digest).0
+    }
+}
@@ -0,0 +1,125 @@
+use message_index::merkle::{hash_pair, zero_hashes, IncrementalMerkle, MerkleProof, TREE_DEPTH};
+use message_index::MessageHeader;
+use sov_hyperlane_integration::{HexHash, HexString};
+
+fn encode(
+    nonce: u32,
+    origin: u32,
+    sender: [u8; 32],
+    destination: u32,
+    recipient: [u8; 32],
+) -> Vec<u8> {
+    let mut message = vec![3];
+    message.extend_from_slice(&nonce.to_be_bytes());
+    message.extend_from_slice(&origin.to_be_bytes());
+    message.extend_from_slice(&sender);
+    message.extend_from_slice(&destination.to_be_bytes());
+    message.extend_from_slice(&recipient);
+    message.extend_from_slice(b"body");
+    message
+}
+
+#[test]
+fn test_decode_header() {
+    let message = encode(7, 1399811149, [1; 32], 4321, [2; 32]);
+    let header = MessageHeader::decode(&message).unwrap();
+
+    assert_eq!(header.nonce, 7);
+    assert_eq!(header.origin, 1399811149);
+    assert_eq!(header.sender, HexString([1; 32]));
+    assert_eq!(header.destination, 4321);
+    assert_eq!(header.recipient, HexString([2; 32]));
+    assert_eq!(
+        header.id,
+        HexString(alloy_primitives::keccak256(&message).0)
+    );
+}
+
+#[test]
+fn test_decode_rejects_truncated_message() {
+    let message = encode(7, 1399811149, [1; 32], 4321, [2; 32]);
+
+    // A message without a body is still valid.
+    assert!(MessageHeader::decode(&message[..MessageHeader::LEN]).is_some());
+    assert!(MessageHeader::decode(&message[..MessageHeader::LEN - 1]).is_none());
+}
+
+/// Computes the root of the tree of `leaves` level by level, padding each level with zeros.
+fn naive_root(leaves: &[HexHash]) -> HexHash {
+    let zeros = zero_hashes();
+    let mut level = leaves.to_vec();
+    for zero in zeros.iter().take(TREE_DEPTH) {
+        if level.len() % 2 == 1 {
+            level.push(zero.clone());
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+    }
+    level.pop().unwrap_or_else(|| zeros[TREE_DEPTH].clone())
+}
+
+fn leaves(count: u8) -> Vec<HexHash> {
+    (1..=count).map(|i| HexString([i; 32])).collect()
+}
+
+#[test]
+fn test_empty_root() {
+    // The initial root of Hyperlane's `MerkleTreeHook`.
+    let initial_root: HexHash = serde_json::from_value(serde_json::json!(
+        "0x27ae5ba08d7291c96c8cbddcc148bf48a6d68c7974b94356f53754ef6171d757"
+    ))
+    .unwrap();
+
+    assert_eq!(zero_hashes()[TREE_DEPTH], initial_root);
+    assert_eq!(naive_root(&[]), initial_root);
+}
+
+#[test]
+fn test_verify_proof() {
+    let leaves = leaves(3);
+    let root = naive_root(&leaves);
+    let zeros = zero_hashes();
+
+    // The third leaf's sibling is empty, and so is the right half of the tree above it.
+    let mut branch = vec![zeros[0].clone(), hash_pair(&leaves[0], &leaves[1])];
+    branch.extend(zeros[2..TREE_DEPTH].iter().cloned());
+    let proof = MerkleProof {
+        leaf: leaves[2].clone(),
+        index: 2,
+        branch,
+    };
+    assert!(proof.verify(&root));
+
+    // The proof doesn't hold for another index, another leaf or another root.
+    assert!(!MerkleProof {
+        index: 3,
+        ..proof.clone()
+    }
+    .verify(&root));
+    assert!(!MerkleProof {
+        leaf: leaves[0].clone(),
+        ..proof.clone()
+    }
+    .verify(&root));
+    assert!(!proof.verify(&naive_root(&leaves[..2])));
+
+    // A proof must have a sibling per level.
+    let mut short = proof.clone();
+    short.branch.pop();
+    assert_eq!(short.root(), None);
+}
+
+#[test]
+fn test_incremental_root() {
+    let leaves = leaves(9);
+    let mut tree = IncrementalMerkle::default();
+    assert_eq!(tree.root(), naive_root(&[]));
+
+    for (count, leaf) in leaves.iter().enumerate() {
+        tree.insert(leaf.clone()).unwrap();
+        assert_eq!(tree.count() as usize, count + 1);
+        assert_eq!(tree.root(), naive_root(&leaves[..=count]));
+    }
+}
@@ -0,0 +1,161 @@
+//! A registry of k-of-n multisig credentials.
+//!
+//! The module only stores configurations; signatures are checked by the rollup's
+//! transaction authenticator, which reads the registered configuration from state.
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Context, CredentialId, CryptoSpec, EventEmitter, Module, ModuleId, ModuleInfo, ModuleRestApi,
+    Spec, StateMap, StateReader, TxState,
+};
+use sov_state::User;
+use std::marker::PhantomData;
+
+/// The maximum number of members a single multisig can have.
+pub const MAX_MEMBERS: usize = 32;
+
+/// A registered k-of-n multisig.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct MultisigConfig {
+    /// The number of distinct member signatures required to authorize a transaction.
+    pub threshold: u8,
+    /// The credentials allowed to sign on behalf of the multisig.
+    pub members: Vec<CredentialId>,
+}
+
+impl MultisigConfig {
+    /// Returns the credential ID of the multisig. The members are sorted first,
+    /// so the ID does not depend on the order in which they were supplied.
+    pub fn credential_id<S: Spec>(&self) -> CredentialId {
+        use sov_modules_api::digest::Digest;
+
+        let mut members = self.members.clone();
+        members.sort();
+
+        let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+        hasher.update(b"multisig");
+        hasher.update([self.threshold]);
+        for member in &members {
+            hasher.update(member.0);
+        }
+        CredentialId(hasher.finalize().into())
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.members.is_empty() || self.members.len() > MAX_MEMBERS {
+            bail!(
+                "A multisig must have between 1 and {MAX_MEMBERS} members, got {}",
+                self.members.len()
+            );
+        }
+
+        let mut members = self.members.clone();
+        members.sort();
+        members.dedup();
+        if members.len() != self.members.len() {
+            bail!("Multisig members must be unique");
+        }
+
+        if self.threshold == 0 || usize::from(self.threshold) > self.members.len() {
+            bail!(
+                "Invalid threshold {} for a multisig with {} members",
+                self.threshold,
+                self.members.len()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps the multisig configurations registered on the rollup.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct Multisig<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// Registered multisigs, keyed by their credential ID.
+    #[state]
+    pub multisigs: StateMap<CredentialId, MultisigConfig>,
+
+    #[phantom]
+    phantom: PhantomData<S>,
+}
+
+impl<S: Spec> Multisig<S> {
+    /// Returns the configuration registered for the given multisig credential.
+    pub fn config(
+        &self,
+        credential_id: &CredentialId,
+        state: &mut impl StateReader<User>,
+    ) -> Result<Option<MultisigConfig>> {
+        Ok(self.multisigs.get(credential_id, state)?)
+    }
+}
+
+impl<S: Spec> Module for Multisig<S> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = CallMessage;
+
+    type Event = Event;
+
+    type Error = anyhow::Error;
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        _context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::Register(config) => {
+                config.validate()?;
+
+                let credential_id = config.credential_id::<S>();
+                if self.multisigs.get(&credential_id, state)?.is_some() {
+                    bail!("Multisig {credential_id} is already registered");
+                }
+
+                self.multisigs.set(&credential_id, &config, state)?;
+                self.emit_event(
+                    state,
+                    Event::Registered {
+                        credential_id,
+                        threshold: config.threshold,
+                        members: config.members,
+                    },
+                );
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum CallMessage {
+    /// Registers a new multisig. Anyone can register a multisig, since its credential ID
+    /// is derived from the configuration itself.
+    Register(MultisigConfig),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    /// A new multisig was registered.
+    Registered {
+        credential_id: CredentialId,
+        threshold: u8,
+        members: Vec<CredentialId>,
+    },
+}
@@ -0,0 +1,135 @@
+use multisig::{CallMessage, Event, Multisig, MultisigConfig};
+use sov_modules_api::{CredentialId, Spec};
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    multisig: Multisig<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub regular_user: TestUser<S>,
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(1);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let regular_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(genesis_config.into(), ());
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { regular_user }, runner)
+}
+
+fn members(count: u8) -> Vec<CredentialId> {
+    (0..count).map(|i| CredentialId([i; 32])).collect()
+}
+
+#[test]
+fn test_register_multisig() {
+    let (test_data, mut runner) = setup();
+    let config = MultisigConfig {
+        threshold: 2,
+        members: members(3),
+    };
+    let credential_id = config.credential_id::<S>();
+    let expected_config = config.clone();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .regular_user
+            .create_plain_message::<TestRuntime<S>, Multisig<S>>(CallMessage::Register(config)),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::Multisig(Event::Registered {
+                    credential_id,
+                    threshold: 2,
+                    members: members(3),
+                })]
+            );
+
+            let stored = Multisig::<S>::default()
+                .config(&credential_id, state)
+                .unwrap();
+            assert_eq!(stored, Some(expected_config));
+        }),
+    });
+}
+
+#[test]
+fn test_credential_id_ignores_member_order() {
+    let mut reversed = members(3);
+    reversed.reverse();
+
+    let a = MultisigConfig {
+        threshold: 2,
+        members: members(3),
+    };
+    let b = MultisigConfig {
+        threshold: 2,
+        members: reversed,
+    };
+    let c = MultisigConfig {
+        threshold: 3,
+        members: members(3),
+    };
+
+    assert_eq!(a.credential_id::<S>(), b.credential_id::<S>());
+    assert_ne!(a.credential_id::<S>(), c.credential_id::<S>());
+}
+
+#[test]
+fn test_invalid_threshold_is_rejected() {
+    let (test_data, mut runner) = setup();
+
+    for threshold in [0, 4] {
+        runner.execute_transaction(TransactionTestCase {
+            input: test_data
+                .regular_user
+                .create_plain_message::<TestRuntime<S>, Multisig<S>>(CallMessage::Register(
+                    MultisigConfig {
+                        threshold,
+                        members: members(3),
+                    },
+                )),
+            assert: Box::new(|result, _state| {
+                assert!(result.tx_receipt.is_reverted());
+            }),
+        });
+    }
+}
+
+#[test]
+fn test_duplicate_registration_is_rejected() {
+    let (test_data, mut runner) = setup();
+    let config = MultisigConfig {
+        threshold: 1,
+        members: members(2),
+    };
+
+    for expect_success in [true, false] {
+        runner.execute_transaction(TransactionTestCase {
+            input: test_data
+                .regular_user
+                .create_plain_message::<TestRuntime<S>, Multisig<S>>(CallMessage::Register(
+                    config.clone(),
+                )),
+            assert: Box::new(move |result, _state| {
+                assert_eq!(result.tx_receipt.is_successful(), expect_success);
+            }),
+        });
+    }
+}
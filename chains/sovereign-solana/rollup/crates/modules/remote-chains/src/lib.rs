@@ -0,0 +1,296 @@
+//! Registry of the remote chains the rollup bridges to.
+//!
+//! Hyperlane addresses are 32 bytes on the wire, but each remote chain uses its own address
+//! format: Sealevel programs and accounts use all 32 bytes, EVM addresses are 20 bytes left-padded
+//! with zeros, and Cosmos addresses are bech32 strings with a chain-specific prefix. The admin
+//! registers the [`AddressFormat`] of each remote domain, and the runtime checks the recipients of
+//! outbound messages and the senders of inbound messages against it. Domains that are not
+//! registered are not checked.
+use anyhow::{bail, Context as _, Result};
+use bech32::{Bech32, Hrp};
+use schemars::JsonSchema;
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Context, EventEmitter, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec,
+    StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+
+/// The maximum length of a chain name, in bytes.
+pub const MAX_NAME_LEN: usize = 64;
+
+/// The address format of a remote chain.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFormat {
+    /// 32 byte addresses, written in base58.
+    Sealevel,
+    /// 20 byte addresses left-padded with zeros, written in hex.
+    Evm,
+    /// 20 or 32 byte addresses, written in bech32 with the human readable part `prefix`. 20 byte
+    /// addresses are left-padded with zeros.
+    Cosmos { prefix: String },
+}
+
+impl AddressFormat {
+    /// Checks that `address` is a valid address of this format.
+    pub fn validate(&self, address: &HexHash) -> Result<()> {
+        if address.0 == [0; 32] {
+            bail!("The zero address is not a valid remote address");
+        }
+        if let AddressFormat::Evm = self {
+            if address.0[..12].iter().any(|byte| *byte != 0) {
+                bail!("{address} is not a left-padded 20 byte EVM address");
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the human readable form of an address of this format.
+    pub fn parse(&self, address: &str) -> Result<HexHash> {
+        let bytes = match self {
+            AddressFormat::Sealevel => bs58::decode(address)
+                .into_vec()
+                .with_context(|| format!("{address} is not a base58 address"))?,
+            AddressFormat::Evm => {
+                let hex = address
+                    .strip_prefix("0x")
+                    .with_context(|| format!("{address} is not a 0x-prefixed EVM address"))?;
+                if hex.len() != 40 {
+                    bail!("{address} is not a 20 byte EVM address");
+                }
+                hex::decode(hex).with_context(|| format!("{address} is not a hex address"))?
+            }
+            AddressFormat::Cosmos { prefix } => {
+                let (hrp, bytes) = bech32::decode(address)
+                    .with_context(|| format!("{address} is not a bech32 address"))?;
+                if hrp.as_str() != prefix.as_str() {
+                    bail!("{address} doesn't have the prefix {prefix}");
+                }
+                bytes
+            }
+        };
+
+        let parsed = match (self, bytes.len()) {
+            (AddressFormat::Sealevel, 32) | (AddressFormat::Cosmos { .. }, 32) => {
+                HexString(bytes.try_into().expect("The length was checked"))
+            }
+            (AddressFormat::Evm, 20) | (AddressFormat::Cosmos { .. }, 20) => {
+                let mut padded = [0; 32];
+                padded[12..].copy_from_slice(&bytes);
+                HexString(padded)
+            }
+            (_, len) => {
+                bail!("{address} is {len} bytes long, which doesn't fit the {self:?} format")
+            }
+        };
+        self.validate(&parsed)?;
+        Ok(parsed)
+    }
+
+    /// Returns the human readable form of `address`.
+    pub fn format(&self, address: &HexHash) -> Result<String> {
+        self.validate(address)?;
+        Ok(match self {
+            AddressFormat::Sealevel => bs58::encode(address.0).into_string(),
+            AddressFormat::Evm => format!("0x{}", hex::encode(&address.0[12..])),
+            AddressFormat::Cosmos { prefix } => {
+                let hrp = Hrp::parse(prefix)?;
+                let bytes = if address.0[..12].iter().all(|byte| *byte == 0) {
+                    &address.0[12..]
+                } else {
+                    &address.0[..]
+                };
+                bech32::encode::<Bech32>(hrp, bytes)?
+            }
+        })
+    }
+}
+
+/// A remote chain the rollup bridges to.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct RemoteChain {
+    /// The Hyperlane domain of the chain.
+    pub domain: u32,
+    /// A display name, such as `solanatestnet` or `ethtest`.
+    pub name: String,
+    /// The address format of the chain.
+    pub format: AddressFormat,
+}
+
+/// Genesis configuration of the [`RemoteChains`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "RemoteChainsConfig")]
+pub struct RemoteChainsConfig<S: Spec> {
+    /// The account allowed to register chains.
+    pub admin: S::Address,
+    /// The chains registered at genesis.
+    pub chains: Vec<RemoteChain>,
+}
+
+/// Stores the remote chains known to the rollup.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct RemoteChains<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The account allowed to manage chains.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The registered chains, by domain.
+    #[state]
+    pub chains: StateMap<u32, RemoteChain>,
+}
+
+impl<S: Spec> RemoteChains<S> {
+    /// Returns the chain registered for `domain`, if any.
+    pub fn chain(
+        &self,
+        domain: u32,
+        state: &mut impl StateReader<User>,
+    ) -> Result<Option<RemoteChain>> {
+        Ok(self.chains.get(&domain, state)?)
+    }
+
+    /// Checks that `address` is valid on `domain`. Addresses of unregistered domains are accepted.
+    pub fn check_address(
+        &self,
+        domain: u32,
+        address: &HexHash,
+        state: &mut impl StateReader<User>,
+    ) -> Result<()> {
+        match self.chain(domain, state)? {
+            Some(chain) => chain
+                .format
+                .validate(address)
+                .with_context(|| format!("Invalid address for {} ({domain})", chain.name)),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the human readable form of an address on `domain`.
+    pub fn parse_address(
+        &self,
+        domain: u32,
+        address: &str,
+        state: &mut impl StateReader<User>,
+    ) -> Result<HexHash> {
+        let chain = self
+            .chain(domain, state)?
+            .with_context(|| format!("Domain {domain} is not registered"))?;
+        chain.format.parse(address)
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .context("The remote chains admin is not set")?;
+        if &admin != context.sender() {
+            bail!("Only the admin {admin} can manage remote chains");
+        }
+        Ok(())
+    }
+}
+
+fn validate(chain: &RemoteChain) -> Result<()> {
+    if chain.domain == config_value!("HYPERLANE_BRIDGE_DOMAIN") {
+        bail!("The local domain {} can't be registered", chain.domain);
+    }
+    if chain.name.is_empty() || chain.name.len() > MAX_NAME_LEN {
+        bail!("A chain name must be between 1 and {MAX_NAME_LEN} bytes long");
+    }
+    if let AddressFormat::Cosmos { prefix } = &chain.format {
+        Hrp::parse(prefix).with_context(|| format!("{prefix} is not a valid bech32 prefix"))?;
+    }
+    Ok(())
+}
+
+impl<S: Spec> Module for RemoteChains<S> {
+    type Spec = S;
+
+    type Config = RemoteChainsConfig<S>;
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.admin.set(&config.admin, state)?;
+        for chain in &config.chains {
+            validate(chain)?;
+            self.chains.set(&chain.domain, chain, state)?;
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        self.ensure_admin(context, state)?;
+
+        match msg {
+            CallMessage::RegisterChain { chain } => {
+                validate(&chain)?;
+                self.chains.set(&chain.domain, &chain, state)?;
+                self.emit_event(state, Event::ChainRegistered { chain });
+            }
+            CallMessage::RemoveChain { domain } => {
+                if self.chains.get(&domain, state)?.is_none() {
+                    bail!("Domain {domain} is not registered");
+                }
+                self.chains.remove(&domain, state)?;
+                self.emit_event(state, Event::ChainRemoved { domain });
+            }
+            CallMessage::SetAdmin { admin } => {
+                self.admin.set(&admin, state)?;
+                self.emit_event(state, Event::AdminSet { admin });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Registers a chain, or replaces the chain registered for its domain. Admin only.
+    RegisterChain { chain: RemoteChain },
+    /// Removes the chain registered for `domain`. Admin only.
+    RemoveChain { domain: u32 },
+    /// Replaces the admin. Admin only.
+    SetAdmin { admin: S::Address },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// A chain was registered or replaced.
+    ChainRegistered { chain: RemoteChain },
+    /// A chain was removed.
+    ChainRemoved { domain: u32 },
+    /// The admin was replaced.
+    AdminSet { admin: S::Address },
+}
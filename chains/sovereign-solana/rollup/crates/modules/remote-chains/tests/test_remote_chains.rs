@@ -0,0 +1,173 @@
+use remote_chains::{
+    AddressFormat, CallMessage, Event, RemoteChain, RemoteChains, RemoteChainsConfig,
+};
+use sov_hyperlane_integration::HexString;
+use sov_modules_api::Spec;
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    remote_chains: RemoteChains<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+fn sealevel() -> RemoteChain {
+    RemoteChain {
+        domain: 1337,
+        name: "solanatestnet".to_string(),
+        format: AddressFormat::Sealevel,
+    }
+}
+
+fn evm() -> RemoteChain {
+    RemoteChain {
+        domain: 3133790210,
+        name: "ethtest".to_string(),
+        format: AddressFormat::Evm,
+    }
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        RemoteChainsConfig {
+            admin: admin.address(),
+            chains: vec![sealevel()],
+        },
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { admin, other_user }, runner)
+}
+
+#[test]
+fn test_admin_registers_chain() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, RemoteChains<S>>(CallMessage::RegisterChain {
+                chain: evm(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::RemoteChains(Event::ChainRegistered {
+                    chain: evm()
+                })]
+            );
+
+            let module = RemoteChains::<S>::default();
+            assert_eq!(module.chain(evm().domain, state).unwrap(), Some(evm()));
+            assert_eq!(
+                module.chain(sealevel().domain, state).unwrap(),
+                Some(sealevel())
+            );
+
+            let mut padded = [0; 32];
+            padded[31] = 1;
+            assert!(module
+                .check_address(evm().domain, &HexString(padded), state)
+                .is_ok());
+            assert!(module
+                .check_address(evm().domain, &HexString([1; 32]), state)
+                .is_err());
+            // Unregistered domains are not checked.
+            assert!(module.check_address(1, &HexString([1; 32]), state).is_ok());
+        }),
+    });
+}
+
+#[test]
+fn test_other_users_cannot_register_chains() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, RemoteChains<S>>(CallMessage::RegisterChain {
+                chain: evm(),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_cosmos_prefix_must_be_valid() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, RemoteChains<S>>(CallMessage::RegisterChain {
+                chain: RemoteChain {
+                    domain: 1234,
+                    name: "cosmostest".to_string(),
+                    format: AddressFormat::Cosmos {
+                        prefix: String::new(),
+                    },
+                },
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_address_formats_round_trip() {
+    let evm = AddressFormat::Evm;
+    let address = evm
+        .parse("0x70997970c51812dc3a010c7d01b50e0d17dc79c8")
+        .unwrap();
+    assert_eq!(address.0[..12], [0; 12]);
+    assert_eq!(
+        evm.format(&address).unwrap(),
+        "0x70997970c51812dc3a010c7d01b50e0d17dc79c8"
+    );
+    assert!(evm
+        .parse("0x70997970c51812dc3a010c7d01b50e0d17dc79")
+        .is_err());
+
+    let sealevel = AddressFormat::Sealevel;
+    let program = "HX6EowhA5XwWj29iTFeqhprg1gUxHgv6RNUu4bRtUgob";
+    let address = sealevel.parse(program).unwrap();
+    assert_eq!(sealevel.format(&address).unwrap(), program);
+
+    let cosmos = AddressFormat::Cosmos {
+        prefix: "cosmos".to_string(),
+    };
+    let mut bytes = [0; 32];
+    bytes[12..].copy_from_slice(&[7; 20]);
+    let encoded = cosmos.format(&HexString(bytes)).unwrap();
+    assert!(encoded.starts_with("cosmos1"));
+    assert_eq!(cosmos.parse(&encoded).unwrap(), HexString(bytes));
+
+    let osmosis = AddressFormat::Cosmos {
+        prefix: "osmo".to_string(),
+    };
+    assert!(osmosis.parse(&encoded).is_err());
+}
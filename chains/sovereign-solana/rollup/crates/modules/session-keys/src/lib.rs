@@ -0,0 +1,299 @@
+//! Scoped, expiring session keys.
+//!
+//! An account authorizes a secondary credential (the session key) to sign transactions on its
+//! behalf. A grant is limited to a set of calls, a spend cap per token over its lifetime and an
+//! expiry height. The limits are enforced by the rollup's transaction authenticator, which calls
+//! [`SessionKeys::check`] before accepting a transaction signed by a session key, and the
+//! runtime records the tokens each transaction moves with [`SessionKeys::record_spend`].
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use sov_bank::{Coins, TokenId};
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, EventEmitter, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec,
+    StateMap, StateReader, TxState,
+};
+use sov_state::User;
+
+/// The maximum number of calls a single grant can allow.
+pub const MAX_ALLOWED_CALLS: usize = 16;
+
+/// The maximum number of tokens a single grant can cap.
+pub const MAX_SPEND_CAPS: usize = 8;
+
+/// The amount of a token the transactions signed by a session key may move.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct SpendCap {
+    /// The capped token.
+    pub token_id: TokenId,
+    /// The maximum amount all the transactions signed by the session key may move.
+    pub cap: Amount,
+    /// The amount moved by the transactions signed by the session key so far.
+    pub spent: Amount,
+}
+
+/// The limits attached to a session key.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "SessionKeyGrant")]
+pub struct SessionKeyGrant<S: Spec> {
+    /// The account the session key acts on behalf of.
+    pub owner: S::Address,
+    /// The calls the session key may sign, as `module.call` names, e.g. `warp.transfer_remote`.
+    /// The runtime defines the names, and which calls session keys can sign at all.
+    pub allowed_calls: Vec<String>,
+    /// The tokens the session key may move. Other tokens can't be moved at all.
+    pub spend_caps: Vec<SpendCap>,
+    /// The first rollup height at which the session key is no longer valid.
+    pub expires_at: u64,
+}
+
+impl<S: Spec> SessionKeyGrant<S> {
+    /// Checks that the session key may sign, at `height`, the call named `call` moving `spend`.
+    pub fn authorize(
+        &self,
+        session_key: &CredentialId,
+        call: &str,
+        spend: &[Coins],
+        height: u64,
+    ) -> Result<()> {
+        if height >= self.expires_at {
+            bail!(
+                "Session key {session_key} expired at height {}, current height is {height}",
+                self.expires_at
+            );
+        }
+
+        if !self.allowed_calls.iter().any(|allowed| allowed == call) {
+            bail!("Session key {session_key} is not allowed to call {call}");
+        }
+
+        self.spent_after(session_key, spend)?;
+        Ok(())
+    }
+
+    fn spent_after(&self, session_key: &CredentialId, spend: &[Coins]) -> Result<Vec<SpendCap>> {
+        let mut caps = self.spend_caps.clone();
+        for coins in spend.iter().filter(|coins| coins.amount != Amount::ZERO) {
+            let Some(cap) = caps.iter_mut().find(|cap| cap.token_id == coins.token_id) else {
+                bail!("Session key {session_key} may not move {}", coins.token_id);
+            };
+            match cap.spent.checked_add(coins.amount) {
+                Some(spent) if spent <= cap.cap => cap.spent = spent,
+                _ => bail!(
+                    "Session key {session_key} may move {} more {}, got {}",
+                    cap.cap.checked_sub(cap.spent).unwrap_or(Amount::ZERO),
+                    coins.token_id,
+                    coins.amount
+                ),
+            }
+        }
+        Ok(caps)
+    }
+}
+
+/// The credential of the transactions signed by a session key. The authenticator attaches it to
+/// the transaction, so the runtime knows which grant to charge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionKeyCredential {
+    /// The credential ID of the session key.
+    pub session_key: CredentialId,
+}
+
+/// Keeps the session keys granted by rollup accounts.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct SessionKeys<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// Active grants, keyed by the credential ID of the session key.
+    #[state]
+    pub grants: StateMap<CredentialId, SessionKeyGrant<S>>,
+
+    /// Used to read the current rollup height.
+    #[module]
+    pub chain_state: sov_chain_state::ChainState<S>,
+}
+
+impl<S: Spec> SessionKeys<S> {
+    /// Returns the grant of `session_key`.
+    pub fn grant(
+        &self,
+        session_key: &CredentialId,
+        state: &mut impl StateReader<User>,
+    ) -> Result<SessionKeyGrant<S>> {
+        let Some(grant) = self.grants.get(session_key, state)? else {
+            bail!("No session key grant for credential {session_key}");
+        };
+        Ok(grant)
+    }
+
+    /// Checks that `session_key` may sign the call named `call` moving `spend` and returns the
+    /// account the transaction should be executed as.
+    pub fn check(
+        &self,
+        session_key: &CredentialId,
+        call: &str,
+        spend: &[Coins],
+        state: &mut impl StateReader<User>,
+    ) -> Result<S::Address> {
+        let grant = self.grant(session_key, state)?;
+        let height = self.chain_state.rollup_height(state)?.get();
+        grant.authorize(session_key, call, spend, height)?;
+        Ok(grant.owner)
+    }
+
+    /// Adds `spend` to the tokens moved by `session_key`. Fails if it exceeds a spend cap of the
+    /// grant, or moves a token the grant doesn't cap.
+    pub fn record_spend(
+        &self,
+        session_key: &CredentialId,
+        spend: &[Coins],
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let mut grant = self.grant(session_key, state)?;
+        grant.spend_caps = grant.spent_after(session_key, spend)?;
+        self.grants.set(session_key, &grant, state)?;
+        Ok(())
+    }
+}
+
+impl<S: Spec> Module for SessionKeys<S> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = CallMessage;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        // Otherwise a session key could renew or widen its own grant, or revoke the others.
+        if context
+            .get_sender_credential::<SessionKeyCredential>()
+            .is_some()
+        {
+            bail!("Session keys can't be managed by session keys");
+        }
+
+        match msg {
+            CallMessage::Authorize {
+                session_key,
+                allowed_calls,
+                spend_caps,
+                expires_at,
+            } => {
+                if allowed_calls.is_empty() || allowed_calls.len() > MAX_ALLOWED_CALLS {
+                    bail!(
+                        "A session key must allow between 1 and {MAX_ALLOWED_CALLS} calls, got {}",
+                        allowed_calls.len()
+                    );
+                }
+                if spend_caps.len() > MAX_SPEND_CAPS {
+                    bail!(
+                        "A session key can cap at most {MAX_SPEND_CAPS} tokens, got {}",
+                        spend_caps.len()
+                    );
+                }
+                for (i, coins) in spend_caps.iter().enumerate() {
+                    if spend_caps[..i].iter().any(|c| c.token_id == coins.token_id) {
+                        bail!("Token {} is capped more than once", coins.token_id);
+                    }
+                }
+
+                let height = self.chain_state.rollup_height(state)?.get();
+                if expires_at <= height {
+                    bail!("Expiry height {expires_at} is not above the current height {height}");
+                }
+
+                if let Some(existing) = self.grants.get(&session_key, state)? {
+                    if &existing.owner != context.sender() {
+                        bail!("Session key {session_key} is already granted by another account");
+                    }
+                }
+
+                let grant = SessionKeyGrant {
+                    owner: context.sender().clone(),
+                    allowed_calls,
+                    spend_caps: spend_caps
+                        .into_iter()
+                        .map(|coins| SpendCap {
+                            token_id: coins.token_id,
+                            cap: coins.amount,
+                            spent: Amount::ZERO,
+                        })
+                        .collect(),
+                    expires_at,
+                };
+                self.grants.set(&session_key, &grant, state)?;
+                self.emit_event(state, Event::Authorized { session_key, grant });
+
+                Ok(())
+            }
+            CallMessage::Revoke { session_key } => {
+                let Some(grant) = self.grants.get(&session_key, state)? else {
+                    bail!("No session key grant for credential {session_key}");
+                };
+                if &grant.owner != context.sender() {
+                    bail!("Only {} can revoke session key {session_key}", grant.owner);
+                }
+
+                self.grants.remove(&session_key, state)?;
+                self.emit_event(
+                    state,
+                    Event::Revoked {
+                        session_key,
+                        owner: grant.owner,
+                    },
+                );
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum CallMessage {
+    /// Authorizes `session_key` to sign the `allowed_calls` on behalf of the sender, moving at
+    /// most `spend_caps` of each token. Authorizing an already granted key replaces its limits
+    /// and resets its spending.
+    Authorize {
+        session_key: CredentialId,
+        allowed_calls: Vec<String>,
+        spend_caps: Vec<Coins>,
+        expires_at: u64,
+    },
+    /// Revokes a session key previously granted by the sender.
+    Revoke { session_key: CredentialId },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// A session key was granted or its limits were updated.
+    Authorized {
+        session_key: CredentialId,
+        grant: SessionKeyGrant<S>,
+    },
+    /// A session key was revoked by its owner.
+    Revoked {
+        session_key: CredentialId,
+        owner: S::Address,
+    },
+}
@@ -0,0 +1,283 @@
+use session_keys::{CallMessage, Event, SessionKeyGrant, SessionKeys, SpendCap};
+use sov_bank::{Coins, TokenId};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::{Amount, CredentialId, Spec};
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    session_keys: SessionKeys<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+const SESSION_KEY: CredentialId = CredentialId([7; 32]);
+const EXPIRES_AT: u64 = 100;
+
+pub struct TestData<S: Spec> {
+    pub owner: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let owner = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(genesis_config.into(), ());
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { owner, other_user }, runner)
+}
+
+/// The call session keys are allowed to sign in these tests. The module doesn't interpret the
+/// names, the runtime does.
+const ALLOWED_CALL: &str = "bank.transfer";
+/// A call session keys are not allowed to sign.
+const OTHER_CALL: &str = "warp.transfer_remote";
+
+fn gas_token() -> TokenId {
+    config_value!("GAS_TOKEN_ID")
+}
+
+/// A token the grants of these tests don't cap.
+fn other_token(owner: &<S as Spec>::Address) -> TokenId {
+    sov_bank::get_token_id::<S>("other", Some(6), owner)
+}
+
+fn gas(amount: u128) -> Vec<Coins> {
+    vec![Coins {
+        amount: Amount::new(amount),
+        token_id: gas_token(),
+    }]
+}
+
+fn authorize_with(allowed_calls: &[&str], spend_caps: Vec<Coins>) -> CallMessage {
+    CallMessage::Authorize {
+        session_key: SESSION_KEY,
+        allowed_calls: allowed_calls.iter().map(|call| call.to_string()).collect(),
+        spend_caps,
+        expires_at: EXPIRES_AT,
+    }
+}
+
+fn authorize(allowed_calls: &[&str]) -> CallMessage {
+    authorize_with(allowed_calls, gas(1_000))
+}
+
+fn grant(owner: <S as Spec>::Address, spent: u128) -> SessionKeyGrant<S> {
+    SessionKeyGrant {
+        owner,
+        allowed_calls: vec![ALLOWED_CALL.to_string()],
+        spend_caps: vec![SpendCap {
+            token_id: gas_token(),
+            cap: Amount::new(1_000),
+            spent: Amount::new(spent),
+        }],
+        expires_at: EXPIRES_AT,
+    }
+}
+
+fn authorize_as_owner(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>) {
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .owner
+            .create_plain_message::<TestRuntime<S>, SessionKeys<S>>(authorize(&[ALLOWED_CALL])),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+}
+
+#[test]
+fn test_authorize_session_key() {
+    let (test_data, mut runner) = setup();
+    let owner = test_data.owner.address();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .owner
+            .create_plain_message::<TestRuntime<S>, SessionKeys<S>>(authorize(&[ALLOWED_CALL])),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+
+            let expected_grant = grant(owner, 0);
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::SessionKeys(Event::Authorized {
+                    session_key: SESSION_KEY,
+                    grant: expected_grant,
+                })]
+            );
+
+            let session_keys = SessionKeys::<S>::default();
+            assert_eq!(
+                session_keys
+                    .check(&SESSION_KEY, ALLOWED_CALL, &gas(1_000), state)
+                    .unwrap(),
+                owner
+            );
+            assert!(session_keys
+                .check(&SESSION_KEY, OTHER_CALL, &[], state)
+                .is_err());
+            assert!(session_keys
+                .check(&SESSION_KEY, ALLOWED_CALL, &gas(1_001), state)
+                .is_err());
+        }),
+    });
+}
+
+#[test]
+fn test_only_owner_can_revoke() {
+    let (test_data, mut runner) = setup();
+    authorize_as_owner(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, SessionKeys<S>>(CallMessage::Revoke {
+                session_key: SESSION_KEY,
+            }),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .owner
+            .create_plain_message::<TestRuntime<S>, SessionKeys<S>>(CallMessage::Revoke {
+                session_key: SESSION_KEY,
+            }),
+        assert: Box::new(|result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(SessionKeys::<S>::default()
+                .check(&SESSION_KEY, ALLOWED_CALL, &[], state)
+                .is_err());
+        }),
+    });
+}
+
+#[test]
+fn test_cannot_take_over_granted_key() {
+    let (test_data, mut runner) = setup();
+    authorize_as_owner(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, SessionKeys<S>>(authorize(&[OTHER_CALL])),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_empty_scope_is_rejected() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .owner
+            .create_plain_message::<TestRuntime<S>, SessionKeys<S>>(authorize(&[])),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_grant_expiry() {
+    let (test_data, _runner) = setup();
+    let grant = grant(test_data.owner.address(), 0);
+
+    assert!(grant
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &[], EXPIRES_AT - 1)
+        .is_ok());
+    assert!(grant
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &[], EXPIRES_AT)
+        .is_err());
+}
+
+#[test]
+fn test_grant_scope() {
+    let (test_data, _runner) = setup();
+    let grant = grant(test_data.owner.address(), 0);
+
+    assert!(grant.authorize(&SESSION_KEY, OTHER_CALL, &[], 0).is_err());
+}
+
+#[test]
+fn test_spend_cap_is_cumulative() {
+    let (test_data, _runner) = setup();
+
+    // Spending is counted against the cap across transactions.
+    let partly_spent = grant(test_data.owner.address(), 600);
+    assert!(partly_spent
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &gas(400), 0)
+        .is_ok());
+    assert!(partly_spent
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &gas(401), 0)
+        .is_err());
+
+    // An exhausted grant can still send calls that don't move tokens.
+    let exhausted = grant(test_data.owner.address(), 1_000);
+    assert!(exhausted
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &[], 0)
+        .is_ok());
+    assert!(exhausted
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &gas(1), 0)
+        .is_err());
+}
+
+#[test]
+fn test_spend_caps_are_per_token() {
+    let (test_data, _runner) = setup();
+    let owner = test_data.owner.address();
+    let grant = grant(owner.clone(), 0);
+
+    // A token the grant doesn't cap can't be moved at all.
+    let other = Coins {
+        amount: Amount::new(1),
+        token_id: other_token(&owner),
+    };
+    assert!(grant
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &[other], 0)
+        .is_err());
+
+    // Two amounts of the same token in one call count together.
+    let mut twice = gas(500);
+    twice.extend(gas(501));
+    assert!(grant
+        .authorize(&SESSION_KEY, ALLOWED_CALL, &twice, 0)
+        .is_err());
+}
+
+#[test]
+fn test_token_capped_twice_is_rejected() {
+    let (test_data, mut runner) = setup();
+    let mut spend_caps = gas(1_000);
+    spend_caps.extend(gas(10));
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .owner
+            .create_plain_message::<TestRuntime<S>, SessionKeys<S>>(authorize_with(
+                &[ALLOWED_CALL],
+                spend_caps,
+            )),
+        assert: Box::new(|result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
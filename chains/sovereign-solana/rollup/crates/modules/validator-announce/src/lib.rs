@@ -0,0 +1,239 @@
+//! Hyperlane ValidatorAnnounce.
+//!
+//! Validators signing checkpoints of the rollup's merkle tree hook publish where they store their
+//! signatures, so relayers can find them without out-of-band configuration. An announcement is
+//! signed by the validator key over the same digest as the Solidity `ValidatorAnnounce` contract,
+//! so validator agents don't need a Sovereign-specific signing path. The local domain in the
+//! digest is the `HYPERLANE_BRIDGE_DOMAIN` constant used by the mailbox.
+use alloy_primitives::{keccak256, Signature};
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Context, EventEmitter, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec,
+    StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+use std::marker::PhantomData;
+
+/// The maximum length of a storage location, in bytes.
+pub const MAX_STORAGE_LOCATION_LEN: usize = 1024;
+
+/// The maximum number of storage locations a validator can announce.
+pub const MAX_STORAGE_LOCATIONS: usize = 16;
+
+/// Genesis configuration of the [`ValidatorAnnounce`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct ValidatorAnnounceConfig {
+    /// The mailbox address validators commit to in their announcements.
+    pub mailbox: HexHash,
+}
+
+/// Stores the checkpoint storage locations announced by Hyperlane validators.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct ValidatorAnnounce<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The mailbox address included in announcement digests.
+    #[state]
+    pub mailbox: StateValue<HexHash>,
+
+    /// Every validator that announced at least one location, by announcement order.
+    #[state]
+    pub validators: StateMap<u64, HexHash>,
+
+    /// The number of entries of `validators`.
+    #[state]
+    pub validator_count: StateValue<u64>,
+
+    /// The storage locations of each validator, in announcement order.
+    #[state]
+    pub storage_locations: StateMap<HexHash, Vec<String>>,
+
+    #[phantom]
+    phantom: PhantomData<S>,
+}
+
+impl<S: Spec> ValidatorAnnounce<S> {
+    /// Returns the validators that announced a storage location.
+    pub fn announced_validators(&self, state: &mut impl StateReader<User>) -> Result<Vec<HexHash>> {
+        let count = self.validator_count.get(state)?.unwrap_or_default();
+        (0..count)
+            .map(|index| {
+                self.validators
+                    .get(&index, state)?
+                    .with_context(|| format!("Validator {index} is missing"))
+            })
+            .collect()
+    }
+
+    /// Returns the storage locations announced by each of `validators`, in the same order.
+    pub fn announced_storage_locations(
+        &self,
+        validators: &[HexHash],
+        state: &mut impl StateReader<User>,
+    ) -> Result<Vec<Vec<String>>> {
+        validators
+            .iter()
+            .map(|validator| {
+                Ok(self
+                    .storage_locations
+                    .get(validator, state)?
+                    .unwrap_or_default())
+            })
+            .collect()
+    }
+
+    /// Returns the digest a validator signs, with an EIP-191 prefix, to announce `storage_location`.
+    pub fn announcement_digest(
+        &self,
+        storage_location: &str,
+        state: &mut impl StateReader<User>,
+    ) -> Result<[u8; 32]> {
+        let Some(mailbox) = self.mailbox.get(state)? else {
+            bail!("The validator announce module is not initialized");
+        };
+        Ok(announcement_digest(
+            config_value!("HYPERLANE_BRIDGE_DOMAIN"),
+            &mailbox,
+            storage_location,
+        ))
+    }
+}
+
+/// The Hyperlane address of the module, `keccak256("validator_announce")`. Agents are configured
+/// with it as the `validatorAnnounce` address of the rollup.
+pub fn address() -> HexHash {
+    HexString(keccak256(b"validator_announce").0)
+}
+
+/// `keccak256(keccak256(local_domain ‖ mailbox ‖ "HYPERLANE_ANNOUNCEMENT") ‖ storage_location)`,
+/// as computed by the Solidity `ValidatorAnnounce` contract.
+pub fn announcement_digest(
+    local_domain: u32,
+    mailbox: &HexHash,
+    storage_location: &str,
+) -> [u8; 32] {
+    let mut domain = Vec::with_capacity(4 + 32 + 22);
+    domain.extend_from_slice(&local_domain.to_be_bytes());
+    domain.extend_from_slice(&mailbox.0);
+    domain.extend_from_slice(b"HYPERLANE_ANNOUNCEMENT");
+    let domain_hash = keccak256(&domain);
+
+    let mut announcement = domain_hash.to_vec();
+    announcement.extend_from_slice(storage_location.as_bytes());
+    keccak256(&announcement).0
+}
+
+impl<S: Spec> Module for ValidatorAnnounce<S> {
+    type Spec = S;
+
+    type Config = ValidatorAnnounceConfig;
+
+    type CallMessage = CallMessage;
+
+    type Event = Event;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.mailbox.set(&config.mailbox, state)?;
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        _context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::Announce {
+                validator,
+                storage_location,
+                signature,
+            } => {
+                if storage_location.is_empty() || storage_location.len() > MAX_STORAGE_LOCATION_LEN
+                {
+                    bail!(
+                        "A storage location must be between 1 and {MAX_STORAGE_LOCATION_LEN} bytes long"
+                    );
+                }
+
+                let digest = self.announcement_digest(&storage_location, state)?;
+                let signature = Signature::try_from(signature.0.as_slice())
+                    .map_err(|e| anyhow::anyhow!("Invalid announcement signature: {e}"))?;
+                let signer = signature
+                    .recover_address_from_msg(digest)
+                    .map_err(|e| anyhow::anyhow!("Invalid announcement signature: {e}"))?;
+                if signer.into_word().0 != validator.0 {
+                    bail!("The announcement for {validator} is signed by {signer}");
+                }
+
+                let mut locations = self
+                    .storage_locations
+                    .get(&validator, state)?
+                    .unwrap_or_default();
+                if locations.contains(&storage_location) {
+                    bail!("{validator} already announced {storage_location}");
+                }
+                if locations.len() >= MAX_STORAGE_LOCATIONS {
+                    bail!(
+                        "{validator} already announced {MAX_STORAGE_LOCATIONS} storage locations"
+                    );
+                }
+
+                if locations.is_empty() {
+                    let count = self.validator_count.get(state)?.unwrap_or_default();
+                    self.validators.set(&count, &validator, state)?;
+                    self.validator_count.set(&(count + 1), state)?;
+                }
+                locations.push(storage_location.clone());
+                self.storage_locations.set(&validator, &locations, state)?;
+
+                self.emit_event(
+                    state,
+                    Event::ValidatorAnnounced {
+                        validator,
+                        storage_location,
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum CallMessage {
+    /// Publishes `storage_location` for `validator`, the Ethereum address of the validator
+    /// left-padded to 32 bytes. `signature` is the 65 byte `r ‖ s ‖ v` signature of the
+    /// validator over the EIP-191 hash of [`announcement_digest`]. Anyone can submit it.
+    Announce {
+        validator: HexHash,
+        storage_location: String,
+        signature: HexString,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    /// A validator published a new storage location.
+    ValidatorAnnounced {
+        validator: HexHash,
+        storage_location: String,
+    },
+}
@@ -0,0 +1,167 @@
+use alloy_primitives::{eip191_hash_message, Address};
+use k256::ecdsa::SigningKey;
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::Spec;
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+use validator_announce::{
+    address, announcement_digest, CallMessage, Event, ValidatorAnnounce, ValidatorAnnounceConfig,
+};
+
+type S = TestSpec;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    validator_announce: ValidatorAnnounce<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+const STORAGE_LOCATION: &str = "file:///tmp/sovereign-checkpoints";
+
+pub struct TestData<S: Spec> {
+    pub relayer: TestUser<S>,
+}
+
+fn mailbox() -> HexHash {
+    HexString([0; 32])
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(1);
+
+    let relayer = genesis_config.additional_accounts()[0].clone();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        ValidatorAnnounceConfig { mailbox: mailbox() },
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { relayer }, runner)
+}
+
+fn validator_key() -> SigningKey {
+    SigningKey::from_slice(&[0x42; 32]).unwrap()
+}
+
+fn validator(key: &SigningKey) -> HexHash {
+    HexString(Address::from_public_key(key.verifying_key()).into_word().0)
+}
+
+fn sign(key: &SigningKey, storage_location: &str) -> HexString {
+    let digest = announcement_digest(
+        config_value!("HYPERLANE_BRIDGE_DOMAIN"),
+        &mailbox(),
+        storage_location,
+    );
+    let (signature, recovery_id) = key
+        .sign_prehash_recoverable(eip191_hash_message(digest).as_slice())
+        .unwrap();
+
+    let mut bytes = signature.to_bytes().to_vec();
+    bytes.push(27 + recovery_id.to_byte());
+    HexString(bytes)
+}
+
+fn announce(validator: HexHash, signature: HexString) -> CallMessage {
+    CallMessage::Announce {
+        validator,
+        storage_location: STORAGE_LOCATION.to_string(),
+        signature,
+    }
+}
+
+#[test]
+fn test_announce_stores_location() {
+    let (test_data, mut runner) = setup();
+    let key = validator_key();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .relayer
+            .create_plain_message::<TestRuntime<S>, ValidatorAnnounce<S>>(announce(
+                validator(&key),
+                sign(&key, STORAGE_LOCATION),
+            )),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::ValidatorAnnounce(
+                    Event::ValidatorAnnounced {
+                        validator: validator(&key),
+                        storage_location: STORAGE_LOCATION.to_string(),
+                    }
+                )]
+            );
+
+            let module = ValidatorAnnounce::<S>::default();
+            assert_eq!(
+                module.announced_validators(state).unwrap(),
+                vec![validator(&key)]
+            );
+            assert_eq!(
+                module
+                    .announced_storage_locations(&[validator(&key)], state)
+                    .unwrap(),
+                vec![vec![STORAGE_LOCATION.to_string()]]
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_announce_rejects_replay() {
+    let (test_data, mut runner) = setup();
+    let key = validator_key();
+
+    for expect_success in [true, false] {
+        runner.execute_transaction(TransactionTestCase {
+            input: test_data
+                .relayer
+                .create_plain_message::<TestRuntime<S>, ValidatorAnnounce<S>>(announce(
+                    validator(&key),
+                    sign(&key, STORAGE_LOCATION),
+                )),
+            assert: Box::new(move |result, _state| {
+                assert_eq!(result.tx_receipt.is_successful(), expect_success);
+            }),
+        });
+    }
+}
+
+#[test]
+fn test_announce_rejects_other_signer() {
+    let (test_data, mut runner) = setup();
+    let other_key = SigningKey::from_slice(&[0x43; 32]).unwrap();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .relayer
+            .create_plain_message::<TestRuntime<S>, ValidatorAnnounce<S>>(announce(
+                validator(&validator_key()),
+                sign(&other_key, STORAGE_LOCATION),
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_agent_config_uses_module_address() {
+    let config: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string("../../../../../../agents/config.json").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        config["chains"]["sovereign"]["validatorAnnounce"],
+        address().to_string()
+    );
+}
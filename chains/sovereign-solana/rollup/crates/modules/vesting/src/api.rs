@@ -0,0 +1,85 @@
+//! REST endpoints serving the locked and claimable amounts of grants.
+//!
+//! The module REST API serves the grants as stored, which doesn't say how much of them is
+//! released. These endpoints evaluate the schedules at the current rollup height and time.
+use sov_bank::TokenId;
+use sov_modules_api::prelude::axum::extract::{Path, State};
+use sov_modules_api::prelude::axum::http::StatusCode;
+use sov_modules_api::prelude::axum::routing::get;
+use sov_modules_api::prelude::axum::{self, Json};
+use sov_modules_api::rest::ApiState;
+use sov_modules_api::Spec;
+
+use crate::{GrantAmounts, Vesting};
+
+/// The path of the amounts of a grant.
+pub const GRANT_AMOUNTS_PATH: &str = "/rollup/vesting/grants/:id/amounts";
+
+/// The path of the amounts of every grant of a beneficiary.
+pub const BENEFICIARY_AMOUNTS_PATH: &str = "/rollup/vesting/beneficiaries/:address/amounts";
+
+/// The amounts of a grant of a beneficiary.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BeneficiaryGrant {
+    /// The id of the grant.
+    pub id: u64,
+    /// The locked token.
+    pub token_id: TokenId,
+    /// The current amounts of the grant.
+    #[serde(flatten)]
+    pub amounts: GrantAmounts,
+}
+
+/// Returns an axum router serving [`GRANT_AMOUNTS_PATH`] and [`BENEFICIARY_AMOUNTS_PATH`].
+pub fn axum_router<S: Spec>(api_state: ApiState<S>) -> axum::Router<()> {
+    axum::Router::new()
+        .route(GRANT_AMOUNTS_PATH, get(grant_amounts::<S>))
+        .route(BENEFICIARY_AMOUNTS_PATH, get(beneficiary_amounts::<S>))
+        .with_state(api_state)
+}
+
+async fn grant_amounts<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+    Path(id): Path<u64>,
+) -> Result<Json<GrantAmounts>, (StatusCode, String)> {
+    let mut state = api_state.default_api_state_accessor();
+    Vesting::<S>::default()
+        .amounts(id, &mut state)
+        .map(Json)
+        .map_err(|e| (StatusCode::NOT_FOUND, format!("{e:#}")))
+}
+
+async fn beneficiary_amounts<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+    Path(address): Path<String>,
+) -> Result<Json<Vec<BeneficiaryGrant>>, (StatusCode, String)> {
+    let internal_error = |e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"));
+
+    let address: S::Address = address.parse().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid address {address}"),
+        )
+    })?;
+    let mut state = api_state.default_api_state_accessor();
+    let vesting = Vesting::<S>::default();
+
+    let ids = vesting
+        .beneficiary_grants
+        .get(&address, &mut state)
+        .map_err(|e| internal_error(e.into()))?
+        .unwrap_or_default();
+    let mut grants = Vec::with_capacity(ids.len());
+    for id in ids {
+        let grant = vesting.grant(id, &mut state).map_err(internal_error)?;
+        let now = vesting
+            .now(grant.clock, &mut state)
+            .map_err(internal_error)?;
+        grants.push(BeneficiaryGrant {
+            id,
+            token_id: grant.token_id,
+            amounts: grant.amounts(now),
+        });
+    }
+    Ok(Json(grants))
+}
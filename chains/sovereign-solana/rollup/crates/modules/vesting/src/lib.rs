@@ -0,0 +1,566 @@
+//! Token vesting and timelocked balances.
+//!
+//! A grant locks an amount of a `sov_bank` token for a beneficiary, and releases it under a
+//! schedule measured either in rollup heights or in seconds of the `chain_state` time. A cliff
+//! schedule releases everything at once, which makes it a timelocked balance. A linear schedule
+//! releases the amount continuously between its start and end, with nothing released before its
+//! cliff. The beneficiary claims the released amount with [`CallMessage::Claim`].
+//!
+//! The locked tokens are held by the vesting account, [`Vesting::address`]. Genesis grants, such
+//! as team and investor allocations of the gas token, are funded by crediting that account in the
+//! bank genesis. Grants created later are funded by their creator.
+//!
+//! A grant created by another account than its beneficiary starts as an offer, which only counts
+//! against [`MAX_GRANTS_PER_BENEFICIARY`] once the beneficiary accepts it with
+//! [`CallMessage::Accept`]. Until then its creator can take the tokens back with
+//! [`CallMessage::Withdraw`]. Fully claimed grants no longer count against the limit either.
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_bank::{Bank, Coins, TokenId};
+use sov_modules_api::macros::{serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, CryptoSpec, EventEmitter, GenesisState, Module, ModuleId,
+    ModuleInfo, ModuleRestApi, Spec, StateMap, StateReader, StateReaderAndWriter, StateValue,
+    TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+
+#[cfg(feature = "native")]
+pub mod api;
+
+/// The maximum number of grants a beneficiary holds unclaimed tokens of.
+pub const MAX_GRANTS_PER_BENEFICIARY: usize = 16;
+
+/// What a vesting schedule is measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum Clock {
+    /// The rollup height.
+    Height,
+    /// The `chain_state` time, in seconds since the unix epoch.
+    Time,
+}
+
+/// When the tokens of a grant are released, in units of its [`Clock`].
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// Everything is released at `at`.
+    Cliff { at: u64 },
+    /// The tokens are released linearly from `start` to `end`, but nothing is released before
+    /// `cliff`.
+    Linear { start: u64, cliff: u64, end: u64 },
+}
+
+impl Schedule {
+    fn validate(&self) -> Result<()> {
+        if let Schedule::Linear { start, cliff, end } = self {
+            if !(start < end && start <= cliff && cliff <= end) {
+                bail!("A linear schedule must satisfy start <= cliff <= end and start < end");
+            }
+        }
+        Ok(())
+    }
+
+    /// The part of `total` released at `now`.
+    pub fn vested(&self, total: Amount, now: u64) -> Amount {
+        match *self {
+            Schedule::Cliff { at } if now < at => Amount::ZERO,
+            Schedule::Cliff { .. } => total,
+            Schedule::Linear { cliff, .. } if now < cliff => Amount::ZERO,
+            Schedule::Linear { end, .. } if now >= end => total,
+            Schedule::Linear { start, end, .. } => {
+                // Splitting `total` avoids overflowing `total * elapsed`. Both terms are exact, so
+                // the result is `floor(total * elapsed / span)`.
+                let (total, elapsed, span) =
+                    (total.0, (now - start) as u128, (end - start) as u128);
+                Amount::new(total / span * elapsed + total % span * elapsed / span)
+            }
+        }
+    }
+}
+
+/// A grant before it is created.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "NewGrant")]
+pub struct NewGrant<S: Spec> {
+    /// The account allowed to claim the released tokens.
+    pub beneficiary: S::Address,
+    /// The locked token.
+    pub token_id: TokenId,
+    /// The amount locked.
+    pub total: Amount,
+    /// What `schedule` is measured in.
+    pub clock: Clock,
+    /// When the tokens are released.
+    pub schedule: Schedule,
+}
+
+impl<S: Spec> NewGrant<S> {
+    fn validate(&self) -> Result<()> {
+        if self.total == Amount::ZERO {
+            bail!("A grant can't lock zero {}", self.token_id);
+        }
+        self.schedule.validate()
+    }
+}
+
+/// A grant of locked tokens.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Grant")]
+pub struct Grant<S: Spec> {
+    /// The account allowed to claim the released tokens.
+    pub beneficiary: S::Address,
+    /// The locked token.
+    pub token_id: TokenId,
+    /// The amount locked when the grant was created.
+    pub total: Amount,
+    /// The amount already claimed.
+    pub claimed: Amount,
+    /// What `schedule` is measured in.
+    pub clock: Clock,
+    /// When the tokens are released.
+    pub schedule: Schedule,
+}
+
+/// A grant created by another account than its beneficiary, waiting for the beneficiary to accept
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Offer")]
+pub struct Offer<S: Spec> {
+    /// The account that locked the tokens, which gets them back if it withdraws the offer.
+    pub creator: S::Address,
+    /// The grant created when the beneficiary accepts.
+    pub grant: NewGrant<S>,
+}
+
+/// The amounts of a grant at a point of its schedule.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GrantAmounts {
+    /// The amount locked when the grant was created.
+    pub total: Amount,
+    /// The amount not released yet.
+    pub locked: Amount,
+    /// The amount released and not claimed yet.
+    pub claimable: Amount,
+    /// The amount already claimed.
+    pub claimed: Amount,
+}
+
+impl<S: Spec> Grant<S> {
+    /// The amounts of the grant at `now`, in units of its clock.
+    pub fn amounts(&self, now: u64) -> GrantAmounts {
+        let vested = self.schedule.vested(self.total, now);
+        GrantAmounts {
+            total: self.total,
+            locked: Amount::new(self.total.0 - vested.0),
+            claimable: Amount::new(vested.0.saturating_sub(self.claimed.0)),
+            claimed: self.claimed,
+        }
+    }
+}
+
+/// Genesis configuration of the [`Vesting`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "VestingConfig")]
+pub struct VestingConfig<S: Spec> {
+    /// The grants created at genesis. The bank genesis must credit [`Vesting::address`] with the
+    /// total of each token they lock.
+    pub grants: Vec<NewGrant<S>>,
+}
+
+/// Locks tokens and releases them to their beneficiaries under a schedule.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct Vesting<S: Spec> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The grants, by id.
+    #[state]
+    pub grants: StateMap<u64, Grant<S>>,
+
+    /// The id of the next grant.
+    #[state]
+    pub next_grant_id: StateValue<u64>,
+
+    /// The ids of the grants of each beneficiary that aren't fully claimed.
+    #[state]
+    pub beneficiary_grants: StateMap<S::Address, Vec<u64>>,
+
+    /// The offers not accepted or withdrawn yet, by the id of their grant.
+    #[state]
+    pub offers: StateMap<u64, Offer<S>>,
+
+    /// Used to read the current rollup height and time.
+    #[module]
+    pub chain_state: sov_chain_state::ChainState<S>,
+
+    /// Used to hold and release the locked tokens.
+    #[module]
+    pub bank: Bank<S>,
+}
+
+impl<S: Spec> Vesting<S> {
+    /// The account holding the locked tokens.
+    pub fn address() -> S::Address {
+        use sov_modules_api::digest::Digest;
+
+        let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+        hasher.update(b"vesting");
+        CredentialId(hasher.finalize().into()).into()
+    }
+
+    /// The current value of `clock`.
+    pub fn now(&self, clock: Clock, state: &mut impl TxState<S>) -> Result<u64> {
+        Ok(match clock {
+            Clock::Height => self.chain_state.rollup_height(state)?.get(),
+            // Times before the epoch are treated as the epoch, nothing is released then.
+            Clock::Time => u64::try_from(self.chain_state.get_time(state)?.secs()).unwrap_or(0),
+        })
+    }
+
+    /// The current amounts of grant `id`.
+    pub fn amounts(&self, id: u64, state: &mut impl TxState<S>) -> Result<GrantAmounts> {
+        let grant = self.grant(id, state)?;
+        let now = self.now(grant.clock, state)?;
+        Ok(grant.amounts(now))
+    }
+
+    fn grant(&self, id: u64, state: &mut impl StateReader<User>) -> Result<Grant<S>> {
+        self.grants
+            .get(&id, state)?
+            .with_context(|| format!("Grant {id} does not exist"))
+    }
+
+    fn offer(&self, id: u64, state: &mut impl StateReader<User>) -> Result<Offer<S>> {
+        self.offers
+            .get(&id, state)?
+            .with_context(|| format!("Offer {id} does not exist"))
+    }
+
+    fn next_id(&mut self, state: &mut impl StateReaderAndWriter<User>) -> Result<u64> {
+        let id = self.next_grant_id.get(state)?.unwrap_or_default();
+        self.next_grant_id.set(&(id + 1), state)?;
+        Ok(id)
+    }
+
+    fn insert_grant(
+        &mut self,
+        id: u64,
+        grant: NewGrant<S>,
+        state: &mut impl StateReaderAndWriter<User>,
+    ) -> Result<()> {
+        let mut ids = self
+            .beneficiary_grants
+            .get(&grant.beneficiary, state)?
+            .unwrap_or_default();
+        if ids.len() >= MAX_GRANTS_PER_BENEFICIARY {
+            bail!(
+                "{} already has {MAX_GRANTS_PER_BENEFICIARY} grants",
+                grant.beneficiary
+            );
+        }
+
+        ids.push(id);
+        self.beneficiary_grants
+            .set(&grant.beneficiary, &ids, state)?;
+        self.grants.set(
+            &id,
+            &Grant {
+                beneficiary: grant.beneficiary,
+                token_id: grant.token_id,
+                total: grant.total,
+                claimed: Amount::ZERO,
+                clock: grant.clock,
+                schedule: grant.schedule,
+            },
+            state,
+        )?;
+        Ok(())
+    }
+
+    fn create(
+        &mut self,
+        grant: NewGrant<S>,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        grant.validate()?;
+        let coins = Coins {
+            amount: grant.total,
+            token_id: grant.token_id,
+        };
+        let (beneficiary, token_id, total) =
+            (grant.beneficiary.clone(), grant.token_id, grant.total);
+        self.bank
+            .transfer_from(context.sender(), &Self::address(), coins, state)
+            .with_context(|| format!("Failed to lock {total} of {token_id}"))?;
+
+        let id = self.next_id(state)?;
+        if &beneficiary == context.sender() {
+            self.insert_grant(id, grant, state)?;
+            self.emit_event(
+                state,
+                Event::GrantCreated {
+                    id,
+                    beneficiary,
+                    token_id,
+                    total,
+                },
+            );
+        } else {
+            let creator = context.sender().clone();
+            self.offers.set(
+                &id,
+                &Offer {
+                    creator: creator.clone(),
+                    grant,
+                },
+                state,
+            )?;
+            self.emit_event(
+                state,
+                Event::GrantOffered {
+                    id,
+                    creator,
+                    beneficiary,
+                    token_id,
+                    total,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn accept(&mut self, id: u64, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let Offer { grant, .. } = self.offer(id, state)?;
+        if &grant.beneficiary != context.sender() {
+            bail!(
+                "Only the beneficiary {} can accept offer {id}",
+                grant.beneficiary
+            );
+        }
+
+        self.offers.remove(&id, state)?;
+        let (beneficiary, token_id, total) =
+            (grant.beneficiary.clone(), grant.token_id, grant.total);
+        self.insert_grant(id, grant, state)?;
+
+        self.emit_event(
+            state,
+            Event::GrantCreated {
+                id,
+                beneficiary,
+                token_id,
+                total,
+            },
+        );
+        Ok(())
+    }
+
+    fn withdraw(
+        &mut self,
+        id: u64,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let Offer { creator, grant } = self.offer(id, state)?;
+        if &creator != context.sender() {
+            bail!("Only the creator {creator} can withdraw offer {id}");
+        }
+
+        self.offers.remove(&id, state)?;
+        let coins = Coins {
+            amount: grant.total,
+            token_id: grant.token_id,
+        };
+        self.bank
+            .transfer_from(&Self::address(), &creator, coins, state)
+            .with_context(|| format!("The vesting account is out of {}", grant.token_id))?;
+
+        self.emit_event(
+            state,
+            Event::OfferWithdrawn {
+                id,
+                creator,
+                amount: grant.total,
+            },
+        );
+        Ok(())
+    }
+
+    fn claim(&mut self, id: u64, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let mut grant = self.grant(id, state)?;
+        if &grant.beneficiary != context.sender() {
+            bail!(
+                "Only the beneficiary {} can claim grant {id}",
+                grant.beneficiary
+            );
+        }
+
+        let now = self.now(grant.clock, state)?;
+        let claimable = grant.amounts(now).claimable;
+        if claimable == Amount::ZERO {
+            bail!("Grant {id} has nothing to claim");
+        }
+
+        let coins = Coins {
+            amount: claimable,
+            token_id: grant.token_id,
+        };
+        self.bank
+            .transfer_from(&Self::address(), &grant.beneficiary, coins, state)
+            .with_context(|| format!("The vesting account is out of {}", grant.token_id))?;
+
+        grant.claimed = grant.claimed.saturating_add(claimable);
+        self.grants.set(&id, &grant, state)?;
+        if grant.claimed >= grant.total {
+            // The grant stays queryable by id, but no longer takes a slot of the beneficiary.
+            let mut ids = self
+                .beneficiary_grants
+                .get(&grant.beneficiary, state)?
+                .unwrap_or_default();
+            ids.retain(|grant_id| *grant_id != id);
+            self.beneficiary_grants
+                .set(&grant.beneficiary, &ids, state)?;
+        }
+
+        self.emit_event(
+            state,
+            Event::Claimed {
+                id,
+                beneficiary: grant.beneficiary,
+                amount: claimable,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl<S: Spec> Module for Vesting<S> {
+    type Spec = S;
+
+    type Config = VestingConfig<S>;
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        let mut locked: Vec<(TokenId, Amount)> = Vec::new();
+        for grant in &config.grants {
+            match locked
+                .iter_mut()
+                .find(|(token_id, _)| token_id == &grant.token_id)
+            {
+                Some((_, amount)) => {
+                    *amount = amount
+                        .checked_add(grant.total)
+                        .with_context(|| format!("The grants lock too much {}", grant.token_id))?;
+                }
+                None => locked.push((grant.token_id, grant.total)),
+            }
+            grant.validate()?;
+            let id = self.next_id(state)?;
+            self.insert_grant(id, grant.clone(), state)?;
+        }
+
+        // The bank genesis runs first, so the vesting account is already funded.
+        let address = Self::address();
+        for (token_id, amount) in locked {
+            let balance = self
+                .bank
+                .get_balance_of(&address, token_id, state)?
+                .unwrap_or(Amount::ZERO);
+            if balance < amount {
+                bail!(
+                    "The genesis grants lock {amount} of {token_id}, but the bank genesis only credits the vesting account {address} with {balance}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::Create { grant } => self.create(grant, context, state),
+            CallMessage::Claim { id } => self.claim(id, context, state),
+            CallMessage::Accept { id } => self.accept(id, context, state),
+            CallMessage::Withdraw { id } => self.withdraw(id, context, state),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Locks `grant.total` of `grant.token_id` from the sender for `grant.beneficiary`. The grant
+    /// is created right away if the sender is the beneficiary, and offered to the beneficiary
+    /// otherwise.
+    Create { grant: NewGrant<S> },
+    /// Sends the released and unclaimed tokens of grant `id` to its beneficiary. Beneficiary only.
+    Claim { id: u64 },
+    /// Accepts offer `id`, which creates its grant. Beneficiary only.
+    Accept { id: u64 },
+    /// Withdraws offer `id` and sends its tokens back to its creator. Creator only.
+    Withdraw { id: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// `total` of `token_id` was locked for `beneficiary` in grant `id`.
+    GrantCreated {
+        id: u64,
+        beneficiary: S::Address,
+        token_id: TokenId,
+        total: Amount,
+    },
+    /// `creator` locked `total` of `token_id` in offer `id`, which `beneficiary` can accept.
+    GrantOffered {
+        id: u64,
+        creator: S::Address,
+        beneficiary: S::Address,
+        token_id: TokenId,
+        total: Amount,
+    },
+    /// Offer `id` was withdrawn and its `amount` sent back to `creator`.
+    OfferWithdrawn {
+        id: u64,
+        creator: S::Address,
+        amount: Amount,
+    },
+    /// `amount` of grant `id` was sent to `beneficiary`.
+    Claimed {
+        id: u64,
+        beneficiary: S::Address,
+        amount: Amount,
+    },
+}
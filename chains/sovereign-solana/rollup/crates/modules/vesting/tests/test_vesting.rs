@@ -0,0 +1,374 @@
+use sov_bank::{Bank, TokenId};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::{Amount, Spec};
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+use vesting::{
+    CallMessage, Clock, Event, GrantAmounts, NewGrant, Schedule, Vesting, VestingConfig,
+    MAX_GRANTS_PER_BENEFICIARY,
+};
+
+type S = TestSpec;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    vesting: Vesting<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub creator: TestUser<S>,
+    pub beneficiary: TestUser<S>,
+}
+
+fn gas_token() -> TokenId {
+    config_value!("GAS_TOKEN_ID")
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let creator = users.pop().unwrap();
+    let beneficiary = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        VestingConfig { grants: Vec::new() },
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (
+        TestData {
+            creator,
+            beneficiary,
+        },
+        runner,
+    )
+}
+
+fn grant(beneficiary: &TestUser<S>, schedule: Schedule) -> NewGrant<S> {
+    NewGrant {
+        beneficiary: beneficiary.address(),
+        token_id: gas_token(),
+        total: Amount::new(1_000),
+        clock: Clock::Height,
+        schedule,
+    }
+}
+
+/// Offers grant 0 to the beneficiary, funded by the creator.
+fn offer(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>, schedule: Schedule) {
+    let creator = test_data.creator.address();
+    let beneficiary = test_data.beneficiary.address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .creator
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Create {
+                grant: grant(&test_data.beneficiary, schedule),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result
+                .events
+                .contains(&TestRuntimeEvent::Vesting(Event::GrantOffered {
+                    id: 0,
+                    creator,
+                    beneficiary,
+                    token_id: gas_token(),
+                    total: Amount::new(1_000),
+                })));
+            assert_eq!(
+                Bank::<S>::default()
+                    .get_balance_of(&Vesting::<S>::address(), gas_token(), state)
+                    .unwrap(),
+                Some(Amount::new(1_000))
+            );
+            assert_eq!(Vesting::<S>::default().grants.get(&0, state).unwrap(), None);
+        }),
+    });
+}
+
+/// Offers grant 0 to the beneficiary, funded by the creator, and accepts it.
+fn create(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>, schedule: Schedule) {
+    offer(test_data, runner, schedule);
+
+    let beneficiary = test_data.beneficiary.address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Accept { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result
+                .events
+                .contains(&TestRuntimeEvent::Vesting(Event::GrantCreated {
+                    id: 0,
+                    beneficiary: beneficiary.clone(),
+                    token_id: gas_token(),
+                    total: Amount::new(1_000),
+                })));
+            let vesting = Vesting::<S>::default();
+            assert_eq!(vesting.offers.get(&0, state).unwrap(), None);
+            assert_eq!(
+                vesting.beneficiary_grants.get(&beneficiary, state).unwrap(),
+                Some(vec![0])
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_beneficiary_claims_released_tokens() {
+    let (test_data, mut runner) = setup();
+    create(&test_data, &mut runner, Schedule::Cliff { at: 0 });
+
+    let beneficiary = test_data.beneficiary.address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Claim { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result
+                .events
+                .contains(&TestRuntimeEvent::Vesting(Event::Claimed {
+                    id: 0,
+                    beneficiary: beneficiary.clone(),
+                    amount: Amount::new(1_000),
+                })));
+            let grant = Vesting::<S>::default()
+                .grants
+                .get(&0, state)
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                grant.amounts(0),
+                GrantAmounts {
+                    total: Amount::new(1_000),
+                    locked: Amount::ZERO,
+                    claimable: Amount::ZERO,
+                    claimed: Amount::new(1_000),
+                }
+            );
+            // The fully claimed grant no longer takes a slot of the beneficiary.
+            assert_eq!(
+                Vesting::<S>::default()
+                    .beneficiary_grants
+                    .get(&beneficiary, state)
+                    .unwrap(),
+                Some(Vec::new())
+            );
+        }),
+    });
+
+    // Everything was claimed.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Claim { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_locked_tokens_cannot_be_claimed() {
+    let (test_data, mut runner) = setup();
+    create(&test_data, &mut runner, Schedule::Cliff { at: u64::MAX });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Claim { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            let grant = Vesting::<S>::default()
+                .grants
+                .get(&0, state)
+                .unwrap()
+                .unwrap();
+            assert_eq!(grant.claimed, Amount::ZERO);
+        }),
+    });
+}
+
+#[test]
+fn test_only_beneficiary_claims() {
+    let (test_data, mut runner) = setup();
+    create(&test_data, &mut runner, Schedule::Cliff { at: 0 });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .creator
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Claim { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_beneficiary_creates_grant_without_offer() {
+    let (test_data, mut runner) = setup();
+
+    let beneficiary = test_data.beneficiary.address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Create {
+                grant: grant(&test_data.beneficiary, Schedule::Cliff { at: u64::MAX }),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result
+                .events
+                .contains(&TestRuntimeEvent::Vesting(Event::GrantCreated {
+                    id: 0,
+                    beneficiary,
+                    token_id: gas_token(),
+                    total: Amount::new(1_000),
+                })));
+            assert_eq!(Vesting::<S>::default().offers.get(&0, state).unwrap(), None);
+        }),
+    });
+}
+
+#[test]
+fn test_creator_withdraws_offer() {
+    let (test_data, mut runner) = setup();
+    offer(&test_data, &mut runner, Schedule::Cliff { at: 0 });
+
+    // Only the creator withdraws.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Withdraw { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    let creator = test_data.creator.address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .creator
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Withdraw { id: 0 }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result
+                .events
+                .contains(&TestRuntimeEvent::Vesting(Event::OfferWithdrawn {
+                    id: 0,
+                    creator,
+                    amount: Amount::new(1_000),
+                })));
+            assert_eq!(
+                Bank::<S>::default()
+                    .get_balance_of(&Vesting::<S>::address(), gas_token(), state)
+                    .unwrap(),
+                Some(Amount::ZERO)
+            );
+        }),
+    });
+
+    // A withdrawn offer can't be accepted.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Accept { id: 0 }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_offers_do_not_take_beneficiary_slots() {
+    let (test_data, mut runner) = setup();
+
+    for _ in 0..=MAX_GRANTS_PER_BENEFICIARY {
+        runner.execute_transaction(TransactionTestCase {
+            input: test_data
+                .creator
+                .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Create {
+                    grant: grant(&test_data.beneficiary, Schedule::Cliff { at: 0 }),
+                }),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_successful());
+            }),
+        });
+    }
+
+    // The beneficiary can still lock its own tokens.
+    let beneficiary = test_data.beneficiary.address();
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .beneficiary
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Create {
+                grant: grant(&test_data.beneficiary, Schedule::Cliff { at: u64::MAX }),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            let id = MAX_GRANTS_PER_BENEFICIARY as u64 + 1;
+            assert_eq!(
+                Vesting::<S>::default()
+                    .beneficiary_grants
+                    .get(&beneficiary, state)
+                    .unwrap(),
+                Some(vec![id])
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_invalid_schedule_is_rejected() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .creator
+            .create_plain_message::<TestRuntime<S>, Vesting<S>>(CallMessage::Create {
+                grant: grant(
+                    &test_data.beneficiary,
+                    Schedule::Linear {
+                        start: 10,
+                        cliff: 5,
+                        end: 20,
+                    },
+                ),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_linear_schedule() {
+    let schedule = Schedule::Linear {
+        start: 100,
+        cliff: 150,
+        end: 200,
+    };
+    let total = Amount::new(1_000);
+
+    assert_eq!(schedule.vested(total, 0), Amount::ZERO);
+    // Nothing is released before the cliff, then the linear amount since the start.
+    assert_eq!(schedule.vested(total, 149), Amount::ZERO);
+    assert_eq!(schedule.vested(total, 150), Amount::new(500));
+    assert_eq!(schedule.vested(total, 175), Amount::new(750));
+    assert_eq!(schedule.vested(total, 200), total);
+    assert_eq!(schedule.vested(total, u64::MAX), total);
+
+    // Large totals don't overflow.
+    let total = Amount::new(u128::MAX);
+    assert_eq!(schedule.vested(total, 150), Amount::new(u128::MAX / 2));
+}
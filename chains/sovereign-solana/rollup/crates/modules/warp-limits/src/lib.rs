@@ -0,0 +1,757 @@
+//! Per-route rate limits and circuit breaker for Warp transfers.
+//!
+//! Each limited warp route has an inbound and an outbound cap over a rolling window of rollup
+//! heights. Transfers that would exceed the cap are queued instead of rejected, and released in
+//! order once the window has room again. The tokens of queued outbound transfers, and the gas
+//! tokens they pay for dispatching, are escrowed in [`WarpLimits::address`] until they are
+//! released or cancelled. A guardian can pause a route: outbound transfers are then rejected and
+//! inbound transfers are queued until the route is unpaused. The guardian can also cancel queued
+//! transfers, so that messages approved by a compromised ISM never mint.
+//!
+//! Routes without limits are not affected.
+mod window;
+
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_bank::{Bank, Coins, TokenId};
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::capabilities::Credentials;
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, CryptoSpec, EventEmitter, GenesisState, Module, ModuleId,
+    ModuleInfo, ModuleRestApi, Spec, StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+use std::marker::PhantomData;
+
+pub use window::{validate_window, RollingUsage, BUCKETS};
+
+/// The maximum number of transfers that can be queued on a single route, in each direction.
+pub const MAX_QUEUED: usize = 256;
+
+/// Runtime hooks used by [`WarpLimits`] to release queued transfers. The module can't depend on
+/// the runtime it is part of, so delivering messages and executing warp calls is delegated.
+pub trait WarpLimitsRuntime<S: Spec>: Clone + Default + Send + Sync + 'static {
+    /// Delivers an inbound Hyperlane message to the warp route `route`.
+    fn deliver(
+        origin: u32,
+        sender: HexHash,
+        route: &HexHash,
+        body: HexString,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()>;
+
+    /// Decodes a borsh encoded warp call and executes it with `context.sender()` as sender,
+    /// without checking the route limits again.
+    fn transfer(call: &[u8], context: &Context<S>, state: &mut impl TxState<S>) -> Result<()>;
+}
+
+/// The direction of a warp transfer, seen from the rollup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Tokens arriving from a remote chain.
+    Inbound,
+    /// Tokens leaving for a remote chain.
+    Outbound,
+}
+
+/// The limits of a warp route.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct RouteLimits {
+    /// The local token of the route, escrowed while outbound transfers are queued.
+    pub token: TokenId,
+    /// The maximum amount received over `window` heights, as encoded in the inbound messages.
+    pub inbound_cap: Amount,
+    /// The maximum amount sent over `window` heights.
+    pub outbound_cap: Amount,
+    /// The length of the rolling window, in rollup heights. A multiple of [`BUCKETS`].
+    pub window: u64,
+}
+
+/// The limits of a route, as set at genesis.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct RouteLimitsEntry {
+    /// The warp route ID.
+    pub route: HexHash,
+    /// The limits of the route.
+    pub limits: RouteLimits,
+}
+
+/// A transfer held back by the limits of its route.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "QueuedTransfer")]
+pub struct QueuedTransfer<S: Spec> {
+    /// Identifies the transfer in events and cancellations.
+    pub id: u64,
+    /// The amount counted against the route limits.
+    pub amount: Amount,
+    /// What to execute once the transfer is released.
+    pub kind: QueuedKind<S>,
+}
+
+/// The payload of a [`QueuedTransfer`].
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "QueuedKind")]
+pub enum QueuedKind<S: Spec> {
+    /// A Hyperlane message to deliver to the route.
+    Inbound {
+        origin: u32,
+        sender: HexHash,
+        body: HexString,
+    },
+    /// A borsh encoded warp call to execute on behalf of `sender`, and the tokens escrowed from
+    /// `sender` to pay for it.
+    Outbound {
+        sender: S::Address,
+        call: HexString,
+        escrow: Vec<Coins>,
+    },
+}
+
+impl<S: Spec> QueuedKind<S> {
+    fn direction(&self) -> Direction {
+        match self {
+            QueuedKind::Inbound { .. } => Direction::Inbound,
+            QueuedKind::Outbound { .. } => Direction::Outbound,
+        }
+    }
+}
+
+/// Genesis configuration of the [`WarpLimits`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "WarpLimitsConfig")]
+pub struct WarpLimitsConfig<S: Spec> {
+    /// The account allowed to set route limits and to replace the guardian.
+    pub admin: S::Address,
+    /// The account allowed to pause routes and to cancel queued transfers.
+    pub guardian: S::Address,
+    /// The routes limited at genesis.
+    pub routes: Vec<RouteLimitsEntry>,
+}
+
+/// Rate limits, queues and pause switches of warp routes.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct WarpLimits<S: Spec, R: WarpLimitsRuntime<S>> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The account allowed to manage limits.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The account allowed to pause routes.
+    #[state]
+    pub guardian: StateValue<S::Address>,
+
+    /// The limits of each limited route.
+    #[state]
+    pub limits: StateMap<HexHash, RouteLimits>,
+
+    /// Routes paused by the guardian or the admin.
+    #[state]
+    pub paused: StateMap<HexHash, bool>,
+
+    /// The amounts received by each route over its window.
+    #[state]
+    pub inbound: StateMap<HexHash, RollingUsage>,
+
+    /// The amounts sent by each route over its window.
+    #[state]
+    pub outbound: StateMap<HexHash, RollingUsage>,
+
+    /// The inbound transfers waiting for room in the window of each route, oldest first.
+    #[state]
+    pub inbound_queues: StateMap<HexHash, Vec<QueuedTransfer<S>>>,
+
+    /// The outbound transfers waiting for room in the window of each route, oldest first.
+    #[state]
+    pub outbound_queues: StateMap<HexHash, Vec<QueuedTransfer<S>>>,
+
+    /// The ID of the next queued transfer.
+    #[state]
+    pub next_transfer_id: StateValue<u64>,
+
+    /// Used to read the current rollup height.
+    #[module]
+    pub chain_state: sov_chain_state::ChainState<S>,
+
+    /// Holds the tokens of queued outbound transfers.
+    #[module]
+    pub bank: Bank<S>,
+
+    #[phantom]
+    phantom: PhantomData<R>,
+}
+
+impl<S: Spec, R: WarpLimitsRuntime<S>> WarpLimits<S, R> {
+    /// The account holding the tokens of queued outbound transfers.
+    pub fn address() -> S::Address {
+        use sov_modules_api::digest::Digest;
+
+        let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+        hasher.update(b"warp_limits");
+        CredentialId(hasher.finalize().into()).into()
+    }
+
+    /// Returns true if `route` is paused.
+    pub fn is_paused(&self, route: &HexHash, state: &mut impl StateReader<User>) -> Result<bool> {
+        Ok(self.paused.get(route, state)?.unwrap_or(false))
+    }
+
+    /// Checks an inbound warp message against the limits of `route`. Returns true if the message
+    /// should be delivered now, false if it was queued.
+    pub fn admit_inbound(
+        &mut self,
+        origin: u32,
+        sender: &HexHash,
+        route: &HexHash,
+        body: &HexString,
+        state: &mut impl TxState<S>,
+    ) -> Result<bool> {
+        let Some(limits) = self.limits.get(route, state)? else {
+            return Ok(true);
+        };
+        // Bodies that are not token messages are left to the warp module to reject.
+        let Some(amount) = inbound_amount(&body.0) else {
+            return Ok(true);
+        };
+        // A transfer above the cap would never be released. Rejecting it leaves the message
+        // undelivered, so relayers can retry it once the admin has raised the cap.
+        if amount > limits.inbound_cap {
+            bail!(
+                "Inbound transfer of {amount} exceeds the cap {} of warp route {route}",
+                limits.inbound_cap
+            );
+        }
+
+        let kind = QueuedKind::Inbound {
+            origin,
+            sender: sender.clone(),
+            body: body.clone(),
+        };
+        // Inbound messages can't be rejected without being retried by relayers, so a paused
+        // route holds them until the guardian unpauses it or cancels them.
+        if self.is_paused(route, state)?
+            || !self.try_record(route, &limits, Direction::Inbound, amount, state)?
+        {
+            self.enqueue(route, amount, kind, state)?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Checks an outbound transfer of `amount` tokens from `sender` against the limits of
+    /// `route`. Returns true if the transfer should be executed now, false if `call` was queued.
+    /// Queued transfers escrow `amount` tokens of the route, and `gas_payment_limit` plus the
+    /// `protocol_fee` of the route's dispatch hooks in gas tokens, from `sender`, so that they
+    /// can't fail for lack of funds once released.
+    pub fn admit_outbound(
+        &mut self,
+        route: &HexHash,
+        amount: Amount,
+        gas_payment_limit: Amount,
+        protocol_fee: Amount,
+        sender: &S::Address,
+        call: Vec<u8>,
+        state: &mut impl TxState<S>,
+    ) -> Result<bool> {
+        let Some(limits) = self.limits.get(route, state)? else {
+            return Ok(true);
+        };
+        if self.is_paused(route, state)? {
+            bail!("Warp route {route} is paused");
+        }
+        if amount > limits.outbound_cap {
+            bail!(
+                "Outbound transfer of {amount} exceeds the cap {} of warp route {route}",
+                limits.outbound_cap
+            );
+        }
+
+        if self.try_record(route, &limits, Direction::Outbound, amount, state)? {
+            return Ok(true);
+        }
+
+        let gas = gas_payment_limit
+            .checked_add(protocol_fee)
+            .context("Gas payment overflow")?;
+        let escrow: Vec<Coins> = [
+            Coins {
+                amount,
+                token_id: limits.token,
+            },
+            Coins {
+                amount: gas,
+                token_id: config_value!("GAS_TOKEN_ID"),
+            },
+        ]
+        .into_iter()
+        .filter(|coins| coins.amount != Amount::ZERO)
+        .collect();
+        for coins in &escrow {
+            let token_id = coins.token_id;
+            self.bank
+                .transfer_from(sender, &Self::address(), coins.clone(), state)
+                .with_context(|| format!("Failed to escrow {} of {token_id}", coins.amount))?;
+        }
+
+        let kind = QueuedKind::Outbound {
+            sender: sender.clone(),
+            call: HexString(call),
+            escrow,
+        };
+        self.enqueue(route, amount, kind, state)?;
+        Ok(false)
+    }
+
+    /// Records `amount` if it fits in the window of `route` and nothing is queued ahead of it
+    /// in the same direction.
+    fn try_record(
+        &mut self,
+        route: &HexHash,
+        limits: &RouteLimits,
+        direction: Direction,
+        amount: Amount,
+        state: &mut impl TxState<S>,
+    ) -> Result<bool> {
+        if !self.queue(route, direction, state)?.is_empty()
+            || !self.fits(route, limits, direction, amount, state)?
+        {
+            return Ok(false);
+        }
+        self.record(route, limits, direction, amount, state)?;
+        Ok(true)
+    }
+
+    /// Returns true if `amount` fits in the window of `route` in `direction`.
+    fn fits(
+        &self,
+        route: &HexHash,
+        limits: &RouteLimits,
+        direction: Direction,
+        amount: Amount,
+        state: &mut impl TxState<S>,
+    ) -> Result<bool> {
+        let height = self.chain_state.rollup_height(state)?.get();
+        let (usage_map, cap) = match direction {
+            Direction::Inbound => (&self.inbound, limits.inbound_cap),
+            Direction::Outbound => (&self.outbound, limits.outbound_cap),
+        };
+        let usage = usage_map.get(route, state)?.unwrap_or_default();
+        Ok(usage.admits(amount, cap, height, limits.window))
+    }
+
+    fn record(
+        &mut self,
+        route: &HexHash,
+        limits: &RouteLimits,
+        direction: Direction,
+        amount: Amount,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let height = self.chain_state.rollup_height(state)?.get();
+        let usage_map = match direction {
+            Direction::Inbound => &mut self.inbound,
+            Direction::Outbound => &mut self.outbound,
+        };
+        let mut usage = usage_map.get(route, state)?.unwrap_or_default();
+        usage.record(amount, height, limits.window)?;
+        usage_map.set(route, &usage, state)?;
+        Ok(())
+    }
+
+    fn enqueue(
+        &mut self,
+        route: &HexHash,
+        amount: Amount,
+        kind: QueuedKind<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let direction = kind.direction();
+        let mut queue = self.queue(route, direction, state)?;
+        if queue.len() >= MAX_QUEUED {
+            bail!("The {direction:?} queue of warp route {route} is full");
+        }
+
+        let id = self.next_transfer_id.get(state)?.unwrap_or_default();
+        self.next_transfer_id.set(&(id + 1), state)?;
+
+        queue.push(QueuedTransfer { id, amount, kind });
+        self.set_queue(route, direction, queue, state)?;
+
+        self.emit_event(
+            state,
+            Event::TransferQueued {
+                route: route.clone(),
+                id,
+                direction,
+                amount,
+            },
+        );
+        Ok(())
+    }
+
+    /// Releases up to `max` queued transfers of `route` in `direction`, oldest first, and stops
+    /// at the first one that doesn't fit in the window yet. A transfer that fails fails the whole
+    /// release, so that none of its writes are kept: the transfer stays at the head of the queue
+    /// until it can succeed, or until the guardian or its sender cancels it.
+    fn release(
+        &mut self,
+        route: &HexHash,
+        direction: Direction,
+        max: u32,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        if self.is_paused(route, state)? {
+            bail!("Warp route {route} is paused");
+        }
+        let limits = self
+            .limits
+            .get(route, state)?
+            .with_context(|| format!("Warp route {route} has no limits"))?;
+
+        let mut queue = self.queue(route, direction, state)?;
+        let mut processed = 0;
+        while processed < max as usize && processed < queue.len() {
+            let transfer = &queue[processed];
+            if !self.fits(route, &limits, direction, transfer.amount, state)? {
+                break;
+            }
+
+            let (id, amount) = (transfer.id, transfer.amount);
+            match &transfer.kind {
+                QueuedKind::Inbound {
+                    origin,
+                    sender,
+                    body,
+                } => R::deliver(*origin, sender.clone(), route, body.clone(), context, state),
+                QueuedKind::Outbound {
+                    sender,
+                    call,
+                    escrow,
+                } => {
+                    // The escrow is returned first, so that the transfer spends the sender's
+                    // own tokens. A failure reverts the refund with the rest of the release.
+                    self.refund(sender, escrow, state)?;
+                    let sender_context = Context::new(
+                        sender.clone(),
+                        Credentials::new(sender.clone()),
+                        context.sequencer().clone(),
+                        context.sequencer_da_address().clone(),
+                    );
+                    R::transfer(&call.0, &sender_context, state)
+                }
+            }
+            .with_context(|| format!("Queued transfer {id} of warp route {route} failed"))?;
+
+            self.record(route, &limits, direction, amount, state)?;
+            self.emit_event(
+                state,
+                Event::TransferReleased {
+                    route: route.clone(),
+                    id,
+                },
+            );
+            processed += 1;
+        }
+
+        if processed == 0 {
+            bail!("No queued {direction:?} transfer of warp route {route} can be released yet");
+        }
+        queue.drain(..processed);
+        self.set_queue(route, direction, queue, state)
+    }
+
+    /// Drops the queued transfer `id` of `route`. Inbound transfers can only be cancelled by the
+    /// guardian, outbound ones also by their sender, who gets the escrowed tokens back.
+    fn cancel(
+        &mut self,
+        route: &HexHash,
+        id: u64,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        for direction in [Direction::Inbound, Direction::Outbound] {
+            let mut queue = self.queue(route, direction, state)?;
+            let Some(index) = queue.iter().position(|queued| queued.id == id) else {
+                continue;
+            };
+
+            let transfer = queue.remove(index);
+            match &transfer.kind {
+                QueuedKind::Outbound { sender, escrow, .. } => {
+                    if sender != context.sender() {
+                        self.ensure_guardian(context, state)?;
+                    }
+                    self.refund(sender, escrow, state)?;
+                }
+                QueuedKind::Inbound { .. } => self.ensure_guardian(context, state)?,
+            }
+            self.set_queue(route, direction, queue, state)?;
+            self.emit_event(
+                state,
+                Event::TransferCancelled {
+                    route: route.clone(),
+                    id,
+                },
+            );
+            return Ok(());
+        }
+        bail!("No queued transfer {id} on warp route {route}")
+    }
+
+    /// Returns the escrow of an outbound transfer to its sender.
+    fn refund(
+        &mut self,
+        sender: &S::Address,
+        escrow: &[Coins],
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        for coins in escrow {
+            let token_id = coins.token_id;
+            self.bank
+                .transfer_from(&Self::address(), sender, coins.clone(), state)
+                .with_context(|| format!("The warp limits account is out of {token_id}"))?;
+        }
+        Ok(())
+    }
+
+    /// The transfers of `route` queued in `direction`, oldest first.
+    pub fn queue(
+        &self,
+        route: &HexHash,
+        direction: Direction,
+        state: &mut impl StateReader<User>,
+    ) -> Result<Vec<QueuedTransfer<S>>> {
+        let queues = match direction {
+            Direction::Inbound => &self.inbound_queues,
+            Direction::Outbound => &self.outbound_queues,
+        };
+        Ok(queues.get(route, state)?.unwrap_or_default())
+    }
+
+    fn set_queue(
+        &mut self,
+        route: &HexHash,
+        direction: Direction,
+        queue: Vec<QueuedTransfer<S>>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let queues = match direction {
+            Direction::Inbound => &mut self.inbound_queues,
+            Direction::Outbound => &mut self.outbound_queues,
+        };
+        if queue.is_empty() {
+            queues.remove(route, state)?;
+        } else {
+            queues.set(route, &queue, state)?;
+        }
+        Ok(())
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .context("The warp limits admin is not set")?;
+        if &admin != context.sender() {
+            bail!("Only the admin {admin} can manage warp route limits");
+        }
+        Ok(())
+    }
+
+    /// The admin can do everything the guardian can.
+    fn ensure_guardian(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let guardian = self
+            .guardian
+            .get(state)?
+            .context("The warp limits guardian is not set")?;
+        if &guardian != context.sender() && self.ensure_admin(context, state).is_err() {
+            bail!("Only the guardian {guardian} or the admin can pause warp routes");
+        }
+        Ok(())
+    }
+}
+
+/// Returns the amount of a Hyperlane token message: a 32 byte recipient followed by a big endian
+/// `uint256` amount. Amounts above `u128::MAX` saturate, so they never fit under a cap.
+pub fn inbound_amount(body: &[u8]) -> Option<Amount> {
+    let amount = body.get(32..64)?;
+    let (high, low) = amount.split_at(16);
+    if high.iter().any(|byte| *byte != 0) {
+        return Some(Amount::new(u128::MAX));
+    }
+    Some(Amount::new(u128::from_be_bytes(low.try_into().ok()?)))
+}
+
+impl<S: Spec, R: WarpLimitsRuntime<S>> Module for WarpLimits<S, R> {
+    type Spec = S;
+
+    type Config = WarpLimitsConfig<S>;
+
+    type CallMessage = CallMessage<S>;
+
+    type Event = Event<S>;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.admin.set(&config.admin, state)?;
+        self.guardian.set(&config.guardian, state)?;
+        for RouteLimitsEntry { route, limits } in &config.routes {
+            validate(limits)?;
+            self.limits.set(route, limits, state)?;
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::SetLimits { route, limits } => {
+                self.ensure_admin(context, state)?;
+                validate(&limits)?;
+                // The buckets of the usage are as long as the window was, so they don't carry
+                // over to a new window.
+                if let Some(previous) = self.limits.get(&route, state)? {
+                    if previous.window != limits.window {
+                        self.inbound.remove(&route, state)?;
+                        self.outbound.remove(&route, state)?;
+                    }
+                }
+                self.limits.set(&route, &limits, state)?;
+                self.emit_event(state, Event::LimitsSet { route, limits });
+                Ok(())
+            }
+            CallMessage::RemoveLimits { route } => {
+                self.ensure_admin(context, state)?;
+                if self.limits.get(&route, state)?.is_none() {
+                    bail!("Warp route {route} has no limits");
+                }
+                if self.inbound_queues.get(&route, state)?.is_some()
+                    || self.outbound_queues.get(&route, state)?.is_some()
+                {
+                    bail!("Warp route {route} still has queued transfers");
+                }
+                self.limits.remove(&route, state)?;
+                self.paused.remove(&route, state)?;
+                self.inbound.remove(&route, state)?;
+                self.outbound.remove(&route, state)?;
+                self.emit_event(state, Event::LimitsRemoved { route });
+                Ok(())
+            }
+            CallMessage::SetGuardian { guardian } => {
+                self.ensure_admin(context, state)?;
+                self.guardian.set(&guardian, state)?;
+                self.emit_event(state, Event::GuardianSet { guardian });
+                Ok(())
+            }
+            CallMessage::Pause { route } => {
+                self.ensure_guardian(context, state)?;
+                if self.limits.get(&route, state)?.is_none() {
+                    bail!("Warp route {route} has no limits");
+                }
+                self.paused.set(&route, &true, state)?;
+                self.emit_event(state, Event::Paused { route });
+                Ok(())
+            }
+            CallMessage::Unpause { route } => {
+                self.ensure_guardian(context, state)?;
+                if !self.is_paused(&route, state)? {
+                    bail!("Warp route {route} is not paused");
+                }
+                self.paused.remove(&route, state)?;
+                self.emit_event(state, Event::Unpaused { route });
+                Ok(())
+            }
+            CallMessage::Release {
+                route,
+                direction,
+                max,
+            } => self.release(&route, direction, max, context, state),
+            CallMessage::Cancel { route, id } => self.cancel(&route, id, context, state),
+        }
+    }
+}
+
+fn validate(limits: &RouteLimits) -> Result<()> {
+    validate_window(limits.window)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Limits `route`, or replaces its limits. Admin only.
+    SetLimits { route: HexHash, limits: RouteLimits },
+    /// Stops limiting `route`. The route must have no queued transfers. Admin only.
+    RemoveLimits { route: HexHash },
+    /// Replaces the guardian. Admin only.
+    SetGuardian { guardian: S::Address },
+    /// Rejects outbound transfers and queues inbound ones on `route`. Guardian or admin only.
+    Pause { route: HexHash },
+    /// Lifts a pause. Guardian or admin only.
+    Unpause { route: HexHash },
+    /// Executes up to `max` queued transfers of `route` in `direction` that fit in its window.
+    /// Anyone can release transfers; the caller pays for their execution. If any of them fails,
+    /// none is released.
+    Release {
+        route: HexHash,
+        direction: Direction,
+        max: u32,
+    },
+    /// Drops a queued transfer and refunds its escrow. Guardian or admin only, except that the
+    /// sender of an outbound transfer can cancel it.
+    Cancel { route: HexHash, id: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// The limits of a route were set or replaced.
+    LimitsSet { route: HexHash, limits: RouteLimits },
+    /// A route is no longer limited.
+    LimitsRemoved { route: HexHash },
+    /// The guardian was replaced.
+    GuardianSet { guardian: S::Address },
+    /// A route was paused.
+    Paused { route: HexHash },
+    /// A route was unpaused.
+    Unpaused { route: HexHash },
+    /// A transfer exceeded the limits of its route, or arrived while it was paused.
+    TransferQueued {
+        route: HexHash,
+        id: u64,
+        direction: Direction,
+        amount: Amount,
+    },
+    /// A queued transfer was executed.
+    TransferReleased { route: HexHash, id: u64 },
+    /// A queued transfer was dropped by the guardian or its sender.
+    TransferCancelled { route: HexHash, id: u64 },
+}
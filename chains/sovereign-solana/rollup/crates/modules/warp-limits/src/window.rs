@@ -0,0 +1,69 @@
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_modules_api::macros::serialize;
+use sov_modules_api::Amount;
+
+/// The number of buckets a window is split into. Amounts leave the window one bucket at a time,
+/// so the effective window is between `window - window / BUCKETS` and `window` heights long.
+/// Windows are a multiple of `BUCKETS` heights, see [`validate_window`].
+pub const BUCKETS: u64 = 8;
+
+/// Amounts transferred over the last `window` rollup heights.
+#[derive(Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct RollingUsage {
+    /// `(bucket index, amount)` pairs, oldest first. A bucket index is `height / bucket length`.
+    pub buckets: Vec<(u64, Amount)>,
+}
+
+/// Checks that `window` splits into [`BUCKETS`] buckets of at least one height. A shorter window,
+/// or one with a remainder, would be stretched or cut to a whole number of buckets.
+pub fn validate_window(window: u64) -> Result<()> {
+    if window < BUCKETS || window % BUCKETS != 0 {
+        bail!("The window of a warp route must be a multiple of {BUCKETS} heights, got {window}");
+    }
+    Ok(())
+}
+
+fn bucket_len(window: u64) -> u64 {
+    // Only zero is guarded against: limits with other invalid windows can't be set.
+    (window / BUCKETS).max(1)
+}
+
+impl RollingUsage {
+    /// Returns the amount transferred in the window ending at `height`.
+    pub fn current(&self, height: u64, window: u64) -> Amount {
+        let bucket = height / bucket_len(window);
+        self.buckets
+            .iter()
+            .filter(|(index, _)| bucket.saturating_sub(*index) < BUCKETS)
+            .fold(Amount::ZERO, |total, (_, amount)| {
+                total.saturating_add(*amount)
+            })
+    }
+
+    /// Returns true if `amount` fits under `cap` at `height`.
+    pub fn admits(&self, amount: Amount, cap: Amount, height: u64, window: u64) -> bool {
+        self.current(height, window)
+            .checked_add(amount)
+            .is_some_and(|total| total <= cap)
+    }
+
+    /// Records `amount` at `height` and drops the buckets that left the window.
+    pub fn record(&mut self, amount: Amount, height: u64, window: u64) -> Result<()> {
+        let bucket = height / bucket_len(window);
+        self.buckets
+            .retain(|(index, _)| bucket.saturating_sub(*index) < BUCKETS);
+
+        match self.buckets.last_mut() {
+            Some((index, total)) if *index == bucket => {
+                *total = total
+                    .checked_add(amount)
+                    .context("Transferred amount overflow")?;
+            }
+            _ => self.buckets.push((bucket, amount)),
+        }
+        Ok(())
+    }
+}
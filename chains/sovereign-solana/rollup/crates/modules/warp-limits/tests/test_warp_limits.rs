@@ -0,0 +1,533 @@
+use schemars::JsonSchema;
+use sov_bank::{Bank, TokenId};
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec, StateReader,
+    TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+use warp_limits::{
+    inbound_amount, validate_window, CallMessage, Direction, Event, RollingUsage, RouteLimits,
+    WarpLimits, WarpLimitsConfig, WarpLimitsRuntime, BUCKETS,
+};
+
+type S = TestSpec;
+
+/// The module tests don't exercise releasing transfers, which needs a full runtime.
+#[derive(Clone, Default)]
+pub struct UnreachableWarp;
+
+impl<S: Spec> WarpLimitsRuntime<S> for UnreachableWarp {
+    fn deliver(
+        _origin: u32,
+        _sender: HexHash,
+        _route: &HexHash,
+        _body: HexString,
+        _context: &Context<S>,
+        _state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("Not available in module tests")
+    }
+
+    fn transfer(
+        _call: &[u8],
+        _context: &Context<S>,
+        _state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("Not available in module tests")
+    }
+}
+
+type Limits = WarpLimits<S, UnreachableWarp>;
+
+/// Submits transfers to the limits of their route, the way the runtime does for warp calls and
+/// Hyperlane messages.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct Transfers<S: Spec> {
+    #[id]
+    pub id: ModuleId,
+
+    #[module]
+    pub warp_limits: WarpLimits<S, UnreachableWarp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum TransfersCall {
+    Outbound {
+        amount: Amount,
+        gas_payment_limit: Amount,
+        protocol_fee: Amount,
+    },
+    Inbound {
+        amount: u128,
+    },
+}
+
+impl<S: Spec> Module for Transfers<S> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = TransfersCall;
+
+    type Event = ();
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        _config: &Self::Config,
+        _state: &mut impl GenesisState<S>,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        match msg {
+            TransfersCall::Outbound {
+                amount,
+                gas_payment_limit,
+                protocol_fee,
+            } => self.warp_limits.admit_outbound(
+                &route(),
+                amount,
+                gas_payment_limit,
+                protocol_fee,
+                context.sender(),
+                Vec::new(),
+                state,
+            )?,
+            TransfersCall::Inbound { amount } => {
+                let mut body = vec![0; 64];
+                body[48..].copy_from_slice(&amount.to_be_bytes());
+                self.warp_limits
+                    .admit_inbound(1, &route(), &route(), &HexString(body), state)?
+            }
+        };
+        Ok(())
+    }
+}
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    warp_limits: Limits,
+    transfers: Transfers<S>
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
+    pub guardian: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(3);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
+    let guardian = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        WarpLimitsConfig {
+            admin: admin.address(),
+            guardian: guardian.address(),
+            routes: Vec::new(),
+        },
+        (),
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (
+        TestData {
+            admin,
+            guardian,
+            other_user,
+        },
+        runner,
+    )
+}
+
+fn route() -> HexHash {
+    HexString([3; 32])
+}
+
+fn gas_token() -> TokenId {
+    config_value!("GAS_TOKEN_ID")
+}
+
+fn limits() -> RouteLimits {
+    RouteLimits {
+        token: gas_token(),
+        inbound_cap: Amount::new(1_000),
+        outbound_cap: Amount::new(500),
+        window: 80,
+    }
+}
+
+fn set_limits(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>) {
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::SetLimits {
+                route: route(),
+                limits: limits(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::WarpLimits(Event::LimitsSet {
+                    route: route(),
+                    limits: limits(),
+                })]
+            );
+            assert_eq!(
+                Limits::default().limits.get(&route(), state).unwrap(),
+                Some(limits())
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_only_admin_sets_limits() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .guardian
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::SetLimits {
+                route: route(),
+                limits: limits(),
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    set_limits(&test_data, &mut runner);
+}
+
+#[test]
+fn test_guardian_pauses_and_unpauses_route() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .guardian
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::Pause { route: route() }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(Limits::default().is_paused(&route(), state).unwrap());
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .guardian
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::Unpause {
+                route: route(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(!Limits::default().is_paused(&route(), state).unwrap());
+        }),
+    });
+}
+
+#[test]
+fn test_other_users_cannot_pause() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::Pause { route: route() }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_release_requires_queued_transfers() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::Release {
+                route: route(),
+                direction: Direction::Outbound,
+                max: 10,
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+fn escrowed(state: &mut impl StateReader<User>) -> Amount {
+    Bank::<S>::default()
+        .get_balance_of(&Limits::address(), gas_token(), state)
+        .unwrap()
+        .unwrap_or(Amount::ZERO)
+}
+
+fn send(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>, call: TransfersCall) {
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Transfers<S>>(call),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+}
+
+/// Queues an outbound transfer of 200 behind one of 400, which fills the outbound cap of 500.
+fn queue_outbound(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>) {
+    for amount in [400, 200] {
+        send(
+            test_data,
+            runner,
+            TransfersCall::Outbound {
+                amount: Amount::new(amount),
+                gas_payment_limit: Amount::new(10),
+                protocol_fee: Amount::new(5),
+            },
+        );
+    }
+}
+
+fn queue_len(runner: &mut TestRunner<TestRuntime<S>, S>, direction: Direction) -> usize {
+    runner.query_state(|state| {
+        Limits::default()
+            .queue(&route(), direction, state)
+            .unwrap()
+            .len()
+    })
+}
+
+#[test]
+fn test_transfers_above_the_cap_are_rejected() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+
+    for call in [
+        TransfersCall::Outbound {
+            amount: Amount::new(501),
+            gas_payment_limit: Amount::ZERO,
+            protocol_fee: Amount::ZERO,
+        },
+        TransfersCall::Inbound { amount: 1_001 },
+    ] {
+        runner.execute_transaction(TransactionTestCase {
+            input: test_data
+                .other_user
+                .create_plain_message::<TestRuntime<S>, Transfers<S>>(call),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_reverted());
+            }),
+        });
+    }
+    assert_eq!(queue_len(&mut runner, Direction::Outbound), 0);
+    assert_eq!(queue_len(&mut runner, Direction::Inbound), 0);
+}
+
+#[test]
+fn test_queued_outbound_transfer_is_escrowed() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+
+    queue_outbound(&test_data, &mut runner);
+
+    assert_eq!(queue_len(&mut runner, Direction::Outbound), 1);
+    // The amount, the gas payment limit and the protocol fee of the queued transfer only.
+    assert_eq!(
+        runner.query_state(|state| escrowed(state)),
+        Amount::new(215)
+    );
+}
+
+#[test]
+fn test_sender_cancels_outbound_transfer() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+    queue_outbound(&test_data, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::Cancel {
+                route: route(),
+                id: 0,
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(result.events.contains(&TestRuntimeEvent::WarpLimits(
+                Event::TransferCancelled {
+                    route: route(),
+                    id: 0,
+                }
+            )));
+            assert_eq!(escrowed(state), Amount::ZERO);
+        }),
+    });
+    assert_eq!(queue_len(&mut runner, Direction::Outbound), 0);
+}
+
+#[test]
+fn test_only_guardian_cancels_inbound_transfer() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+
+    // The second transfer exceeds the inbound cap of 1000 and is queued.
+    for amount in [600, 600] {
+        send(&test_data, &mut runner, TransfersCall::Inbound { amount });
+    }
+    assert_eq!(queue_len(&mut runner, Direction::Inbound), 1);
+
+    for (user, allowed) in [(&test_data.other_user, false), (&test_data.guardian, true)] {
+        runner.execute_transaction(TransactionTestCase {
+            input: user.create_plain_message::<TestRuntime<S>, Limits>(CallMessage::Cancel {
+                route: route(),
+                id: 0,
+            }),
+            assert: Box::new(move |result, _state| {
+                assert_eq!(result.tx_receipt.is_successful(), allowed);
+            }),
+        });
+    }
+    assert_eq!(queue_len(&mut runner, Direction::Inbound), 0);
+}
+
+#[test]
+fn test_queues_are_separate_per_direction() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+    queue_outbound(&test_data, &mut runner);
+
+    // The outbound queue doesn't hold back inbound transfers, which are counted on their own.
+    send(
+        &test_data,
+        &mut runner,
+        TransfersCall::Inbound { amount: 1_000 },
+    );
+    assert_eq!(queue_len(&mut runner, Direction::Inbound), 0);
+    assert_eq!(queue_len(&mut runner, Direction::Outbound), 1);
+}
+
+#[test]
+fn test_rolling_usage_expires_old_buckets() {
+    let window = 80;
+    let cap = Amount::new(100);
+    let mut usage = RollingUsage::default();
+
+    usage.record(Amount::new(60), 0, window).unwrap();
+    usage.record(Amount::new(40), 15, window).unwrap();
+    assert_eq!(usage.current(15, window), Amount::new(100));
+    assert!(!usage.admits(Amount::new(1), cap, 15, window));
+
+    // The first bucket leaves the window after `BUCKETS` bucket lengths.
+    let bucket_len = window / BUCKETS;
+    assert_eq!(usage.current(BUCKETS * bucket_len, window), Amount::new(40));
+    assert!(usage.admits(Amount::new(60), cap, BUCKETS * bucket_len, window));
+    assert_eq!(usage.current(window * 2, window), Amount::ZERO);
+}
+
+#[test]
+fn test_windows_split_into_whole_buckets() {
+    validate_window(BUCKETS).unwrap();
+    validate_window(BUCKETS * 75).unwrap();
+    assert!(validate_window(0).is_err());
+    assert!(validate_window(BUCKETS - 1).is_err());
+    assert!(validate_window(BUCKETS + 1).is_err());
+}
+
+#[test]
+fn test_set_limits_rejects_short_windows() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::SetLimits {
+                route: route(),
+                limits: RouteLimits {
+                    window: BUCKETS - 1,
+                    ..limits()
+                },
+            }),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_changing_the_window_resets_usage() {
+    let (test_data, mut runner) = setup();
+    set_limits(&test_data, &mut runner);
+    send(
+        &test_data,
+        &mut runner,
+        TransfersCall::Inbound { amount: 600 },
+    );
+
+    // Keeping the window keeps what was counted in it.
+    for (window, counted) in [(limits().window, 600), (limits().window * 2, 0)] {
+        runner.execute_transaction(TransactionTestCase {
+            input: test_data
+                .admin
+                .create_plain_message::<TestRuntime<S>, Limits>(CallMessage::SetLimits {
+                    route: route(),
+                    limits: RouteLimits { window, ..limits() },
+                }),
+            assert: Box::new(move |result, state| {
+                assert!(result.tx_receipt.is_successful());
+                let usage = Limits::default()
+                    .inbound
+                    .get(&route(), state)
+                    .unwrap()
+                    .unwrap_or_default();
+                assert_eq!(usage.current(0, window), Amount::new(counted));
+            }),
+        });
+    }
+}
+
+#[test]
+fn test_inbound_amount_reads_token_message() {
+    let mut body = vec![0; 64];
+    body[63] = 42;
+    assert_eq!(inbound_amount(&body), Some(Amount::new(42)));
+
+    body[32] = 1;
+    assert_eq!(inbound_amount(&body), Some(Amount::new(u128::MAX)));
+
+    assert_eq!(inbound_amount(&[0; 40]), None);
+}
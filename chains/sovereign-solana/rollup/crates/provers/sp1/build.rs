@@ -10,7 +10,7 @@ fn main() {
         return;
     }
 
-    let features = sov_zkvm_utils::collect_features(&["bench"], &["native"]);
+    let features = sov_zkvm_utils::collect_features(&["bench", "evm", "faucet"], &["native"]);
 
     let args = BuildArgs {
         features,
@@ -4,6 +4,7 @@ use anyhow::Context;
 use clap::Parser;
 use rollup_starter::da::DaService;
 use rollup_starter::rollup::StarterRollup;
+use rollup_starter::validator::ValidatorConfig;
 use rollup_starter::zkvm::{rollup_host_args, InnerZkvm};
 use sov_modules_rollup_blueprint::logging::initialize_logging;
 use sov_modules_rollup_blueprint::FullNodeBlueprint;
@@ -92,6 +93,16 @@ struct Args {
     /// Stops the rollup at a given height.
     #[arg(long, default_value = None)]
     stop_at_rollup_height: Option<u64>,
+
+    /// Runs a Hyperlane validator in the node, writing signed checkpoints to the given directory.
+    /// The validator key is read from `HYP_VALIDATOR_KEY`.
+    #[arg(long, default_value = None)]
+    validator_checkpoint_dir: Option<PathBuf>,
+
+    /// The key file of the rollup account that pays for announcing the validator's checkpoint
+    /// directory, in the format of the CLI wallet.
+    #[arg(long, default_value = None, requires = "validator_checkpoint_dir")]
+    validator_announcer_key: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -114,12 +125,20 @@ async fn main() {
 
     let prover_config =
         prover_config_disc.map(|config_disc| config_disc.into_config(rollup_host_args()));
+    let validator = args
+        .validator_checkpoint_dir
+        .map(|checkpoint_dir| {
+            ValidatorConfig::from_env(checkpoint_dir, args.validator_announcer_key.clone())
+        })
+        .transpose()
+        .expect("Invalid validator config");
     let rollup = new_rollup(
         args.genesis_path,
         args.rollup_config_path,
         prover_config,
         args.start_at_rollup_height.map(RollupHeight::new),
         args.stop_at_rollup_height.map(RollupHeight::new),
+        validator,
     )
     .await
     .expect("Couldn't start rollup");
@@ -153,6 +172,7 @@ async fn new_rollup(
     prover_config: Option<RollupProverConfig<InnerZkvm>>,
     start_at_rollup_height: Option<RollupHeight>,
     stop_at_rollup_height: Option<RollupHeight>,
+    validator: Option<ValidatorConfig>,
 ) -> Result<Rollup<StarterRollup<Native>, Native>, anyhow::Error> {
     tracing::info!(
         ?rollup_config_path,
@@ -170,7 +190,10 @@ async fn new_rollup(
             )
         })?;
 
-    let rollup = StarterRollup::default();
+    let rollup = match validator {
+        Some(validator) => StarterRollup::with_validator(validator),
+        None => StarterRollup::default(),
+    };
     let modules_config_input = rollup_config_path
         .parent()
         .map(|path| path.join("modules.json"))
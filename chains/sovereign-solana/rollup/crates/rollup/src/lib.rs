@@ -1,3 +1,4 @@
 pub mod da;
 pub mod rollup;
+pub mod validator;
 pub mod zkvm;
@@ -30,6 +30,7 @@ use stf_starter::Runtime;
 use tokio::sync::watch;
 
 use crate::da::{new_da_service, new_verifier, DaService, DaSpec};
+use crate::validator::ValidatorConfig;
 use crate::zkvm::{create_inner_vm_from_config, get_outer_vm, Hasher, InnerZkvm, OuterZkvm};
 
 type NativeStorage = NomtProverStorage<
@@ -50,9 +51,21 @@ pub type RollupSpec<Da, InnerZkvm, OuterZkvm> = ConfigurableSpec<
 /// Starter rollup implementation.
 #[derive(Default)]
 pub struct StarterRollup<M> {
+    /// The built-in Hyperlane validator, if the node runs one.
+    validator: Option<ValidatorConfig>,
     phantom: std::marker::PhantomData<M>,
 }
 
+impl<M> StarterRollup<M> {
+    /// Creates a rollup whose node also runs a Hyperlane validator.
+    pub fn with_validator(validator: ValidatorConfig) -> Self {
+        Self {
+            validator: Some(validator),
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
 /// This is the place where all the rollup components come together, and
 /// they can be easily swapped with alternative implementations as needed.
 impl RollupBlueprint<Native> for StarterRollup<Native>
@@ -97,15 +110,28 @@ impl FullNodeBlueprint<Native> for StarterRollup<Native> {
         _da_service: &Self::DaService,
         rollup_config: &RollupConfig<<Self::Spec as Spec>::Address, Self::DaService>,
     ) -> anyhow::Result<sov_modules_api::NodeEndpoints> {
-        sov_modules_rollup_blueprint::register_endpoints::<Self, _>(
+        // The runtime starts watching the module config when its endpoints are registered.
+        stf_starter::module_config::set_shutdown_receiver(shutdown_receiver.clone());
+        let mut endpoints = sov_modules_rollup_blueprint::register_endpoints::<Self, _>(
             state_update_receiver.clone(),
             sync_status_receiver,
-            shutdown_receiver,
+            shutdown_receiver.clone(),
             ledger_db,
             sequencer,
             rollup_config,
         )
-        .await
+        .await?;
+
+        if let Some(validator) = &self.validator {
+            endpoints
+                .background_handles
+                .push(crate::validator::spawn::<Self::Spec>(
+                    validator.clone(),
+                    endpoints.axum_router.clone(),
+                    shutdown_receiver,
+                ));
+        }
+        Ok(endpoints)
     }
 
     async fn create_da_service(
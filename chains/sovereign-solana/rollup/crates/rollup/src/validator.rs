@@ -0,0 +1,512 @@
+//! A Hyperlane validator built into the node, for single-operator devnets.
+//!
+//! The validator follows the tree of the rollup's `MerkleTreeHook`, signs a checkpoint for every
+//! message dispatched at a finalized rollup height, and writes the signatures to a directory in
+//! the layout of the Hyperlane agents' `localStorage` checkpoint syncer. Relayers started with
+//! `--allowLocalCheckpointSyncers true` read them from there, so no separate validator agent is
+//! needed.
+//!
+//! The REST API is queried in-process through the node's router. The message IDs are read from
+//! the mirror of the message index module, and the tree they rebuild must have the root of the
+//! `MerkleTreeHook`, read from the rollup state, before anything is signed. A message is only
+//! signed once the height it was dispatched at is finalized, so a reorg of the DA layer can't
+//! make the validator sign two roots for the same index.
+//!
+//! The validator announces the directory through the `ValidatorAnnounce` module, with a
+//! transaction paid for by a configured rollup account.
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{eip191_hash_message, hex, Address, U256};
+use anyhow::{bail, ensure, Context as _};
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use k256::ecdsa::SigningKey;
+use message_index::api::{MerkleTreeResponse, MessageResponse, ProofResponse, MERKLE_TREE_PATH};
+use message_index::merkle::{
+    Checkpoint, CheckpointWithMessageId, IncrementalMerkle, MERKLE_TREE_HOOK_ADDRESS,
+};
+use sov_cli::wallet_state::PrivateKeyAndAddress;
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress};
+use sov_modules_api::capabilities::UniquenessData;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::{Amount, RawTx, Spec};
+use stf_starter::authentication::EvmAndEip712AuthenticatorInput;
+use stf_starter::{Runtime, RuntimeCall};
+use tokio::sync::watch;
+use tower::ServiceExt;
+
+/// The environment variable holding the hex encoded secp256k1 key of the validator, as in the
+/// agents' `.env`.
+pub const VALIDATOR_KEY_ENV: &str = "HYP_VALIDATOR_KEY";
+
+/// How often the merkle tree is checked for new messages.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the validator waits for its announcement to land before submitting it again.
+pub const ANNOUNCE_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The maximum fee of the announcement transaction.
+const ANNOUNCE_MAX_FEE: Amount = Amount::new(100_000_000);
+
+/// The path of the latest finalized slot in the ledger API. Its number is the rollup height.
+const FINALIZED_SLOT_PATH: &str = "/ledger/slots/finalized";
+
+/// The path the sequencer accepts transactions at.
+const SEQUENCER_TXS_PATH: &str = "/sequencer/txs";
+
+/// The path of the mailbox the announcements commit to.
+const ANNOUNCE_MAILBOX_PATH: &str = "/modules/validator-announce/state/mailbox";
+
+/// The maximum size of a REST response read by the validator.
+const MAX_RESPONSE_SIZE: usize = 1 << 20;
+
+/// The configuration of the built-in validator.
+#[derive(Clone)]
+pub struct ValidatorConfig {
+    /// The key signing the checkpoints.
+    pub key: SigningKey,
+    /// The directory the signed checkpoints are written to.
+    pub checkpoint_dir: PathBuf,
+    /// The key file of the rollup account submitting the announcement of the validator, as
+    /// written by the CLI wallet. Without it, the validator must be announced by another account.
+    pub announcer_key: Option<PathBuf>,
+}
+
+impl ValidatorConfig {
+    /// Reads the validator key from [`VALIDATOR_KEY_ENV`].
+    pub fn from_env(
+        checkpoint_dir: PathBuf,
+        announcer_key: Option<PathBuf>,
+    ) -> anyhow::Result<Self> {
+        let key = std::env::var(VALIDATOR_KEY_ENV)
+            .with_context(|| format!("`{VALIDATOR_KEY_ENV}` must be set to run the validator"))?;
+        let key = hex::decode(key.trim())
+            .with_context(|| format!("`{VALIDATOR_KEY_ENV}` is not hex encoded"))?;
+        let key = SigningKey::from_slice(&key)
+            .with_context(|| format!("`{VALIDATOR_KEY_ENV}` is not a secp256k1 key"))?;
+        Ok(Self {
+            key,
+            checkpoint_dir,
+            announcer_key,
+        })
+    }
+
+    /// The Ethereum address of the validator key, which ISMs list.
+    pub fn address(&self) -> Address {
+        Address::from_public_key(self.key.verifying_key())
+    }
+
+    /// Signs the EIP-191 hash of `digest`, and returns the signature with its `v` value.
+    fn sign(&self, digest: [u8; 32]) -> anyhow::Result<(k256::ecdsa::Signature, u64)> {
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(eip191_hash_message(digest).as_slice())?;
+        Ok((signature, 27 + u64::from(recovery_id.to_byte())))
+    }
+}
+
+/// A checkpoint signed by the validator, as stored by the Hyperlane agents.
+#[derive(serde::Serialize)]
+struct SignedCheckpoint {
+    value: CheckpointWithMessageId,
+    signature: SignatureParts,
+    serialized_signature: HexString,
+}
+
+#[derive(serde::Serialize)]
+struct SignatureParts {
+    r: String,
+    s: String,
+    v: u64,
+}
+
+/// A slot of the ledger API.
+#[derive(serde::Deserialize)]
+struct SlotResponse {
+    number: u64,
+}
+
+/// The uniqueness data of a rollup account, served by the dedup endpoint of the node.
+#[derive(serde::Deserialize)]
+struct DedupResponse {
+    nonce: u64,
+}
+
+/// A state value, or an item of a state map, served by the module REST API.
+#[derive(serde::Deserialize)]
+struct StateResponse<T> {
+    value: T,
+}
+
+/// Spawns the validator. It stops when the node shuts down.
+pub fn spawn<S: Spec>(
+    config: ValidatorConfig,
+    router: axum::Router,
+    mut shutdown_receiver: watch::Receiver<()>,
+) -> tokio::task::JoinHandle<()>
+where
+    S::Address: HyperlaneAddress,
+{
+    tokio::spawn(async move {
+        tracing::info!(
+            validator = %config.address(),
+            checkpoint_dir = %config.checkpoint_dir.display(),
+            "Starting the Hyperlane validator"
+        );
+        let mut validator = match Validator::<S>::new(config, router).await {
+            Ok(validator) => validator,
+            Err(e) => {
+                tracing::error!(
+                    error = format!("{e:#}"),
+                    "Failed to initialize the validator, the validator is stopped"
+                );
+                return;
+            }
+        };
+
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_receiver.changed() => return,
+            }
+            if let Err(e) = validator.announce().await {
+                tracing::warn!(error = format!("{e:#}"), "Failed to announce the validator");
+            }
+            if let Err(e) = validator.sign_new_checkpoints().await {
+                tracing::warn!(error = format!("{e:#}"), "Failed to sign checkpoints");
+            }
+        }
+    })
+}
+
+struct Validator<S: Spec> {
+    config: ValidatorConfig,
+    router: axum::Router,
+    /// The storage location announced for the checkpoint directory.
+    storage_location: String,
+    /// The rollup account submitting the announcement.
+    announcer: Option<PrivateKeyAndAddress<S>>,
+    /// Whether the storage location is announced in the rollup state.
+    announced: bool,
+    /// When the announcement was last attempted.
+    announcement_submitted: Option<Instant>,
+    /// The tree of the finalized messages, rebuilt from the node at startup.
+    tree: IncrementalMerkle,
+    /// The index of the latest signed checkpoint.
+    latest_signed: Option<u32>,
+}
+
+impl<S: Spec> Validator<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    async fn new(config: ValidatorConfig, router: axum::Router) -> anyhow::Result<Self> {
+        let announcer = match &config.announcer_key {
+            Some(path) => {
+                let contents = tokio::fs::read(path)
+                    .await
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                Some(
+                    serde_json::from_slice(&contents)
+                        .with_context(|| format!("{} is not a rollup key file", path.display()))?,
+                )
+            }
+            None => None,
+        };
+        let mut validator = Self {
+            config,
+            router,
+            storage_location: String::new(),
+            announcer,
+            announced: false,
+            announcement_submitted: None,
+            tree: IncrementalMerkle::default(),
+            latest_signed: None,
+        };
+        validator.load_latest_signed().await?;
+        // The agents' `localStorage` syncer reads `file://` locations with an absolute path.
+        let checkpoint_dir = tokio::fs::canonicalize(&validator.config.checkpoint_dir).await?;
+        validator.storage_location = format!("file://{}", checkpoint_dir.display());
+        Ok(validator)
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.config.checkpoint_dir.join("index.json")
+    }
+
+    fn checkpoint_path(&self, index: u32) -> PathBuf {
+        self.config
+            .checkpoint_dir
+            .join(format!("{index}_with_id.json"))
+    }
+
+    async fn load_latest_signed(&mut self) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.config.checkpoint_dir).await?;
+        self.latest_signed = match tokio::fs::read(self.index_path()).await {
+            Ok(contents) => Some(serde_json::from_slice(&contents).with_context(|| {
+                format!("{} is not a checkpoint index", self.index_path().display())
+            })?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(())
+    }
+
+    /// Submits the announcement of the checkpoint directory, unless the rollup state already
+    /// holds it or the last attempt was less than [`ANNOUNCE_RETRY_INTERVAL`] ago.
+    async fn announce(&mut self) -> anyhow::Result<()> {
+        if self.announced {
+            return Ok(());
+        }
+        let validator = HexString(self.config.address().into_word().0);
+        let path = format!("/modules/validator-announce/state/storage-locations/items/{validator}");
+        let locations = get::<StateResponse<Vec<String>>>(&self.router, &path)
+            .await?
+            .map(|locations| locations.value)
+            .unwrap_or_default();
+        if locations.contains(&self.storage_location) {
+            tracing::info!(
+                storage_location = self.storage_location,
+                "The Hyperlane validator is announced"
+            );
+            self.announced = true;
+            return Ok(());
+        }
+
+        if self
+            .announcement_submitted
+            .is_some_and(|submitted| submitted.elapsed() < ANNOUNCE_RETRY_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.announcement_submitted = Some(Instant::now());
+        let Some(announcer) = &self.announcer else {
+            bail!(
+                "{} is not announced, and no announcer key is configured",
+                self.storage_location
+            );
+        };
+
+        let Some(mailbox) =
+            get::<StateResponse<HexHash>>(&self.router, ANNOUNCE_MAILBOX_PATH).await?
+        else {
+            bail!("The validator announce module is not initialized");
+        };
+        let digest = validator_announce::announcement_digest(
+            config_value!("HYPERLANE_BRIDGE_DOMAIN"),
+            &mailbox.value,
+            &self.storage_location,
+        );
+        let (signature, v) = self.config.sign(digest)?;
+        let mut serialized_signature = signature.to_bytes().to_vec();
+        serialized_signature.push(v as u8);
+
+        // The announcer's next nonce, so that the announcement can't collide with the other
+        // transactions of the account. A resubmission reads it again, and is rejected as a
+        // duplicate if the previous attempt is still pending.
+        let path = format!("/rollup/addresses/{}/dedup", announcer.address);
+        let Some(dedup) = get::<DedupResponse>(&self.router, &path).await? else {
+            bail!("The node doesn't serve the nonce of {}", announcer.address);
+        };
+        let tx = Transaction::<Runtime<S>, S>::new_signed_tx(
+            &announcer.private_key,
+            &<Runtime<S> as sov_modules_stf_blueprint::Runtime<S>>::CHAIN_HASH,
+            UnsignedTransaction::new(
+                RuntimeCall::<S>::ValidatorAnnounce(validator_announce::CallMessage::Announce {
+                    validator,
+                    storage_location: self.storage_location.clone(),
+                    signature: HexString(serialized_signature),
+                }),
+                config_value!("CHAIN_ID"),
+                PriorityFeeBips::ZERO,
+                ANNOUNCE_MAX_FEE,
+                UniquenessData::Nonce(dedup.nonce),
+                None,
+            ),
+        );
+        let input = EvmAndEip712AuthenticatorInput::Standard(RawTx::new(borsh::to_vec(&tx)?));
+        post(
+            &self.router,
+            SEQUENCER_TXS_PATH,
+            &serde_json::json!({ "body": BASE64_STANDARD.encode(borsh::to_vec(&input)?) }),
+        )
+        .await?;
+        tracing::info!(
+            storage_location = self.storage_location,
+            "Submitted the announcement of the Hyperlane validator"
+        );
+        Ok(())
+    }
+
+    /// Inserts the messages dispatched since the last call, and signs a checkpoint for each of
+    /// them that was dispatched at a finalized height. The checkpoints signed before a restart
+    /// are not signed again.
+    ///
+    /// Nothing is signed unless the tree rebuilt from the message IDs has the root of the
+    /// `MerkleTreeHook`.
+    async fn sign_new_checkpoints(&mut self) -> anyhow::Result<()> {
+        let Some(finalized) = get::<SlotResponse>(&self.router, FINALIZED_SLOT_PATH).await? else {
+            // No slot is finalized yet.
+            return Ok(());
+        };
+        let Some(hook_tree) = get::<MerkleTreeResponse>(&self.router, MERKLE_TREE_PATH).await?
+        else {
+            bail!("The node doesn't serve the merkle tree");
+        };
+        ensure!(
+            hook_tree.mirrored,
+            "The message index mirrors {} of the {} leaves of the MerkleTreeHook, refusing to sign",
+            hook_tree.mirrored_count,
+            hook_tree.count
+        );
+        ensure!(
+            hook_tree.count >= self.tree.count(),
+            "The MerkleTreeHook has {} leaves, fewer than the {} finalized ones already seen",
+            hook_tree.count,
+            self.tree.count()
+        );
+
+        // The whole tree is rebuilt to be checked against the hook, but only the finalized
+        // prefix is kept and signed.
+        let mut tree = self.tree.clone();
+        let mut finalized_tree = self.tree.clone();
+        let mut checkpoints = Vec::new();
+        while tree.count() < hook_tree.count {
+            let index = tree.count();
+            let path = format!("/hyperlane/merkle-tree/proofs/{index}");
+            let Some(proof) = get::<ProofResponse>(&self.router, &path).await? else {
+                bail!("The node doesn't serve leaf {index}");
+            };
+            ensure!(
+                proof.root == hook_tree.root && proof.proof.verify(&hook_tree.root),
+                "The MerkleTreeHook changed while its leaves were read"
+            );
+            let message_id = proof.proof.leaf;
+            tree.insert(message_id.clone())?;
+
+            let path = format!("/hyperlane/messages/{message_id}");
+            let Some(message) = get::<MessageResponse>(&self.router, &path).await? else {
+                bail!("The node doesn't serve message {message_id}");
+            };
+            let dispatch_height = message
+                .record
+                .dispatch_height
+                .with_context(|| format!("Message {message_id} wasn't dispatched by the rollup"))?;
+            // Leaves are final in order, so the first one that isn't ends the finalized prefix.
+            if dispatch_height > finalized.number || finalized_tree.count() < index {
+                continue;
+            }
+            finalized_tree = tree.clone();
+
+            if self.latest_signed.is_some_and(|signed| signed >= index) {
+                continue;
+            }
+            checkpoints.push(CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook_address: MERKLE_TREE_HOOK_ADDRESS,
+                    mailbox_domain: config_value!("HYPERLANE_BRIDGE_DOMAIN"),
+                    root: tree.root(),
+                    index,
+                },
+                message_id,
+            });
+        }
+        ensure!(
+            tree.root() == hook_tree.root,
+            "The rebuilt merkle tree has root {}, but the MerkleTreeHook has root {}, refusing to sign",
+            tree.root(),
+            hook_tree.root
+        );
+
+        self.tree = finalized_tree;
+        for checkpoint in checkpoints {
+            self.write_checkpoint(checkpoint).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_checkpoint(
+        &mut self,
+        checkpoint: CheckpointWithMessageId,
+    ) -> anyhow::Result<()> {
+        let index = checkpoint.checkpoint.index;
+        let (signature, v) = self.config.sign(checkpoint.digest())?;
+        let (r, s) = signature.split_bytes();
+        let mut serialized_signature = signature.to_bytes().to_vec();
+        serialized_signature.push(v as u8);
+
+        let signed = SignedCheckpoint {
+            value: checkpoint,
+            signature: SignatureParts {
+                r: format!("{:#x}", U256::from_be_slice(&r)),
+                s: format!("{:#x}", U256::from_be_slice(&s)),
+                v,
+            },
+            serialized_signature: HexString(serialized_signature),
+        };
+
+        write_atomically(&self.checkpoint_path(index), &serde_json::to_vec(&signed)?).await?;
+        write_atomically(&self.index_path(), &serde_json::to_vec(&index)?).await?;
+        self.latest_signed = Some(index);
+        tracing::debug!(index, "Signed a Hyperlane checkpoint");
+        Ok(())
+    }
+}
+
+/// Writes `contents` next to `path` and renames it, so relayers never read a partial file.
+async fn write_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, contents).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+/// Queries the node's REST API. Returns `None` if the resource is not found.
+async fn get<T: serde::de::DeserializeOwned>(
+    router: &axum::Router,
+    path: &str,
+) -> anyhow::Result<Option<T>> {
+    let request = Request::get(path).body(Body::empty())?;
+    let Some(body) = send(router, Method::GET, path, request).await? else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// Posts `body` to the node's REST API.
+async fn post(router: &axum::Router, path: &str, body: &serde_json::Value) -> anyhow::Result<()> {
+    let request = Request::post(path)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(body)?))?;
+    send(router, Method::POST, path, request)
+        .await?
+        .with_context(|| format!("POST {path} is not served"))?;
+    Ok(())
+}
+
+/// Sends `request` to the node's router, and returns the body of the response, or `None` if the
+/// resource is not found.
+async fn send(
+    router: &axum::Router,
+    method: Method,
+    path: &str,
+    request: Request<Body>,
+) -> anyhow::Result<Option<axum::body::Bytes>> {
+    let response = router.clone().oneshot(request).await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body = axum::body::to_bytes(response.into_body(), MAX_RESPONSE_SIZE).await?;
+    ensure!(
+        status.is_success(),
+        "{method} {path} failed with {status}: {}",
+        String::from_utf8_lossy(&body)
+    );
+    Ok(Some(body))
+}
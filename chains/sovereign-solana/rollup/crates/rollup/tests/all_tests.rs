@@ -1,3 +1,6 @@
 mod bank;
+mod eip712_preview;
+mod forced_inclusion;
+mod hyperlane_quote;
 // Add additional tests here
 mod test_helpers;
@@ -0,0 +1,168 @@
+use super::test_helpers::{read_private_keys, start_rollup};
+use anyhow::Context;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use futures::StreamExt;
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::SigningKey;
+use sov_address::{EthereumAddress, EvmCryptoSpec};
+use sov_api_spec::types::AcceptTxBody;
+use sov_cli::NodeClient;
+use sov_mock_da::{BlockProducingConfig, MockAddress, MockDaConfig, MockDaSpec};
+use sov_mock_zkvm::MockZkvm;
+use sov_modules_api::capabilities::UniquenessData;
+use sov_modules_api::configurable_spec::ConfigurableSpec;
+use sov_modules_api::execution_mode::Native;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::{Amount, PrivateKey, RawTx, Spec};
+use sov_rollup_interface::da::DaSpec;
+use sov_rollup_interface::zk::CryptoSpec;
+use sov_state::nomt::prover_storage::NomtProverStorage;
+use sov_state::DefaultStorageSpec;
+use std::str::FromStr;
+use stf_starter::authentication::EvmAndEip712AuthenticatorInput;
+use stf_starter::eip712_preview::{Eip712PreviewResponse, EIP712_PREVIEW_PATH};
+use stf_starter::Runtime;
+use stf_starter::RuntimeCall;
+
+const MAX_TX_FEE: Amount = Amount::new(100_000_000);
+const SLOTS_TO_WAIT: usize = 3;
+
+type Hasher = <EvmCryptoSpec as CryptoSpec>::Hasher;
+type NomtStorage = NomtProverStorage<DefaultStorageSpec<Hasher>, <MockDaSpec as DaSpec>::SlotHash>;
+type TestSpec = ConfigurableSpec<
+    MockDaSpec,
+    MockZkvm,
+    MockZkvm,
+    EthereumAddress,
+    Native,
+    EvmCryptoSpec,
+    NomtStorage,
+>;
+type TestCrypto = <TestSpec as Spec>::CryptoSpec;
+
+/// Signs the digest returned by the preview endpoint and checks that the rollup accepts the
+/// transaction, so the preview matches what `sov_eip712_auth` verifies.
+#[tokio::test(flavor = "multi_thread")]
+async fn eip712_preview_digest_is_accepted() -> Result<(), anyhow::Error> {
+    let (rest_port_tx, rest_port_rx) = tokio::sync::oneshot::channel();
+
+    let rollup_task = tokio::spawn(async {
+        start_rollup(
+            rest_port_tx,
+            std::path::PathBuf::from_str("../../configs/mock/genesis.json")
+                .expect("Failed to build genesis config path"),
+            None,
+            MockDaConfig {
+                connection_string: MockDaConfig::sqlite_in_memory(),
+                sender_address: MockAddress::new([0; 32]),
+                finalization_blocks: 3,
+                block_producing: BlockProducingConfig::Periodic { block_time_ms: 300 },
+                da_layer: None,
+                randomization: None,
+            },
+        )
+        .await;
+    });
+    let rest_port = rest_port_rx.await?.port();
+    let client = NodeClient::new_at_localhost(rest_port).await?;
+
+    tokio::select! {
+        err = rollup_task => err?,
+        res = send_eip712_transfer(&client, rest_port) => res?,
+    }
+    Ok(())
+}
+
+async fn send_eip712_transfer(client: &NodeClient, rest_port: u16) -> Result<(), anyhow::Error> {
+    let key_and_address = read_private_keys::<TestSpec>("tx_signer_private_key.json");
+    let recipient: <TestSpec as Spec>::Address =
+        EthereumAddress::from_str("0x000000000000000000000000000000000000e712")?;
+    let amount = Amount::new(1_000);
+    let gas_token_id = config_value!("GAS_TOKEN_ID");
+
+    let UnsignedTransaction {
+        runtime_call,
+        uniqueness,
+        details,
+        ..
+    } = UnsignedTransaction::<Runtime<TestSpec>, TestSpec>::new(
+        RuntimeCall::<TestSpec>::Bank(sov_bank::CallMessage::<TestSpec>::Transfer {
+            to: recipient,
+            coins: sov_bank::Coins {
+                amount,
+                token_id: gas_token_id,
+            },
+        }),
+        config_value!("CHAIN_ID"),
+        PriorityFeeBips::ZERO,
+        MAX_TX_FEE,
+        UniquenessData::Generation(0),
+        None,
+    );
+
+    let mut slot_subscription = client
+        .client
+        .subscribe_slots()
+        .await
+        .context("Failed to subscribe to slots!")?;
+    slot_subscription.next().await.transpose()?;
+
+    let preview: Eip712PreviewResponse = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{rest_port}{EIP712_PREVIEW_PATH}"))
+        .json(&serde_json::json!({
+            "call": runtime_call,
+            "details": details,
+            "uniqueness": uniqueness,
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(
+        preview.chain_hash,
+        format!(
+            "0x{}",
+            hex::encode(
+                <Runtime<TestSpec> as sov_modules_stf_blueprint::Runtime<TestSpec>>::CHAIN_HASH
+            )
+        )
+    );
+
+    // Sign the digest the way a wallet does for `eth_signTypedData_v4`.
+    let digest = hex::decode(preview.digest.trim_start_matches("0x"))?;
+    let key_hex: String =
+        serde_json::from_value(serde_json::to_value(&key_and_address.private_key)?)?;
+    let signing_key = SigningKey::from_slice(&hex::decode(key_hex)?)?;
+    let signature: k256::ecdsa::Signature = signing_key.sign_prehash(&digest)?;
+    let signature =
+        <TestCrypto as CryptoSpec>::Signature::try_from(signature.to_bytes().as_slice())?;
+
+    let tx = Transaction::<Runtime<TestSpec>, TestSpec>::new_with_details_v0(
+        key_and_address.private_key.pub_key(),
+        runtime_call,
+        signature,
+        uniqueness,
+        details,
+    );
+    let input = EvmAndEip712AuthenticatorInput::Eip712(RawTx::new(borsh::to_vec(&tx)?));
+    client
+        .client
+        .accept_tx(&AcceptTxBody {
+            body: BASE64_STANDARD.encode(borsh::to_vec(&input)?),
+        })
+        .await?;
+
+    for _ in 0..SLOTS_TO_WAIT {
+        slot_subscription.next().await.transpose()?;
+    }
+
+    let balance = client
+        .get_balance::<TestSpec>(&recipient, &gas_token_id, None)
+        .await?;
+    assert_eq!(balance, amount);
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use super::test_helpers::{read_private_keys, start_rollup};
+use anyhow::Context;
+use futures::StreamExt;
+use sov_address::{EthereumAddress, EvmCryptoSpec};
+use sov_cli::NodeClient;
+use sov_mock_da::storable::local_service::StorableMockDaService;
+use sov_mock_da::{BlockProducingConfig, MockAddress, MockDaConfig, MockDaSpec};
+use sov_mock_zkvm::MockZkvm;
+use sov_modules_api::capabilities::UniquenessData;
+use sov_modules_api::configurable_spec::ConfigurableSpec;
+use sov_modules_api::execution_mode::Native;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::{Amount, BlobData, FullyBakedTx, RawTx, Spec};
+use sov_rollup_interface::da::DaSpec;
+use sov_rollup_interface::node::da::DaService;
+use sov_rollup_interface::zk::CryptoSpec;
+use sov_state::nomt::prover_storage::NomtProverStorage;
+use sov_state::DefaultStorageSpec;
+use std::str::FromStr;
+use stf_starter::authentication::EvmAndEip712AuthenticatorInput;
+use stf_starter::Runtime;
+use stf_starter::RuntimeCall;
+
+const MAX_TX_FEE: Amount = Amount::new(100_000_000);
+const SLOTS_TO_WAIT: usize = 5;
+
+type Hasher = <EvmCryptoSpec as CryptoSpec>::Hasher;
+type NomtStorage = NomtProverStorage<DefaultStorageSpec<Hasher>, <MockDaSpec as DaSpec>::SlotHash>;
+type TestSpec = ConfigurableSpec<
+    MockDaSpec,
+    MockZkvm,
+    MockZkvm,
+    EthereumAddress,
+    Native,
+    EvmCryptoSpec,
+    NomtStorage,
+>;
+
+fn da_config(connection_string: String, sender: [u8; 32]) -> MockDaConfig {
+    MockDaConfig {
+        connection_string,
+        sender_address: MockAddress::new(sender),
+        finalization_blocks: 3,
+        block_producing: BlockProducingConfig::Periodic { block_time_ms: 300 },
+        da_layer: None,
+        randomization: None,
+    }
+}
+
+/// Posts a bank transfer straight to the DA layer from an address that is not a registered
+/// sequencer, and checks that the rollup executes it because `bank.transfer` is on the
+/// forced inclusion allow-list in `configs/mock/genesis.json`.
+#[tokio::test(flavor = "multi_thread")]
+async fn forced_inclusion_from_unregistered_sender() -> Result<(), anyhow::Error> {
+    let da_dir = tempfile::tempdir()?;
+    let (client, rollup_task, connection_string) = start_mock_rollup(&da_dir).await?;
+
+    tokio::select! {
+        err = rollup_task => err?,
+        res = send_transfer_through_da(&client, connection_string) => res?,
+    }
+    Ok(())
+}
+
+/// Posts a bank burn, which is not on the forced inclusion allow-list, followed by an allowed
+/// transfer from the same unregistered sender, and checks that only the transfer is executed.
+#[tokio::test(flavor = "multi_thread")]
+async fn forced_inclusion_rejects_calls_outside_allow_list() -> Result<(), anyhow::Error> {
+    let da_dir = tempfile::tempdir()?;
+    let (client, rollup_task, connection_string) = start_mock_rollup(&da_dir).await?;
+
+    tokio::select! {
+        err = rollup_task => err?,
+        res = send_burn_through_da(&client, connection_string) => res?,
+    }
+    Ok(())
+}
+
+async fn start_mock_rollup(
+    da_dir: &tempfile::TempDir,
+) -> Result<(NodeClient, tokio::task::JoinHandle<()>, String), anyhow::Error> {
+    let connection_string = format!(
+        "sqlite://{}?mode=rwc",
+        da_dir.path().join("mock_da.sqlite").display()
+    );
+
+    let (rest_port_tx, rest_port_rx) = tokio::sync::oneshot::channel();
+    let rollup_da_config = da_config(connection_string.clone(), [0; 32]);
+    let rollup_task = tokio::spawn(async {
+        start_rollup(
+            rest_port_tx,
+            std::path::PathBuf::from_str("../../configs/mock/genesis.json")
+                .expect("Failed to build genesis config path"),
+            None,
+            rollup_da_config,
+        )
+        .await;
+    });
+    let rest_port = rest_port_rx.await?.port();
+    let client = NodeClient::new_at_localhost(rest_port).await?;
+
+    Ok((client, rollup_task, connection_string))
+}
+
+fn transfer(to: <TestSpec as Spec>::Address, amount: Amount) -> RuntimeCall<TestSpec> {
+    RuntimeCall::<TestSpec>::Bank(sov_bank::CallMessage::<TestSpec>::Transfer {
+        to,
+        coins: sov_bank::Coins {
+            amount,
+            token_id: config_value!("GAS_TOKEN_ID"),
+        },
+    })
+}
+
+async fn send_transfer_through_da(
+    client: &NodeClient,
+    connection_string: String,
+) -> Result<(), anyhow::Error> {
+    let recipient: <TestSpec as Spec>::Address =
+        EthereumAddress::from_str("0x000000000000000000000000000000000000f0cc")?;
+    let amount = Amount::new(1_000);
+
+    send_through_da(client, connection_string, vec![transfer(recipient, amount)]).await?;
+
+    let balance = client
+        .get_balance::<TestSpec>(&recipient, &config_value!("GAS_TOKEN_ID"), None)
+        .await?;
+    assert_eq!(balance, amount);
+
+    Ok(())
+}
+
+async fn send_burn_through_da(
+    client: &NodeClient,
+    connection_string: String,
+) -> Result<(), anyhow::Error> {
+    let sender = read_private_keys::<TestSpec>("tx_signer_private_key.json").address;
+    let recipient: <TestSpec as Spec>::Address =
+        EthereumAddress::from_str("0x000000000000000000000000000000000000f0cd")?;
+    let amount = Amount::new(1_000);
+    let gas_token_id = config_value!("GAS_TOKEN_ID");
+
+    let initial_balance = client
+        .get_balance::<TestSpec>(&sender, &gas_token_id, None)
+        .await?;
+    let burned = Amount::new(initial_balance.0 / 2);
+    let burn = RuntimeCall::<TestSpec>::Bank(sov_bank::CallMessage::<TestSpec>::Burn {
+        coins: sov_bank::Coins {
+            amount: burned,
+            token_id: gas_token_id,
+        },
+    });
+
+    send_through_da(
+        client,
+        connection_string,
+        vec![burn, transfer(recipient, amount)],
+    )
+    .await?;
+
+    // The transfer after the burn landed, so both blobs were processed.
+    let balance = client
+        .get_balance::<TestSpec>(&recipient, &gas_token_id, None)
+        .await?;
+    assert_eq!(balance, amount);
+
+    // The sender only paid for the transfer: the burn never reached the bank.
+    let balance = client
+        .get_balance::<TestSpec>(&sender, &gas_token_id, None)
+        .await?;
+    assert!(
+        balance.0 + amount.0 + MAX_TX_FEE.0 >= initial_balance.0,
+        "the burn outside the allow-list was executed: {initial_balance} -> {balance}"
+    );
+
+    Ok(())
+}
+
+/// Signs `calls` with the test key and posts each of them in its own blob from a DA address that
+/// is not in the sequencer registry, then waits for the rollup to process them.
+async fn send_through_da(
+    client: &NodeClient,
+    connection_string: String,
+    calls: Vec<RuntimeCall<TestSpec>>,
+) -> Result<(), anyhow::Error> {
+    let key_and_address = read_private_keys::<TestSpec>("tx_signer_private_key.json");
+
+    let mut slot_subscription = client
+        .client
+        .subscribe_slots()
+        .await
+        .context("Failed to subscribe to slots!")?;
+    slot_subscription.next().await.transpose()?;
+
+    // A separate DA client with a sender address that is not in the sequencer registry.
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(());
+    let unregistered_da =
+        StorableMockDaService::from_config(da_config(connection_string, [42; 32]), shutdown_rx)
+            .await;
+
+    for (generation, msg) in (0..).zip(calls) {
+        let tx = Transaction::<Runtime<TestSpec>, TestSpec>::new_signed_tx(
+            &key_and_address.private_key,
+            &<Runtime<TestSpec> as sov_modules_stf_blueprint::Runtime<TestSpec>>::CHAIN_HASH,
+            UnsignedTransaction::new(
+                msg,
+                config_value!("CHAIN_ID"),
+                PriorityFeeBips::ZERO,
+                MAX_TX_FEE,
+                UniquenessData::Generation(generation),
+                None,
+            ),
+        );
+
+        let input = EvmAndEip712AuthenticatorInput::Standard(RawTx::new(borsh::to_vec(&tx)?));
+        let blob = BlobData::new_batch(vec![FullyBakedTx::new(borsh::to_vec(&input)?)]);
+        unregistered_da
+            .send_transaction(&borsh::to_vec(&blob)?)
+            .await
+            .await??;
+    }
+
+    for _ in 0..SLOTS_TO_WAIT {
+        slot_subscription.next().await.transpose()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use super::test_helpers::{read_private_keys, start_rollup};
+use anyhow::Context;
+use futures::StreamExt;
+use sov_address::{EthereumAddress, EvmCryptoSpec};
+use sov_cli::NodeClient;
+use sov_hyperlane_integration::igp::{
+    CallMessage as IgpCallMessage, DomainDefaultGas, DomainOracleData, ExchangeRateAndGasPrice,
+};
+use sov_mock_da::{BlockProducingConfig, MockAddress, MockDaConfig, MockDaSpec};
+use sov_mock_zkvm::MockZkvm;
+use sov_modules_api::capabilities::UniquenessData;
+use sov_modules_api::configurable_spec::ConfigurableSpec;
+use sov_modules_api::execution_mode::Native;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::Amount;
+use sov_rollup_interface::da::DaSpec;
+use sov_rollup_interface::zk::CryptoSpec;
+use sov_state::nomt::prover_storage::NomtProverStorage;
+use sov_state::DefaultStorageSpec;
+use std::str::FromStr;
+use stf_starter::hyperlane_quote::{QuoteResponse, QUOTE_PATH};
+use stf_starter::Runtime;
+use stf_starter::RuntimeCall;
+
+const MAX_TX_FEE: Amount = Amount::new(100_000_000);
+const SLOTS_TO_WAIT: usize = 3;
+
+/// The Solana testnet domain.
+const DESTINATION: u32 = 1337;
+const DEFAULT_GAS: u128 = 50_000;
+const GAS_PRICE: u128 = 1_000;
+const TOKEN_EXCHANGE_RATE: u128 = 10_000_000_000;
+
+type Hasher = <EvmCryptoSpec as CryptoSpec>::Hasher;
+type NomtStorage = NomtProverStorage<DefaultStorageSpec<Hasher>, <MockDaSpec as DaSpec>::SlotHash>;
+type TestSpec = ConfigurableSpec<
+    MockDaSpec,
+    MockZkvm,
+    MockZkvm,
+    EthereumAddress,
+    Native,
+    EvmCryptoSpec,
+    NomtStorage,
+>;
+
+/// Configures the IGP of a relayer, and checks that the quote endpoint prices dispatches from
+/// its oracle data and default gas.
+#[tokio::test(flavor = "multi_thread")]
+async fn quote_follows_relayer_igp_config() -> Result<(), anyhow::Error> {
+    let (rest_port_tx, rest_port_rx) = tokio::sync::oneshot::channel();
+
+    let rollup_task = tokio::spawn(async {
+        start_rollup(
+            rest_port_tx,
+            std::path::PathBuf::from_str("../../configs/mock/genesis.json")
+                .expect("Failed to build genesis config path"),
+            None,
+            MockDaConfig {
+                connection_string: MockDaConfig::sqlite_in_memory(),
+                sender_address: MockAddress::new([0; 32]),
+                finalization_blocks: 3,
+                block_producing: BlockProducingConfig::Periodic { block_time_ms: 300 },
+                da_layer: None,
+                randomization: None,
+            },
+        )
+        .await;
+    });
+    let rest_port = rest_port_rx.await?.port();
+    let client = NodeClient::new_at_localhost(rest_port).await?;
+
+    tokio::select! {
+        err = rollup_task => err?,
+        res = configure_relayer_and_quote(&client, rest_port) => res?,
+    }
+    Ok(())
+}
+
+async fn quote(rest_port: u16, query: &[(&str, String)]) -> Result<QuoteResponse, anyhow::Error> {
+    Ok(reqwest::Client::new()
+        .get(format!("http://127.0.0.1:{rest_port}{QUOTE_PATH}"))
+        .query(query)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+async fn configure_relayer_and_quote(
+    client: &NodeClient,
+    rest_port: u16,
+) -> Result<(), anyhow::Error> {
+    let key_and_address = read_private_keys::<TestSpec>("tx_signer_private_key.json");
+    let relayer = key_and_address.address.to_string();
+
+    let tx = Transaction::<Runtime<TestSpec>, TestSpec>::new_signed_tx(
+        &key_and_address.private_key,
+        &<Runtime<TestSpec> as sov_modules_stf_blueprint::Runtime<TestSpec>>::CHAIN_HASH,
+        UnsignedTransaction::new(
+            RuntimeCall::<TestSpec>::InterchainGasPaymaster(IgpCallMessage::SetRelayerConfig {
+                beneficiary: None,
+                default_gas: DEFAULT_GAS.into(),
+                domain_default_gas: vec![DomainDefaultGas {
+                    domain: DESTINATION,
+                    default_gas: DEFAULT_GAS.into(),
+                }],
+                domain_oracle_data: vec![DomainOracleData {
+                    domain: DESTINATION,
+                    data_value: ExchangeRateAndGasPrice {
+                        gas_price: GAS_PRICE.into(),
+                        token_exchange_rate: TOKEN_EXCHANGE_RATE.into(),
+                    },
+                }],
+            }),
+            config_value!("CHAIN_ID"),
+            PriorityFeeBips::ZERO,
+            MAX_TX_FEE,
+            UniquenessData::Generation(0),
+            None,
+        ),
+    );
+
+    let mut slot_subscription = client
+        .client
+        .subscribe_slots()
+        .await
+        .context("Failed to subscribe to slots!")?;
+    slot_subscription.next().await.transpose()?;
+
+    client.client.send_txs_to_sequencer(&[tx]).await?;
+    for _ in 0..SLOTS_TO_WAIT {
+        slot_subscription.next().await.transpose()?;
+    }
+
+    let base = vec![
+        ("destination", DESTINATION.to_string()),
+        ("relayer", relayer),
+    ];
+    let with = |extra: &[(&'static str, String)]| {
+        let mut query = base.clone();
+        query.extend_from_slice(extra);
+        query
+    };
+
+    // Without a gas limit, the relayer's default gas for the destination is paid for.
+    let default_quote = quote(rest_port, &base).await?;
+    assert!(default_quote.igp_payment > Amount::ZERO);
+    assert_eq!(default_quote.hook_fee, Amount::ZERO);
+    assert_eq!(default_quote.total, default_quote.igp_payment);
+
+    let explicit_quote = quote(rest_port, &with(&[("gas_limit", DEFAULT_GAS.to_string())])).await?;
+    assert_eq!(explicit_quote.igp_payment, default_quote.igp_payment);
+
+    // The payment scales with the gas limit, and not with the body size.
+    let double_quote = quote(
+        rest_port,
+        &with(&[("gas_limit", (2 * DEFAULT_GAS).to_string())]),
+    )
+    .await?;
+    assert_eq!(
+        double_quote.igp_payment,
+        default_quote
+            .igp_payment
+            .checked_add(default_quote.igp_payment)
+            .unwrap()
+    );
+    let sized_quote = quote(rest_port, &with(&[("body_size", "1024".to_string())])).await?;
+    assert_eq!(sized_quote.total, default_quote.total);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Feeds arbitrary bytes to the native entry points of the rollup authenticator.
+//!
+//! Run with `cargo +nightly fuzz run authenticator` from `crates/stf`. Any panic, or any
+//! disagreement between parsing, hashing, decoding and authentication on whether the transaction
+//! is valid, its hash or its call, is a bug.
+#![no_main]
+
+use std::cell::RefCell;
+
+use libfuzzer_sys::fuzz_target;
+use sov_address::{EthereumAddress, EvmCryptoSpec};
+use sov_mock_da::MockDaSpec;
+use sov_mock_zkvm::MockZkvm;
+use sov_modules_api::capabilities::{calculate_hash, FatalError, TransactionAuthenticator};
+use sov_modules_api::configurable_spec::ConfigurableSpec;
+use sov_modules_api::execution_mode::Native;
+use sov_modules_api::FullyBakedTx;
+use sov_modules_stf_blueprint::{GenesisParams, Runtime as _};
+use sov_rollup_interface::da::DaSpec;
+use sov_rollup_interface::zk::CryptoSpec;
+use sov_state::nomt::prover_storage::NomtProverStorage;
+use sov_state::DefaultStorageSpec;
+use sov_test_utils::runtime::TestRunner;
+use stf_starter::authentication::EvmAndEip712AuthenticatorInput;
+use stf_starter::Runtime;
+
+type Hasher = <EvmCryptoSpec as CryptoSpec>::Hasher;
+type NomtStorage = NomtProverStorage<DefaultStorageSpec<Hasher>, <MockDaSpec as DaSpec>::SlotHash>;
+type FuzzSpec = ConfigurableSpec<
+    MockDaSpec,
+    MockZkvm,
+    MockZkvm,
+    EthereumAddress,
+    Native,
+    EvmCryptoSpec,
+    NomtStorage,
+>;
+type FuzzRuntime = Runtime<FuzzSpec>;
+type Auth = <FuzzRuntime as sov_modules_stf_blueprint::Runtime<FuzzSpec>>::Auth;
+
+thread_local! {
+    /// The state `authenticate` reads, at the genesis of the mock rollup.
+    static RUNNER: RefCell<TestRunner<FuzzRuntime, FuzzSpec>> = {
+        let genesis_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../../configs/mock/genesis.json");
+        let genesis_config = FuzzRuntime::genesis_config(&genesis_path)
+            .expect("The mock genesis config is valid");
+        RefCell::new(TestRunner::new_with_genesis(
+            GenesisParams {
+                runtime: genesis_config,
+            },
+            FuzzRuntime::default(),
+        ))
+    };
+}
+
+fuzz_target!(|data: &[u8]| {
+    let tx = FullyBakedTx::new(data.to_vec());
+
+    let parsed = EvmAndEip712AuthenticatorInput::parse(&tx);
+    let hash = Auth::compute_tx_hash(&tx);
+    let decoded = Auth::decode_serialized_tx(&tx);
+    let authenticated = RUNNER.with(|runner| {
+        runner
+            .borrow_mut()
+            .query_state(|state| Auth::authenticate(&tx, state))
+    });
+
+    match (parsed, hash) {
+        (Ok(input), Ok(hash)) => {
+            assert_eq!(hash, calculate_hash::<FuzzSpec>(&input.raw_tx().data));
+        }
+        (Err(_), Err(_)) => {
+            assert!(matches!(decoded, Err(FatalError::DeserializationFailed(_))));
+        }
+        (parsed, hash) => panic!("parse ok = {}, hash ok = {}", parsed.is_ok(), hash.is_ok()),
+    }
+
+    match (decoded, authenticated) {
+        (Ok(decoded), Ok((tx_and_raw_hash, _, call))) => {
+            assert_eq!(Ok(tx_and_raw_hash.raw_tx_hash), Auth::compute_tx_hash(&tx));
+            assert_eq!(
+                FuzzRuntime::wrap_call(call),
+                FuzzRuntime::wrap_call(decoded)
+            );
+        }
+        (Err(e), Ok(_)) => panic!("authenticated a transaction that doesn't decode: {e:?}"),
+        (_, Err(_)) => {}
+    }
+});
@@ -1,17 +1,30 @@
+mod ethereum;
+pub mod multisig;
+pub mod session_key;
+pub mod solana_offchain;
+
 use std::marker::PhantomData;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use forced_inclusion::ForcedInclusion;
 use sov_eip712_auth::{SchemaProvider, Secp256k1CryptoSpec};
 use sov_modules_api::capabilities::{
     self, BatchFromUnregisteredSequencer, FatalError, TransactionAuthenticator,
     UnregisteredAuthenticationError,
 };
 use sov_modules_api::runtime::capabilities::AuthenticationError;
+use sov_modules_api::transaction::UnsignedTransaction;
 use sov_modules_api::{
-    DispatchCall, FullyBakedTx, GetGasPrice, ProvableStateReader, RawTx, Runtime, Spec,
+    AuthenticatedTransactionAndRawHash, AuthenticatedTransactionData, AuthorizationData,
+    CredentialId, DispatchCall, FullyBakedTx, GetGasPrice, ProvableStateReader, RawTx, Runtime,
+    Spec, TxHash,
 };
 use sov_state::User;
 
+use crate::forced_inclusion::CallPath;
+pub use ethereum::EthereumCall;
+pub use session_key::SessionKeyScope;
+
 /// See [`TransactionAuthenticator::Input`].
 #[derive(std::fmt::Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub enum EvmAndEip712AuthenticatorInput<T = RawTx> {
@@ -21,6 +34,73 @@ pub enum EvmAndEip712AuthenticatorInput<T = RawTx> {
     /// Authenticate using the standard `sov-module` authenticator, which uses the default
     /// signature scheme and hashing algorithm defined in the rollup's [`Spec`].
     Standard(T),
+    /// Authenticate using a signature produced by a Solana wallet over a Solana "off-chain message"
+    /// envelope. The transaction is encoded the same way as a standard sov transaction, but the
+    /// signature covers the envelope instead of the raw signing payload.
+    SolanaOffchain(T),
+    /// Authenticate a k-of-n multisig transaction: a single unsigned transaction body signed by
+    /// several members of a multisig registered in the `multisig` module.
+    /// See [`multisig::MultisigTx`] for the payload layout.
+    Multisig(T),
+    /// Authenticate a standard sov transaction signed by a session key. The transaction is executed
+    /// on behalf of the account that granted the key, within the limits stored in the `session-keys` module.
+    SessionKey(T),
+    /// Authenticate a raw, EIP-2718 encoded Ethereum transaction. The transaction is executed by
+    /// the EVM module, which is only part of the runtime when the `evm` feature is enabled.
+    Evm(T),
+}
+
+impl EvmAndEip712AuthenticatorInput {
+    /// Decodes the authenticator input of a transaction. Every authenticator entry point parses
+    /// the outer envelope through this function, so they all accept and reject the same payloads.
+    pub fn parse(tx: &FullyBakedTx) -> Result<Self, borsh::io::Error> {
+        borsh::from_slice(&tx.data)
+    }
+
+    /// Returns the raw transaction wrapped by the input, whatever the variant.
+    pub fn raw_tx(&self) -> &RawTx {
+        match self {
+            Self::Eip712(tx)
+            | Self::Standard(tx)
+            | Self::SolanaOffchain(tx)
+            | Self::Multisig(tx)
+            | Self::SessionKey(tx)
+            | Self::Evm(tx) => tx,
+        }
+    }
+}
+
+/// Builds the authentication output for a transaction whose signatures were already verified.
+fn authentication_output<S, Rt>(
+    unsigned_tx: UnsignedTransaction<Rt, S>,
+    credential_id: CredentialId,
+    credentials: capabilities::Credentials,
+    raw_tx_hash: TxHash,
+) -> capabilities::AuthenticationOutput<S, Rt::Decodable>
+where
+    S: Spec,
+    Rt: DispatchCall<Spec = S>,
+{
+    let UnsignedTransaction {
+        runtime_call,
+        uniqueness,
+        details,
+        ..
+    } = unsigned_tx;
+
+    (
+        AuthenticatedTransactionAndRawHash {
+            raw_tx_hash,
+            authenticated_tx: AuthenticatedTransactionData(details),
+        },
+        AuthorizationData {
+            uniqueness,
+            credential_id,
+            credentials,
+            default_address: credential_id.into(),
+        },
+        runtime_call,
+    )
 }
 
 /// EIP712 transaction authenticator. See [`TransactionAuthenticator`].
@@ -29,7 +109,7 @@ pub struct EvmAndEip712Authenticator<S, Rt, SP>(PhantomData<(S, Rt, SP)>);
 impl<S, Rt, SP> TransactionAuthenticator<S> for EvmAndEip712Authenticator<S, Rt, SP>
 where
     S: Spec<CryptoSpec: Secp256k1CryptoSpec>,
-    Rt: Runtime<S> + DispatchCall<Spec = S>,
+    Rt: Runtime<S> + DispatchCall<Spec = S> + SessionKeyScope + EthereumCall + CallPath + Default,
     SP: SchemaProvider,
 {
     type Decodable = EvmAndEip712AuthenticatorInput<<Rt as DispatchCall>::Decodable>;
@@ -42,7 +122,7 @@ where
         capabilities::AuthenticationOutput<S, Self::Decodable>,
         capabilities::AuthenticationError,
     > {
-        let input: EvmAndEip712AuthenticatorInput = borsh::from_slice(&tx.data).map_err(|e| {
+        let input = EvmAndEip712AuthenticatorInput::parse(tx).map_err(|e| {
             sov_modules_api::capabilities::fatal_deserialization_error::<_, S, _>(
                 &tx.data, e, state,
             )
@@ -73,6 +153,46 @@ where
                     EvmAndEip712AuthenticatorInput::Standard(runtime_call),
                 ))
             }
+            EvmAndEip712AuthenticatorInput::SolanaOffchain(tx) => {
+                let (tx_and_raw_hash, auth_data, runtime_call) =
+                    solana_offchain::authenticate::<_, S, Rt>(&tx.data, &Rt::CHAIN_HASH, state)?;
+
+                Ok((
+                    tx_and_raw_hash,
+                    auth_data,
+                    EvmAndEip712AuthenticatorInput::SolanaOffchain(runtime_call),
+                ))
+            }
+            EvmAndEip712AuthenticatorInput::Multisig(tx) => {
+                let (tx_and_raw_hash, auth_data, runtime_call) =
+                    multisig::authenticate::<_, S, Rt>(&tx.data, &Rt::CHAIN_HASH, state)?;
+
+                Ok((
+                    tx_and_raw_hash,
+                    auth_data,
+                    EvmAndEip712AuthenticatorInput::Multisig(runtime_call),
+                ))
+            }
+            EvmAndEip712AuthenticatorInput::SessionKey(tx) => {
+                let (tx_and_raw_hash, auth_data, runtime_call) =
+                    session_key::authenticate::<_, S, Rt>(&tx.data, state)?;
+
+                Ok((
+                    tx_and_raw_hash,
+                    auth_data,
+                    EvmAndEip712AuthenticatorInput::SessionKey(runtime_call),
+                ))
+            }
+            EvmAndEip712AuthenticatorInput::Evm(tx) => {
+                let (tx_and_raw_hash, auth_data, runtime_call) =
+                    ethereum::authenticate::<_, S, Rt>(&tx.data, state)?;
+
+                Ok((
+                    tx_and_raw_hash,
+                    auth_data,
+                    EvmAndEip712AuthenticatorInput::Evm(runtime_call),
+                ))
+            }
         }
     }
 
@@ -80,24 +200,17 @@ where
     fn compute_tx_hash(
         tx: &sov_modules_api::FullyBakedTx,
     ) -> anyhow::Result<sov_modules_api::TxHash> {
-        let input: EvmAndEip712AuthenticatorInput = borsh::from_slice(&tx.data)?;
-
-        match input {
-            EvmAndEip712AuthenticatorInput::Eip712(tx)
-            | EvmAndEip712AuthenticatorInput::Standard(tx) => {
-                Ok(capabilities::calculate_hash::<S>(&tx.data))
-            }
-        }
+        let input = EvmAndEip712AuthenticatorInput::parse(tx)?;
+        Ok(capabilities::calculate_hash::<S>(&input.raw_tx().data))
     }
 
     #[cfg(feature = "native")]
     fn decode_serialized_tx(
         tx: &FullyBakedTx,
     ) -> Result<Self::Decodable, sov_modules_api::capabilities::FatalError> {
-        let auth_variant: EvmAndEip712AuthenticatorInput =
-            borsh::from_slice(&tx.data).map_err(|e| {
-                sov_modules_api::capabilities::FatalError::DeserializationFailed(e.to_string())
-            })?;
+        let auth_variant = EvmAndEip712AuthenticatorInput::parse(tx).map_err(|e| {
+            sov_modules_api::capabilities::FatalError::DeserializationFailed(e.to_string())
+        })?;
 
         match &auth_variant {
             EvmAndEip712AuthenticatorInput::Standard(raw_tx) => {
@@ -112,6 +225,22 @@ where
                 >(&raw_tx.data)?;
                 Ok(EvmAndEip712AuthenticatorInput::Eip712(call))
             }
+            EvmAndEip712AuthenticatorInput::SolanaOffchain(raw_tx) => {
+                let call = solana_offchain::decode_sov_tx::<S, Rt>(&raw_tx.data)?;
+                Ok(EvmAndEip712AuthenticatorInput::SolanaOffchain(call))
+            }
+            EvmAndEip712AuthenticatorInput::Multisig(raw_tx) => {
+                let call = multisig::decode_sov_tx::<S, Rt>(&raw_tx.data)?;
+                Ok(EvmAndEip712AuthenticatorInput::Multisig(call))
+            }
+            EvmAndEip712AuthenticatorInput::SessionKey(raw_tx) => {
+                let call = capabilities::decode_sov_tx::<S, Rt>(&raw_tx.data)?;
+                Ok(EvmAndEip712AuthenticatorInput::SessionKey(call))
+            }
+            EvmAndEip712AuthenticatorInput::Evm(raw_tx) => {
+                let call = ethereum::decode_evm_tx::<Rt>(&raw_tx.data)?;
+                Ok(EvmAndEip712AuthenticatorInput::Evm(call))
+            }
         }
     }
 
@@ -122,7 +251,7 @@ where
         capabilities::AuthenticationOutput<S, Self::Decodable>,
         capabilities::UnregisteredAuthenticationError,
     > {
-        let Self::Input::Standard(input) = borsh::from_slice(&batch.tx.data)
+        let Self::Input::Standard(input) = EvmAndEip712AuthenticatorInput::parse(&batch.tx)
             .map_err(|_| UnregisteredAuthenticationError::InvalidAuthenticationDiscriminant)?
         else {
             return Err(UnregisteredAuthenticationError::InvalidAuthenticationDiscriminant);
@@ -143,7 +272,19 @@ where
                 }
             })?;
 
-        if Rt::allow_unregistered_tx(&runtime_call) {
+        // Sequencer registration is always allowed, other calls come from the genesis allow-list.
+        let (module, call) = Rt::call_path(&runtime_call);
+        let is_allowed = Rt::allow_unregistered_tx(&runtime_call)
+            || ForcedInclusion::<S>::default()
+                .is_allowed(module, call, state)
+                .map_err(|e| {
+                    UnregisteredAuthenticationError::FatalError(
+                        FatalError::Other(e.to_string()),
+                        tx_and_raw_hash.raw_tx_hash,
+                    )
+                })?;
+
+        if is_allowed {
             Ok((
                 tx_and_raw_hash,
                 auth_data,
@@ -163,3 +304,15 @@ where
         EvmAndEip712AuthenticatorInput::Standard(tx)
     }
 }
+
+#[cfg(feature = "evm")]
+impl<S, Rt, SP> sov_evm::EthereumAuthenticator<S> for EvmAndEip712Authenticator<S, Rt, SP>
+where
+    S: Spec<CryptoSpec: Secp256k1CryptoSpec>,
+    Rt: Runtime<S> + DispatchCall<Spec = S> + SessionKeyScope + EthereumCall + CallPath + Default,
+    SP: SchemaProvider,
+{
+    fn add_ethereum_auth(tx: RawTx) -> Self::Input {
+        EvmAndEip712AuthenticatorInput::Evm(tx)
+    }
+}
@@ -0,0 +1,57 @@
+//! Authentication of raw Ethereum transactions.
+//!
+//! The payload is an EIP-2718 encoded, signed Ethereum transaction, as produced by MetaMask or
+//! `eth_sendRawTransaction`. Signature recovery, nonces and gas accounting are handled by
+//! `sov-evm`; the resulting call is dispatched to the EVM module of the runtime.
+use sov_modules_api::capabilities::{AuthenticationError, AuthenticationOutput, FatalError};
+use sov_modules_api::{DispatchCall, GetGasPrice, ProvableStateReader, Spec};
+use sov_state::User;
+
+/// Runtime specific knowledge required to dispatch Ethereum transactions.
+pub trait EthereumCall: DispatchCall {
+    /// Wraps an EVM call into a runtime call, or returns `None` if the runtime was built without
+    /// the EVM module.
+    fn evm_call(call: sov_evm::CallMessage) -> Option<Self::Decodable>;
+}
+
+/// Authenticates a raw Ethereum transaction.
+pub fn authenticate<Accessor, S, Rt>(
+    raw_tx: &[u8],
+    state: &mut Accessor,
+) -> Result<AuthenticationOutput<S, Rt::Decodable>, AuthenticationError>
+where
+    Accessor: ProvableStateReader<User, Spec = S> + GetGasPrice<Spec = S>,
+    S: Spec,
+    Rt: DispatchCall<Spec = S> + EthereumCall,
+{
+    let (tx_and_raw_hash, auth_data, evm_call) = sov_evm::authenticate::<_, S>(raw_tx, state)?;
+
+    let Some(runtime_call) = Rt::evm_call(evm_call) else {
+        return Err(AuthenticationError::FatalError(
+            evm_disabled(),
+            tx_and_raw_hash.raw_tx_hash,
+        ));
+    };
+
+    Ok((tx_and_raw_hash, auth_data, runtime_call))
+}
+
+/// Decodes a raw Ethereum transaction without verifying its signature.
+#[cfg(feature = "native")]
+pub fn decode_evm_tx<Rt: EthereumCall>(raw_tx: &[u8]) -> Result<Rt::Decodable, FatalError> {
+    use alloy_consensus::transaction::Decodable2718;
+
+    alloy_consensus::TxEnvelope::decode_2718(&mut &raw_tx[..])
+        .map_err(|e| FatalError::DeserializationFailed(e.to_string()))?;
+
+    let call = sov_evm::CallMessage {
+        rlp: sov_evm::RlpEvmTransaction {
+            rlp: raw_tx.to_vec(),
+        },
+    };
+    Rt::evm_call(call).ok_or_else(evm_disabled)
+}
+
+fn evm_disabled() -> FatalError {
+    FatalError::Other("This rollup was built without the EVM module".to_string())
+}
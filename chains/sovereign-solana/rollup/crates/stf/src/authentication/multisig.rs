@@ -0,0 +1,156 @@
+//! Authentication of k-of-n multisig transactions.
+//!
+//! A multisig transaction carries a single unsigned transaction body and a list of member
+//! signatures over `"sov-multisig" || multisig credential ID || borsh(unsigned tx) || chain hash`.
+//! The prefix and the credential ID keep a member signature from being replayed as a standard
+//! transaction signature, or for another multisig the member belongs to. The transaction is
+//! accepted when the number of registered members that signed it reaches the threshold stored in
+//! the `multisig` module. A member may only sign once.
+use std::collections::BTreeSet;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use multisig::{Multisig, MultisigConfig};
+use sov_modules_api::capabilities::{
+    self, AuthenticationError, AuthenticationOutput, Credentials, FatalError,
+};
+use sov_modules_api::transaction::UnsignedTransaction;
+use sov_modules_api::{
+    CredentialId, CryptoSpec, DispatchCall, GetGasPrice, ProvableStateReader, PublicKey, RawTx,
+    Signature, Spec,
+};
+use sov_state::User;
+
+/// The domain separator prepended to the payload signed by multisig members.
+pub const SIGNING_DOMAIN: &[u8] = b"sov-multisig";
+
+/// A signature from a single multisig member.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[borsh(bound(serialize = "", deserialize = ""))]
+pub struct MemberSignature<C: CryptoSpec> {
+    /// The public key of the member.
+    pub pub_key: C::PublicKey,
+    /// The member's signature over the unsigned transaction and the chain hash.
+    pub signature: C::Signature,
+}
+
+/// The payload of the `Multisig` authenticator variant.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[borsh(bound(serialize = "", deserialize = ""))]
+pub struct MultisigTx<C: CryptoSpec> {
+    /// The credential ID of the registered multisig.
+    pub multisig: CredentialId,
+    /// The borsh encoded `UnsignedTransaction` shared by all signers.
+    pub unsigned_tx: RawTx,
+    /// The member signatures.
+    pub signatures: Vec<MemberSignature<C>>,
+}
+
+/// Returns the bytes each member of `multisig` signs.
+pub fn signing_payload(
+    multisig: &CredentialId,
+    unsigned_tx: &[u8],
+    chain_hash: &[u8; 32],
+) -> Vec<u8> {
+    [
+        SIGNING_DOMAIN,
+        multisig.0.as_slice(),
+        unsigned_tx,
+        chain_hash.as_slice(),
+    ]
+    .concat()
+}
+
+/// Checks that enough distinct members of `config` signed `tx`.
+pub fn verify_signatures<C: CryptoSpec>(
+    tx: &MultisigTx<C>,
+    config: &MultisigConfig,
+    chain_hash: &[u8; 32],
+) -> Result<(), FatalError> {
+    let payload = signing_payload(&tx.multisig, &tx.unsigned_tx.data, chain_hash);
+    let mut signers = BTreeSet::new();
+    for member in &tx.signatures {
+        let credential_id = member.pub_key.credential_id::<C::Hasher>();
+        if !config.members.contains(&credential_id) {
+            return Err(FatalError::SigVerificationFailed(format!(
+                "{credential_id} is not a member of multisig {}",
+                tx.multisig
+            )));
+        }
+        if !signers.insert(credential_id) {
+            return Err(FatalError::SigVerificationFailed(format!(
+                "{credential_id} signed multisig {} more than once",
+                tx.multisig
+            )));
+        }
+
+        member
+            .signature
+            .verify(&member.pub_key, &payload)
+            .map_err(|e| FatalError::SigVerificationFailed(e.to_string()))?;
+    }
+
+    if signers.len() < usize::from(config.threshold) {
+        return Err(FatalError::SigVerificationFailed(format!(
+            "Multisig {} requires {} signatures, got {}",
+            tx.multisig,
+            config.threshold,
+            signers.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the signatures against the registered multisig and returns the authentication output.
+pub fn authenticate<Accessor, S, Rt>(
+    raw_tx: &[u8],
+    chain_hash: &[u8; 32],
+    state: &mut Accessor,
+) -> Result<AuthenticationOutput<S, Rt::Decodable>, AuthenticationError>
+where
+    Accessor: ProvableStateReader<User, Spec = S> + GetGasPrice<Spec = S>,
+    S: Spec,
+    Rt: DispatchCall<Spec = S>,
+{
+    let raw_tx_hash = capabilities::calculate_hash::<S>(raw_tx);
+    let fatal = |err: FatalError| AuthenticationError::FatalError(err, raw_tx_hash);
+
+    let tx: MultisigTx<S::CryptoSpec> = borsh::from_slice(raw_tx)
+        .map_err(|e| capabilities::fatal_deserialization_error::<_, S, _>(raw_tx, e, state))?;
+
+    let config = Multisig::<S>::default()
+        .config(&tx.multisig, state)
+        .map_err(|e| fatal(FatalError::Other(e.to_string())))?
+        .ok_or_else(|| {
+            fatal(FatalError::Other(format!(
+                "Multisig {} is not registered",
+                tx.multisig
+            )))
+        })?;
+
+    verify_signatures(&tx, &config, chain_hash).map_err(fatal)?;
+
+    let unsigned_tx: UnsignedTransaction<Rt, S> = borsh::from_slice(&tx.unsigned_tx.data)
+        .map_err(|e| fatal(FatalError::DeserializationFailed(e.to_string())))?;
+
+    Ok(super::authentication_output(
+        unsigned_tx,
+        tx.multisig,
+        Credentials::new(config),
+        raw_tx_hash,
+    ))
+}
+
+/// Decodes the runtime call of a multisig transaction without verifying the signatures.
+#[cfg(feature = "native")]
+pub fn decode_sov_tx<S, Rt>(raw_tx: &[u8]) -> Result<Rt::Decodable, FatalError>
+where
+    S: Spec,
+    Rt: DispatchCall<Spec = S>,
+{
+    let tx: MultisigTx<S::CryptoSpec> =
+        borsh::from_slice(raw_tx).map_err(|e| FatalError::DeserializationFailed(e.to_string()))?;
+    let unsigned_tx: UnsignedTransaction<Rt, S> = borsh::from_slice(&tx.unsigned_tx.data)
+        .map_err(|e| FatalError::DeserializationFailed(e.to_string()))?;
+
+    Ok(unsigned_tx.runtime_call)
+}
@@ -0,0 +1,164 @@
+//! Authentication of transactions signed by session keys.
+//!
+//! A session key transaction is a standard sov transaction signed by the session key. After the
+//! signature is verified, the grant stored in the `session-keys` module is checked against the
+//! call the transaction sends, the tokens it moves and its `max_fee`, and the transaction is
+//! executed as the account that issued the grant. The runtime charges the tokens to the grant
+//! when the call is dispatched and the fee when the transaction completes, so the spend caps hold
+//! across transactions.
+use session_keys::{SessionKeyCredential, SessionKeyGrant, SessionKeys};
+use sov_bank::Coins;
+use sov_modules_api::capabilities::{
+    self, AuthenticationError, AuthenticationOutput, Credentials, FatalError,
+};
+use sov_modules_api::digest::Digest;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::{
+    Amount, CredentialId, CryptoSpec, DispatchCall, GetGasPrice, ProvableStateReader, Runtime,
+    Spec, StateReader,
+};
+use sov_state::User;
+
+/// The domain separator of the credential IDs session keys act under.
+pub const CREDENTIAL_DOMAIN: &[u8] = b"sov-session-key";
+
+/// The calls session keys can sign, by the `module.call` names grants list them with.
+pub const SESSION_KEY_CALLS: &[&str] = &[
+    "bank.transfer",
+    "bank.burn",
+    "warp.transfer_remote",
+    "vesting.create",
+    "vesting.claim",
+    "vesting.accept",
+    "vesting.withdraw",
+    "interchain_accounts.call_remote",
+    "ping_pong.ping",
+];
+
+/// A call a session key can sign.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopedCall {
+    /// The name grants list the call with, one of [`SESSION_KEY_CALLS`].
+    pub name: &'static str,
+    /// The tokens the call moves out of the sender's account.
+    pub spend: Vec<Coins>,
+}
+
+/// Runtime specific knowledge required to enforce session key limits.
+pub trait SessionKeyScope: DispatchCall {
+    /// Returns the name of `call` and the tokens it moves, or `None` if session keys can't sign
+    /// it: calls that manage session keys, and calls whose spending the runtime can't bound.
+    fn scope(
+        call: &Self::Decodable,
+        state: &mut impl StateReader<User>,
+    ) -> anyhow::Result<Option<ScopedCall>>;
+}
+
+/// Checks that every entry of a grant's `allowed_calls` is one of [`SESSION_KEY_CALLS`].
+pub fn validate_allowed_calls(allowed_calls: &[String]) -> anyhow::Result<()> {
+    for allowed in allowed_calls {
+        anyhow::ensure!(
+            SESSION_KEY_CALLS.contains(&allowed.as_str()),
+            "Session keys can't sign `{allowed}`, expected one of {SESSION_KEY_CALLS:?}"
+        );
+    }
+    Ok(())
+}
+
+/// Returns the tokens `call` moves, or an error if session keys can't sign it.
+pub fn scope<Rt: SessionKeyScope>(
+    call: &Rt::Decodable,
+    state: &mut impl StateReader<User>,
+) -> anyhow::Result<ScopedCall> {
+    Rt::scope(call, state)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Session keys can only sign the calls {SESSION_KEY_CALLS:?} whose spending the runtime can bound"
+        )
+    })
+}
+
+/// The gas tokens of a transaction fee.
+pub fn fee(max_fee: Amount) -> Coins {
+    Coins {
+        amount: max_fee,
+        token_id: config_value!("GAS_TOKEN_ID"),
+    }
+}
+
+/// Returns the credential ID the transactions of `session_key` are executed under on behalf of
+/// `owner`. It is derived from both behind a domain separator, so no signature of the session key
+/// itself resolves to the owner, and a key granted again by another account gets a new ID.
+pub fn delegated_credential_id<S: Spec>(
+    session_key: &CredentialId,
+    owner: &S::Address,
+) -> CredentialId {
+    let owner = borsh::to_vec(owner).expect("Addresses are always serializable");
+    let digest = <S::CryptoSpec as CryptoSpec>::Hasher::digest(
+        [
+            CREDENTIAL_DOMAIN,
+            session_key.0.as_slice(),
+            owner.as_slice(),
+        ]
+        .concat(),
+    );
+    CredentialId(digest.into())
+}
+
+/// Checks that the session key of `grant` may sign `call` with `max_fee` at `height`, and
+/// returns the account the call is executed as.
+pub fn authorize<S, Rt>(
+    grant: &SessionKeyGrant<S>,
+    session_key: &CredentialId,
+    call: &Rt::Decodable,
+    max_fee: Amount,
+    height: u64,
+    state: &mut impl StateReader<User>,
+) -> anyhow::Result<S::Address>
+where
+    S: Spec,
+    Rt: DispatchCall<Spec = S> + SessionKeyScope,
+{
+    let ScopedCall { name, mut spend } = scope::<Rt>(call, state)?;
+    spend.push(fee(max_fee));
+    grant.authorize(session_key, name, &spend, height)?;
+    Ok(grant.owner.clone())
+}
+
+/// Authenticates a transaction signed by a session key.
+pub fn authenticate<Accessor, S, Rt>(
+    raw_tx: &[u8],
+    state: &mut Accessor,
+) -> Result<AuthenticationOutput<S, Rt::Decodable>, AuthenticationError>
+where
+    Accessor: ProvableStateReader<User, Spec = S> + GetGasPrice<Spec = S>,
+    S: Spec,
+    Rt: Runtime<S> + DispatchCall<Spec = S> + SessionKeyScope,
+{
+    let (tx_and_raw_hash, mut auth_data, runtime_call) =
+        capabilities::authenticate::<_, S, Rt>(raw_tx, &Rt::CHAIN_HASH, state)?;
+
+    let session_key = auth_data.credential_id;
+    let max_fee = tx_and_raw_hash.authenticated_tx.0.max_fee;
+    let session_keys = SessionKeys::<S>::default();
+    let owner = session_keys
+        .grant(&session_key, state)
+        .and_then(|grant| {
+            let height = session_keys.chain_state.rollup_height(state)?.get();
+            authorize::<S, Rt>(&grant, &session_key, &runtime_call, max_fee, height, state)
+        })
+        .map_err(|e| {
+            AuthenticationError::FatalError(
+                FatalError::Other(e.to_string()),
+                tx_and_raw_hash.raw_tx_hash,
+            )
+        })?;
+
+    // The accounts module may bind a credential it hasn't seen to the address it first resolves
+    // to. The session key's own credential would then stay bound to the owner after the grant
+    // expires, so the transaction runs under a credential only this path can produce.
+    auth_data.credential_id = delegated_credential_id::<S>(&session_key, &owner);
+    auth_data.default_address = owner;
+    auth_data.credentials = Credentials::new(SessionKeyCredential { session_key });
+
+    Ok((tx_and_raw_hash, auth_data, runtime_call))
+}
@@ -0,0 +1,121 @@
+//! Authentication of transactions signed by Solana wallets (Phantom, Solflare, ...).
+//!
+//! Browser wallets for Solana refuse to sign arbitrary bytes that could be mistaken for a Solana
+//! transaction. Instead they sign "off-chain messages": a payload wrapped into an envelope that
+//! starts with the `\xffsolana offchain` signing domain. The transaction layout is identical to the
+//! standard sov transaction, only the signed message differs: the wallet signs the envelope
+//! around the hex encoded hash of the standard signing payload.
+use sov_modules_api::capabilities::{
+    self, AuthenticationError, AuthenticationOutput, Credentials, FatalError,
+};
+use sov_modules_api::digest::Digest;
+use sov_modules_api::transaction::Transaction;
+use sov_modules_api::{
+    CredentialId, CryptoSpec, DispatchCall, GetGasPrice, ProvableStateReader, PublicKey, Signature,
+    Spec,
+};
+use sov_state::User;
+
+/// The signing domain prepended to every Solana off-chain message.
+pub const SIGNING_DOMAIN: &[u8; 16] = b"\xffsolana offchain";
+
+/// The only off-chain message header version supported by Solana wallets today.
+pub const HEADER_VERSION: u8 = 0;
+
+/// Message format `0` is "restricted ASCII", which every wallet renders as plain text.
+pub const MESSAGE_FORMAT_RESTRICTED_ASCII: u8 = 0;
+
+/// Wraps `message` into a version 0 Solana off-chain message envelope.
+///
+/// Layout: `signing domain (16) | version (1) | format (1) | length (2, LE) | message`.
+pub fn envelope(message: &[u8]) -> Result<Vec<u8>, FatalError> {
+    let len = u16::try_from(message.len()).map_err(|_| {
+        FatalError::Other(format!(
+            "Solana off-chain message is too long: {} bytes",
+            message.len()
+        ))
+    })?;
+
+    let mut envelope = Vec::with_capacity(SIGNING_DOMAIN.len() + 4 + message.len());
+    envelope.extend_from_slice(SIGNING_DOMAIN);
+    envelope.push(HEADER_VERSION);
+    envelope.push(MESSAGE_FORMAT_RESTRICTED_ASCII);
+    envelope.extend_from_slice(&len.to_le_bytes());
+    envelope.extend_from_slice(message);
+    Ok(envelope)
+}
+
+/// Builds the exact bytes a Solana wallet is asked to sign for the given transaction.
+///
+/// The message is the lowercase hex encoding of the hash of the standard signing payload
+/// (`borsh(unsigned tx) || chain hash`), which keeps it within the restricted ASCII format.
+pub fn signing_message<Rt, S>(
+    tx: &Transaction<Rt, S>,
+    chain_hash: &[u8; 32],
+) -> Result<Vec<u8>, FatalError>
+where
+    S: Spec,
+    Rt: DispatchCall<Spec = S>,
+{
+    let mut payload = borsh::to_vec(&tx.to_unsigned())
+        .map_err(|e| FatalError::DeserializationFailed(e.to_string()))?;
+    payload.extend_from_slice(chain_hash);
+
+    let digest = <S::CryptoSpec as CryptoSpec>::Hasher::digest(&payload);
+    envelope(hex::encode(digest).as_bytes())
+}
+
+/// Checks that the signature of `tx` was produced by its public key over the Solana off-chain
+/// message of the transaction for `chain_hash`.
+pub fn verify<Rt, S>(tx: &Transaction<Rt, S>, chain_hash: &[u8; 32]) -> Result<(), FatalError>
+where
+    S: Spec,
+    Rt: DispatchCall<Spec = S>,
+{
+    let message = signing_message(tx, chain_hash)?;
+    tx.signature()
+        .verify(tx.pub_key(), &message)
+        .map_err(|e| FatalError::SigVerificationFailed(e.to_string()))
+}
+
+/// Verifies a transaction whose signature was produced over a Solana off-chain message
+/// and returns the same authentication output as the standard authenticator.
+pub fn authenticate<Accessor, S, Rt>(
+    raw_tx: &[u8],
+    chain_hash: &[u8; 32],
+    state: &mut Accessor,
+) -> Result<AuthenticationOutput<S, Rt::Decodable>, AuthenticationError>
+where
+    Accessor: ProvableStateReader<User, Spec = S> + GetGasPrice<Spec = S>,
+    S: Spec,
+    Rt: DispatchCall<Spec = S>,
+{
+    let raw_tx_hash = capabilities::calculate_hash::<S>(raw_tx);
+
+    let tx: Transaction<Rt, S> = borsh::from_slice(raw_tx)
+        .map_err(|e| capabilities::fatal_deserialization_error::<_, S, _>(raw_tx, e, state))?;
+
+    verify(&tx, chain_hash).map_err(|e| AuthenticationError::FatalError(e, raw_tx_hash))?;
+
+    let credential_id: CredentialId = tx
+        .pub_key()
+        .credential_id::<<S::CryptoSpec as CryptoSpec>::Hasher>();
+
+    Ok(super::authentication_output(
+        tx.to_unsigned(),
+        credential_id,
+        Credentials::new(tx.pub_key().clone()),
+        raw_tx_hash,
+    ))
+}
+
+/// Decodes the runtime call of a Solana off-chain signed transaction without verifying it.
+#[cfg(feature = "native")]
+pub fn decode_sov_tx<S, Rt>(raw_tx: &[u8]) -> Result<Rt::Decodable, FatalError>
+where
+    S: Spec,
+    Rt: DispatchCall<Spec = S>,
+{
+    // The transaction layout matches the standard one, only the signed message differs.
+    capabilities::decode_sov_tx::<S, Rt>(raw_tx)
+}
@@ -1,4 +1,5 @@
 //! This is a technical only module to forward all necessary implementations to inner, non-authenticated Runtime
+use session_keys::SessionKeyCredential;
 use sov_capabilities::StandardProvenRollupCapabilities as StandardCapabilities;
 use sov_hyperlane_integration::HyperlaneAddress;
 use sov_kernels::soft_confirmations::SoftConfirmationsKernel;
@@ -14,6 +15,7 @@ use sov_modules_api::{
 use sov_rollup_interface::da::DaSpec;
 use stf_starter_declaration::{GenesisConfig, Runtime as RuntimeInner, RuntimeCall};
 
+use crate::authentication::session_key;
 use crate::Runtime;
 
 impl<S: Spec> Genesis for Runtime<S>
@@ -29,6 +31,10 @@ where
         config: &Self::Config,
         state: &mut impl GenesisState<Self::Spec>,
     ) -> Result<(), ModuleError> {
+        // The forced inclusion module doesn't know the modules of the runtime, so its allow-list
+        // is checked here.
+        crate::forced_inclusion::validate_allowed_calls(&config.forced_inclusion.allowed_calls)
+            .map_err(ModuleError::ModuleError)?;
         self.0.genesis(genesis_rollup_header, config, state)
     }
 }
@@ -50,7 +56,71 @@ where
         state: &mut WorkingSet<Self::Spec, I>,
         context: &Context<Self::Spec>,
     ) -> Result<(), ModuleError> {
-        self.0.dispatch_call(message, state, context)
+        // `TxHooks` only see the transaction details, so the pause switch is checked here, before
+        // the call reaches its module. The calls that modules execute themselves, such as the calls
+        // of governance proposals, are checked in `execute_call`.
+        self.0
+            .ensure_unpaused(&message, state)
+            .map_err(ModuleError::ModuleError)?;
+
+        // The authenticator can't write to state, so the tokens moved by a session key are
+        // charged to its grant here. A reverted call reverts the charge too.
+        if let Some(credential) = context.get_sender_credential::<SessionKeyCredential>() {
+            let scoped =
+                session_key::scope::<Self>(&message, state).map_err(ModuleError::ModuleError)?;
+            self.0
+                .session_keys
+                .record_spend(&credential.session_key, &scoped.spend, state)
+                .map_err(ModuleError::ModuleError)?;
+        }
+
+        // The session keys module doesn't know the calls of the runtime, so the calls a grant
+        // allows are checked here.
+        if let RuntimeCall::SessionKeys(session_keys::CallMessage::Authorize {
+            allowed_calls,
+            ..
+        }) = &message
+        {
+            session_key::validate_allowed_calls(allowed_calls).map_err(ModuleError::ModuleError)?;
+        }
+
+        #[cfg(feature = "native")]
+        let (delivered, events_before) = (
+            crate::message_index::delivered_message(&message),
+            state.events().len(),
+        );
+
+        // Warp calls go through the runtime so that transfers respect the warp route limits, and
+        // mailbox calls so that dispatched messages run the checks and hooks of their sender.
+        let result = match message {
+            RuntimeCall::Warp(msg) => self
+                .0
+                .call_warp(msg, context, state)
+                .map_err(ModuleError::ModuleError),
+            RuntimeCall::Mailbox(msg) => self
+                .0
+                .call_mailbox(msg, context, state)
+                .map_err(ModuleError::ModuleError),
+            message => self.0.dispatch_call(message, state, context),
+        };
+
+        #[cfg(feature = "native")]
+        if result.is_ok() {
+            match self.0.chain_state.rollup_height(state) {
+                Ok(height) => crate::message_index::record::<S>(
+                    &mut self.1,
+                    delivered,
+                    &state.events()[events_before..],
+                    context.sender(),
+                    height.get(),
+                ),
+                Err(e) => tracing::warn!(
+                    error = format!("{e:#}"),
+                    "Failed to read the rollup height, the Hyperlane messages of the call are not indexed"
+                ),
+            }
+        }
+        result
     }
 
     fn module_id(&self, message: &Self::Decodable) -> &ModuleId {
@@ -113,7 +183,9 @@ where
     }
 
     fn end_rollup_block_hook(&mut self, state: &mut StateCheckpoint<Self::Spec>) {
-        self.0.end_rollup_block_hook(state)
+        self.0.end_rollup_block_hook(state);
+        // Proposals execute once their timelock has passed, without waiting for a transaction.
+        stf_starter_declaration::execute_due_proposals(&mut self.0, state);
     }
 }
 
@@ -128,6 +200,8 @@ where
         tx: &AuthenticatedTransactionData<Self::Spec>,
         state: &mut T,
     ) -> anyhow::Result<()> {
+        #[cfg(feature = "native")]
+        self.1.begin();
         self.0.pre_dispatch_tx_hook(tx, state)
     }
 
@@ -137,7 +211,26 @@ where
         ctx: &Context<Self::Spec>,
         state: &mut T,
     ) -> anyhow::Result<()> {
-        self.0.post_dispatch_tx_hook(tx, ctx, state)
+        let mut result = self.0.post_dispatch_tx_hook(tx, ctx, state);
+        // The owner of a session key pays the fees of its transactions, so they are charged to
+        // the grant too. The authenticator checked that `max_fee` fits in the grant.
+        if let (Ok(()), Some(credential)) =
+            (&result, ctx.get_sender_credential::<SessionKeyCredential>())
+        {
+            result = self.0.session_keys.record_spend(
+                &credential.session_key,
+                &[session_key::fee(tx.0.max_fee)],
+                state,
+            );
+        }
+        // This is the last hook of a transaction, so its messages are committed with it. A
+        // failure reverts the transaction, and its messages are dropped.
+        #[cfg(feature = "native")]
+        match result {
+            Ok(()) => self.1.commit(),
+            Err(_) => self.1.discard(),
+        }
+        result
     }
 }
 
@@ -153,7 +246,13 @@ where
         root_hash: &<<Self::Spec as Spec>::Storage as Storage>::Root,
         state: &mut impl sov_modules_api::AccessoryStateReaderAndWriter,
     ) {
-        self.0.finalize_hook(root_hash, state)
+        self.0.finalize_hook(root_hash, state);
+        if let Err(e) = self.0.message_index.flush(&mut self.1, state) {
+            tracing::error!(
+                error = format!("{e:#}"),
+                "Failed to write the Hyperlane message index"
+            );
+        }
     }
 }
 
@@ -0,0 +1,133 @@
+//! REST endpoint that previews the EIP-712 payload of a transaction.
+//!
+//! Front-ends need the exact typed data that `sov_eip712_auth` verifies. Instead of
+//! re-implementing the encoding on top of the schema, they can post the call and the
+//! transaction details and sign the returned digest.
+use std::sync::Arc;
+
+use sov_hyperlane_integration::HyperlaneAddress;
+use sov_modules_api::capabilities::UniquenessData;
+use sov_modules_api::prelude::axum::extract::State;
+use sov_modules_api::prelude::axum::http::StatusCode;
+use sov_modules_api::prelude::axum::routing::post;
+use sov_modules_api::prelude::axum::{self, Json};
+use sov_modules_api::transaction::{TxDetails, UnsignedTransaction};
+use sov_modules_api::Spec;
+use sov_universal_wallet::schema::{RollupRoots, Schema};
+
+use crate::{Runtime, RuntimeCall};
+
+/// The path of the preview endpoint.
+pub const EIP712_PREVIEW_PATH: &str = "/rollup/eip712/preview";
+
+/// The body of a preview request.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(bound = "S: Spec")]
+pub struct Eip712PreviewRequest<S: Spec>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// The runtime call, in the same JSON format accepted by the sequencer.
+    pub call: RuntimeCall<S>,
+    /// Fees, gas limit and chain ID of the transaction.
+    pub details: TxDetails<S>,
+    /// The uniqueness data of the transaction.
+    pub uniqueness: UniquenessData,
+}
+
+/// Everything a wallet needs to produce an EIP-712 signature for the transaction.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Eip712PreviewResponse {
+    /// The typed data, ready to be passed to `eth_signTypedData_v4`.
+    pub typed_data: serde_json::Value,
+    /// The hex encoded EIP-712 domain separator.
+    pub domain_separator: String,
+    /// The hex encoded digest the signature must be produced over.
+    pub digest: String,
+    /// The hex encoded chain hash the digest is bound to.
+    pub chain_hash: String,
+}
+
+/// The schema the preview is computed with, checked against the chain hash of the runtime.
+struct PreviewState {
+    schema: Schema,
+    chain_hash: [u8; 32],
+}
+
+/// Returns an axum router serving [`EIP712_PREVIEW_PATH`].
+///
+/// Fails if `schema` doesn't hash to `chain_hash`: the digests it produces would then be rejected
+/// by `sov_eip712_auth`, which verifies signatures against the schema of the running chain.
+pub fn axum_router<S: Spec>(
+    schema: Schema,
+    chain_hash: [u8; 32],
+) -> anyhow::Result<axum::Router<()>>
+where
+    S::Address: HyperlaneAddress,
+{
+    let schema_chain_hash = schema
+        .chain_hash()
+        .map_err(|e| anyhow::anyhow!("Failed to compute the chain hash of the schema: {e}"))?;
+    anyhow::ensure!(
+        schema_chain_hash == chain_hash,
+        "The schema has chain hash 0x{}, but the runtime has chain hash 0x{}",
+        hex::encode(schema_chain_hash),
+        hex::encode(chain_hash)
+    );
+
+    Ok(axum::Router::new()
+        .route(EIP712_PREVIEW_PATH, post(preview::<S>))
+        .with_state(Arc::new(PreviewState { schema, chain_hash })))
+}
+
+/// Returns the EIP-712 digest `sov_eip712_auth` verifies the signature of `unsigned_tx` over.
+pub fn signing_hash<S: Spec>(
+    schema: &Schema,
+    unsigned_tx: &UnsignedTransaction<Runtime<S>, S>,
+) -> anyhow::Result<[u8; 32]>
+where
+    S::Address: HyperlaneAddress,
+{
+    let unsigned_tx_bytes = borsh::to_vec(unsigned_tx)?;
+    schema
+        .eip712_signing_hash(
+            RollupRoots::UnsignedTransaction as usize,
+            &unsigned_tx_bytes,
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to compute the EIP-712 signing hash: {e}"))
+}
+
+async fn preview<S: Spec>(
+    State(state): State<Arc<PreviewState>>,
+    Json(request): Json<Eip712PreviewRequest<S>>,
+) -> Result<Json<Eip712PreviewResponse>, (StatusCode, String)>
+where
+    S::Address: HyperlaneAddress,
+{
+    let bad_request = |e: &dyn std::fmt::Display| (StatusCode::BAD_REQUEST, e.to_string());
+    let schema = &state.schema;
+
+    let unsigned_tx = UnsignedTransaction::<Runtime<S>, S>::new_with_details(
+        request.call,
+        request.uniqueness,
+        request.details,
+    );
+    let unsigned_tx_bytes = borsh::to_vec(&unsigned_tx).map_err(|e| bad_request(&e))?;
+    let root = RollupRoots::UnsignedTransaction as usize;
+
+    let typed_data_json = schema
+        .eip712_json(root, &unsigned_tx_bytes)
+        .map_err(|e| bad_request(&e))?;
+    let digest = signing_hash(schema, &unsigned_tx).map_err(|e| bad_request(&e))?;
+
+    let typed_data: alloy_dyn_abi::TypedData =
+        serde_json::from_str(&typed_data_json).map_err(|e| bad_request(&e))?;
+    let domain_separator = typed_data.domain().separator();
+
+    Ok(Json(Eip712PreviewResponse {
+        typed_data: serde_json::from_str(&typed_data_json).map_err(|e| bad_request(&e))?,
+        domain_separator: format!("0x{}", hex::encode(domain_separator)),
+        digest: format!("0x{}", hex::encode(digest)),
+        chain_hash: format!("0x{}", hex::encode(state.chain_hash)),
+    }))
+}
@@ -0,0 +1,129 @@
+//! The names the forced inclusion allow-list uses for runtime calls.
+//!
+//! Entries of the allow-list are either a module (`bank`) or a `module.call` pair
+//! (`warp.transfer_remote`), with the snake case names of the JSON representation of a
+//! [`RuntimeCall`]. [`MODULES`] and the module names of [`call_path`] come from the same
+//! exhaustive match on the call variants, so adding a module to the runtime doesn't compile until
+//! it is named here.
+use sov_hyperlane_integration::HyperlaneAddress;
+use sov_modules_api::{DispatchCall, Spec};
+use stf_starter_declaration::RuntimeCall;
+
+/// Runtime specific names of calls, as used by the forced inclusion allow-list.
+pub trait CallPath: DispatchCall {
+    /// Returns the module name of `call`, and its call name if the call can be allowed on its own.
+    fn call_path(call: &Self::Decodable) -> (&'static str, Option<&'static str>);
+}
+
+/// Declares [`MODULES`] and [`module_name`] from the same list, so that the list can't miss a
+/// module: the match of `module_name` is exhaustive.
+macro_rules! modules {
+    ($($(#[$attr:meta])* $variant:ident => $name:literal,)*) => {
+        /// The modules of the runtime.
+        pub const MODULES: &[&str] = &[$($(#[$attr])* $name,)*];
+
+        /// Returns the module name of `call`.
+        pub fn module_name<S: Spec>(call: &RuntimeCall<S>) -> &'static str
+        where
+            S::Address: HyperlaneAddress,
+        {
+            match call {
+                $($(#[$attr])* RuntimeCall::$variant(_) => $name,)*
+            }
+        }
+    };
+}
+
+modules! {
+    Accounts => "accounts",
+    Uniqueness => "uniqueness",
+    Bank => "bank",
+    SequencerRegistry => "sequencer_registry",
+    OperatorIncentives => "operator_incentives",
+    AttesterIncentives => "attester_incentives",
+    ProverIncentives => "prover_incentives",
+    ChainState => "chain_state",
+    BlobStorage => "blob_storage",
+    Paymaster => "paymaster",
+    RevenueShare => "revenue_share",
+    Mailbox => "mailbox",
+    InterchainGasPaymaster => "interchain_gas_paymaster",
+    MerkleTreeHook => "merkle_tree_hook",
+    Warp => "warp",
+    ValueSetter => "value_setter",
+    #[cfg(feature = "acceptance-testing")]
+    StateConsistency => "state_consistency",
+    SolanaRegistration => "solana_registration",
+    Multisig => "multisig",
+    SessionKeys => "session_keys",
+    ForcedInclusion => "forced_inclusion",
+    InterchainAccounts => "interchain_accounts",
+    WarpLimits => "warp_limits",
+    ValidatorAnnounce => "validator_announce",
+    GasOracle => "gas_oracle",
+    DispatchHooks => "dispatch_hooks",
+    RemoteChains => "remote_chains",
+    PingPong => "ping_pong",
+    Governance => "governance",
+    Vesting => "vesting",
+    ChainPause => "chain_pause",
+    MessageIndex => "message_index",
+    #[cfg(feature = "evm")]
+    Evm => "evm",
+    #[cfg(feature = "faucet")]
+    Faucet => "faucet",
+}
+
+/// The calls that can be allowed on their own, without the rest of their module. They are the
+/// calls a user may need to get funds out of the rollup while the sequencer ignores them.
+pub const CALLS: &[(&str, &str)] = &[
+    ("bank", "transfer"),
+    ("bank", "burn"),
+    ("warp", "transfer_remote"),
+    ("warp_limits", "release"),
+    ("interchain_accounts", "call_remote"),
+    ("vesting", "claim"),
+    ("governance", "execute"),
+    ("sequencer_registry", "register"),
+];
+
+/// Returns the module name of `call`, and its call name if it is one of [`CALLS`].
+pub fn call_path<S: Spec>(call: &RuntimeCall<S>) -> (&'static str, Option<&'static str>)
+where
+    S::Address: HyperlaneAddress,
+{
+    use sov_hyperlane_integration::warp::CallMessage as WarpCall;
+
+    let call_name = match call {
+        RuntimeCall::Bank(sov_bank::CallMessage::Transfer { .. }) => Some("transfer"),
+        RuntimeCall::Bank(sov_bank::CallMessage::Burn { .. }) => Some("burn"),
+        RuntimeCall::SequencerRegistry(sov_sequencer_registry::CallMessage::Register {
+            ..
+        }) => Some("register"),
+        RuntimeCall::Warp(WarpCall::TransferRemote { .. }) => Some("transfer_remote"),
+        RuntimeCall::InterchainAccounts(interchain_accounts::CallMessage::CallRemote {
+            ..
+        }) => Some("call_remote"),
+        RuntimeCall::WarpLimits(warp_limits::CallMessage::Release { .. }) => Some("release"),
+        RuntimeCall::Governance(governance::CallMessage::Execute { .. }) => Some("execute"),
+        RuntimeCall::Vesting(vesting::CallMessage::Claim { .. }) => Some("claim"),
+        _ => None,
+    };
+    (module_name(call), call_name)
+}
+
+/// Checks that every entry of a forced inclusion allow-list names a module of the runtime, or
+/// one of [`CALLS`].
+pub fn validate_allowed_calls(allowed_calls: &[String]) -> anyhow::Result<()> {
+    for allowed in allowed_calls {
+        let known = match allowed.split_once('.') {
+            Some((module, call)) => CALLS.contains(&(module, call)),
+            None => MODULES.contains(&allowed.as_str()),
+        };
+        anyhow::ensure!(
+            known,
+            "Unknown forced inclusion entry `{allowed}`, expected one of the modules {MODULES:?} or one of the calls {CALLS:?}"
+        );
+    }
+    Ok(())
+}
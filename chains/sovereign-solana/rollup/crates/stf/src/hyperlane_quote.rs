@@ -0,0 +1,91 @@
+//! REST endpoint quoting the fees of a Hyperlane dispatch.
+//!
+//! A dispatch pays the interchain gas paymaster (IGP) of its relayer, and the protocol fees of
+//! the dispatch hooks of its sender. Wallets need both before signing a `transfer_remote`: the
+//! IGP payment is capped by `gas_payment_limit`, and the protocol fees are charged on top of it.
+use dispatch_hooks::DispatchHooks;
+use sov_hyperlane_integration::{HexHash, HyperlaneAddress, InterchainGasPaymaster};
+use sov_modules_api::prelude::axum::extract::{Query, State};
+use sov_modules_api::prelude::axum::http::StatusCode;
+use sov_modules_api::prelude::axum::routing::get;
+use sov_modules_api::prelude::axum::{self, Json};
+use sov_modules_api::rest::ApiState;
+use sov_modules_api::{Amount, Spec};
+
+/// The path of the quote endpoint.
+pub const QUOTE_PATH: &str = "/hyperlane/quote";
+
+/// The query of a quote.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(bound = "S: Spec")]
+pub struct QuoteQuery<S: Spec> {
+    /// The Hyperlane domain of the destination.
+    pub destination: u32,
+    /// The size of the message body, in bytes. Optional: the IGP and the dispatch hooks don't
+    /// charge by size, so it doesn't change the quote.
+    pub body_size: Option<u32>,
+    /// The destination gas paid for. Defaults to the relayer's default gas for the destination,
+    /// as when the dispatch doesn't set one.
+    pub gas_limit: Option<u128>,
+    /// The relayer paid by the IGP.
+    pub relayer: S::Address,
+    /// The Hyperlane sender of the message, such as a warp route ID, whose dispatch hooks run.
+    /// Without it, no protocol fee is quoted.
+    pub sender: Option<HexHash>,
+}
+
+/// The fees of a dispatch, in gas tokens.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct QuoteResponse {
+    /// The payment to the relayer's IGP. A `gas_payment_limit` below it makes the dispatch fail.
+    pub igp_payment: Amount,
+    /// The protocol fees of the sender's dispatch hooks.
+    pub hook_fee: Amount,
+    /// The sum of both.
+    pub total: Amount,
+}
+
+/// Returns an axum router serving [`QUOTE_PATH`].
+pub fn axum_router<S: Spec>(api_state: ApiState<S>) -> axum::Router<()>
+where
+    S::Address: HyperlaneAddress,
+{
+    axum::Router::new()
+        .route(QUOTE_PATH, get(quote::<S>))
+        .with_state(api_state)
+}
+
+async fn quote<S: Spec>(
+    State(api_state): State<ApiState<S>>,
+    Query(query): Query<QuoteQuery<S>>,
+) -> Result<Json<QuoteResponse>, (StatusCode, String)>
+where
+    S::Address: HyperlaneAddress,
+{
+    let bad_request = |e: anyhow::Error| (StatusCode::BAD_REQUEST, format!("{e:#}"));
+    let mut state = api_state.default_api_state_accessor();
+
+    let igp_payment = InterchainGasPaymaster::<S>::default()
+        .quote_gas_payment(
+            &query.relayer,
+            query.destination,
+            query.gas_limit,
+            &mut state,
+        )
+        .map_err(|e| bad_request(e.into()))?;
+    let hook_fee = match &query.sender {
+        Some(sender) => DispatchHooks::<S>::default()
+            .quote_fee(sender, query.destination, &mut state)
+            .map_err(bad_request)?,
+        None => Amount::ZERO,
+    };
+    let total = igp_payment
+        .checked_add(hook_fee)
+        .ok_or_else(|| bad_request(anyhow::anyhow!("The fees overflow")))?;
+
+    Ok(Json(QuoteResponse {
+        igp_payment,
+        hook_fee,
+        total,
+    }))
+}
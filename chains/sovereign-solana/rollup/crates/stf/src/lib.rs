@@ -2,6 +2,15 @@
 
 pub mod authentication;
 mod delegation;
+#[cfg(feature = "native")]
+pub mod eip712_preview;
+pub mod forced_inclusion;
+#[cfg(feature = "native")]
+pub mod hyperlane_quote;
+#[cfg(feature = "native")]
+pub mod message_index;
+#[cfg(feature = "native")]
+pub mod module_config;
 pub mod runtime;
 
 pub use runtime::*;
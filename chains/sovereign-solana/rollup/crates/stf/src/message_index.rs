@@ -0,0 +1,90 @@
+//! Records the Hyperlane messages of each call in the message journal, and serves the index.
+//!
+//! Messages dispatched by a call are read from the mailbox `Dispatch` events it emitted, including
+//! the events of the calls it runs, so the messages of warp transfers, warp limits releases,
+//! interchain accounts and executed governance proposals are all covered. A delivered message is
+//! read from the mailbox `process` call itself. When the warp limits module queued the transfer of
+//! a delivered message, the `TransferQueued` event of the call marks the message as queued, and
+//! the `TransferReleased` or `TransferCancelled` event of its transfer later settles it. Only the
+//! calls that succeed are recorded, and the runtime commits them with their transaction.
+use sov_hyperlane_integration::mailbox::{
+    CallMessage as MailboxCallMessage, Event as MailboxEvent,
+};
+use sov_hyperlane_integration::HyperlaneAddress;
+use sov_modules_api::prelude::tracing;
+use sov_modules_api::{RuntimeEventProcessor, Spec, TypeErasedEvent};
+use stf_starter_declaration::{Runtime as RuntimeInner, RuntimeCall, RuntimeEvent};
+use warp_limits::{Direction as WarpDirection, Event as WarpLimitsEvent};
+
+pub use ::message_index::MessageJournal;
+
+pub use ::message_index::api::{
+    axum_router, LATEST_CHECKPOINT_PATH, MERKLE_TREE_PATH, MESSAGE_PATH, PROOF_PATH,
+    SENDER_MESSAGES_PATH,
+};
+
+/// Returns the encoded message delivered by `call`, if it is a mailbox `process` call.
+pub(crate) fn delivered_message<S: Spec>(call: &RuntimeCall<S>) -> Option<Vec<u8>>
+where
+    S::Address: HyperlaneAddress,
+{
+    match call {
+        RuntimeCall::Mailbox(MailboxCallMessage::Process { message, .. }) => {
+            Some(message.0.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Records the message delivered by a call of `sender` that succeeded at rollup height `height`,
+/// and the messages it dispatched, given the events it emitted.
+pub(crate) fn record<S: Spec>(
+    journal: &mut MessageJournal,
+    delivered: Option<Vec<u8>>,
+    events: &[TypeErasedEvent],
+    sender: &S::Address,
+    height: u64,
+) where
+    S::Address: HyperlaneAddress,
+{
+    let events: Vec<_> = events
+        .iter()
+        .filter_map(|event| RuntimeInner::<S>::convert_to_runtime_event(event.clone()))
+        .collect();
+
+    if let Some(message) = delivered {
+        // The inbound transfer queued by a `process` call is the one of its message.
+        let queued = events.iter().find_map(|event| match event {
+            RuntimeEvent::WarpLimits(WarpLimitsEvent::TransferQueued {
+                id,
+                direction: WarpDirection::Inbound,
+                ..
+            }) => Some(*id),
+            _ => None,
+        });
+        let indexed = match queued {
+            Some(transfer_id) => journal.queued(&message, height, transfer_id),
+            None => journal.delivered(&message, height),
+        };
+        if !indexed {
+            tracing::warn!("Failed to index a delivered Hyperlane message");
+        }
+    }
+
+    for event in events {
+        match event {
+            RuntimeEvent::Mailbox(MailboxEvent::Dispatch { message, .. }) => {
+                if !journal.dispatched(&message.0, height, sender.to_sender()) {
+                    tracing::warn!("Failed to index a dispatched Hyperlane message");
+                }
+            }
+            RuntimeEvent::WarpLimits(WarpLimitsEvent::TransferReleased { id, .. }) => {
+                journal.released(id, height)
+            }
+            RuntimeEvent::WarpLimits(WarpLimitsEvent::TransferCancelled { id, .. }) => {
+                journal.cancelled(id, height)
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! The module execution config, read from `modules.json` next to the rollup config.
+//!
+//! The node validates the config at startup, and applies it once every monitored route is
+//! registered in the rollup state. It then checks the file for changes every [`RELOAD_INTERVAL`]
+//! until the node shuts down, so operators can change the monitored warp routes without
+//! restarting the node. A config is
+//! always checked against the rollup state before it is applied. If it is invalid, the previous
+//! config stays in effect and the error names the offending field.
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, bail, Context as _};
+use sov_hyperlane_integration::warp::{Warp, WarpExecutionConfig};
+use sov_hyperlane_integration::{HexHash, HyperlaneAddress};
+use sov_modules_api::prelude::tokio::sync::watch;
+use sov_modules_api::prelude::{tokio, tracing};
+use sov_modules_api::rest::ApiState;
+use sov_modules_api::{Spec, StateReader};
+use sov_state::User;
+
+/// How often the config file is checked for changes.
+pub const RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The path of the applied config, set at startup.
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// The shutdown signal of the node, set before its endpoints are created.
+static SHUTDOWN: OnceLock<watch::Receiver<()>> = OnceLock::new();
+
+/// Stops watching the module config when `shutdown_receiver` changes. The runtime creates its
+/// endpoints without the shutdown signal of the node, so the node passes it here first.
+pub fn set_shutdown_receiver(shutdown_receiver: watch::Receiver<()>) {
+    let _ = SHUTDOWN.set(shutdown_receiver);
+}
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleConfig<S: Spec> {
+    hyperlane: WarpExecutionConfig,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<S>,
+}
+
+impl<S: Spec> ModuleConfig<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// Reads and validates the config at `path`. Errors name the offending field, as in
+    /// `hyperlane.monitored_route_ids[1]`.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read(path)
+            .with_context(|| format!("Failed to read the module config {}", path.display()))?;
+        let mut deserializer = serde_json::Deserializer::from_slice(&contents);
+        let config: Self = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+            anyhow!(
+                "Invalid module config {}: `{}`: {}",
+                path.display(),
+                e.path(),
+                e.inner()
+            )
+        })?;
+
+        let route_ids = config.monitored_route_ids();
+        for (index, route_id) in route_ids.iter().enumerate() {
+            if route_ids[..index].contains(route_id) {
+                bail!(
+                    "Invalid module config {}: `hyperlane.monitored_route_ids[{index}]`: {route_id} is listed twice",
+                    path.display()
+                );
+            }
+        }
+        Ok(config)
+    }
+
+    /// The warp routes monitored by the node.
+    pub fn monitored_route_ids(&self) -> &[HexHash] {
+        &self.hyperlane.monitored_route_ids
+    }
+
+    /// Checks that every monitored route is registered in the warp module.
+    pub fn check_routes(&self, state: &mut impl StateReader<User>) -> anyhow::Result<()> {
+        let warp = Warp::<S>::default();
+        for (index, route_id) in self.monitored_route_ids().iter().enumerate() {
+            if warp.warp_routes.get(route_id, state)?.is_none() {
+                bail!("`hyperlane.monitored_route_ids[{index}]`: warp route {route_id} is not registered");
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes the warp module with the config, replacing the one it was initialized with.
+    pub fn apply(&self) -> anyhow::Result<()> {
+        Warp::<S>::init(&self.hyperlane)
+            .map_err(|e| anyhow!("Failed to apply the `hyperlane` module config: {e:?}"))
+    }
+
+    /// The config without monitored routes, in effect until the routes have been checked.
+    fn unchecked(&self) -> Self {
+        let mut config = self.clone();
+        config.hyperlane.monitored_route_ids.clear();
+        config
+    }
+
+    /// Reloads the config the node started with, checks it against the current rollup state and
+    /// applies it.
+    pub fn reload(api_state: &ApiState<S>) -> anyhow::Result<Self> {
+        let path = CONFIG_PATH
+            .get()
+            .context("The module config was not loaded at startup")?;
+        let config = Self::load(path)?;
+        config.check_routes(&mut api_state.default_api_state_accessor())?;
+        config.apply()?;
+        Ok(config)
+    }
+}
+
+impl<S: Spec> sov_modules_api::ModuleExecutionConfig for ModuleConfig<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    type Input = PathBuf;
+
+    fn configure(input: &Self::Input) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let config = Self::load(input)?;
+        tracing::info!(
+            monitored_routes = config.monitored_route_ids().len(),
+            "Initializing hyperlane execution config"
+        );
+        // The routes can only be checked once the rollup state is available, so none is monitored
+        // until `watch` has applied the config.
+        config.unchecked().apply()?;
+        let _ = CONFIG_PATH.set(input.clone());
+        Ok(())
+    }
+}
+
+/// Applies the startup config, then spawns a task that retries every [`RELOAD_INTERVAL`] until
+/// its routes are registered, and reloads it whenever its file changes. The task stops when the
+/// node shuts down. Without a shutdown signal, see [`set_shutdown_receiver`], the config is only
+/// applied once.
+pub fn watch<S: Spec>(api_state: ApiState<S>) -> Option<tokio::task::JoinHandle<()>>
+where
+    S::Address: HyperlaneAddress,
+{
+    let path = CONFIG_PATH.get()?;
+    let modified = |path: &Path| -> Option<SystemTime> { path.metadata().ok()?.modified().ok() };
+    let mut last_modified = modified(path);
+    let mut applied = reload(&api_state, false);
+
+    let Some(mut shutdown_receiver) = SHUTDOWN.get().cloned() else {
+        tracing::warn!("No shutdown signal, the module config is not reloaded when it changes");
+        return None;
+    };
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+        // The first tick completes immediately, right after the config was loaded.
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_receiver.changed() => return,
+            }
+            let current = modified(path);
+            if applied && current == last_modified {
+                continue;
+            }
+            last_modified = current;
+            if reload(&api_state, applied) {
+                applied = true;
+            }
+        }
+    }))
+}
+
+/// Reloads the module config and logs the outcome. Returns true if the config was applied.
+fn reload<S: Spec>(api_state: &ApiState<S>, applied: bool) -> bool
+where
+    S::Address: HyperlaneAddress,
+{
+    match ModuleConfig::<S>::reload(api_state) {
+        Ok(config) => {
+            tracing::info!(
+                monitored_routes = config.monitored_route_ids().len(),
+                "Applied the module config"
+            );
+            true
+        }
+        Err(e) if applied => {
+            tracing::error!(
+                error = format!("{e:#}"),
+                "Failed to reload the module config, keeping the previous one"
+            );
+            false
+        }
+        Err(e) => {
+            tracing::warn!(
+                error = format!("{e:#}"),
+                "The module config can't be applied yet, no warp route is monitored"
+            );
+            false
+        }
+    }
+}
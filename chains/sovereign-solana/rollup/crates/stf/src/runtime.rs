@@ -1,7 +1,6 @@
 #![allow(unused_doc_comments)]
 //! This module implements `Runtime` trait and ensures that it uses correct `CHAIN_HASH`
 use sov_eip712_auth::{SchemaProvider, Secp256k1CryptoSpec};
-use sov_hyperlane_integration::warp::WarpExecutionConfig;
 use sov_hyperlane_integration::HyperlaneAddress;
 use sov_modules_api::capabilities::TransactionAuthenticator;
 #[cfg(feature = "native")]
@@ -18,8 +17,14 @@ pub use stf_starter_declaration::RuntimeCall;
 #[cfg(feature = "native")]
 pub use stf_starter_declaration::RuntimeSubcommand;
 
+use crate::authentication::session_key::ScopedCall;
+use crate::authentication::EthereumCall;
 use crate::authentication::EvmAndEip712Authenticator;
 use crate::authentication::EvmAndEip712AuthenticatorInput;
+use crate::authentication::SessionKeyScope;
+use crate::forced_inclusion::CallPath;
+#[cfg(feature = "native")]
+pub use crate::module_config::ModuleConfig;
 
 // CHAIN_HASH and Schema are generated during build time.
 // This allows make sure that Runtime can be authenticated in ZKVM guest.
@@ -27,8 +32,12 @@ mod __generated {
     include!("../.artifacts/autogenerated.rs");
 }
 
+/// The runtime, and the journal of the Hyperlane messages of the transactions it executes.
 #[derive(Clone, Default)]
-pub struct Runtime<S: Spec>(pub(crate) RuntimeInner<S>)
+pub struct Runtime<S: Spec>(
+    pub(crate) RuntimeInner<S>,
+    #[cfg(feature = "native")] pub(crate) crate::message_index::MessageJournal,
+)
 where
     <S as Spec>::Address: HyperlaneAddress;
 
@@ -39,26 +48,6 @@ where
     const SCHEMA_BORSH: &'static [u8] = __generated::SCHEMA_BORSH;
 }
 
-#[derive(Clone, serde::Deserialize)]
-pub struct ModuleConfig<S: Spec> {
-    hyperlane: WarpExecutionConfig,
-    #[serde(skip)]
-    _marker: std::marker::PhantomData<S>,
-}
-
-impl<S: Spec> sov_modules_api::ModuleExecutionConfig for ModuleConfig<S> {
-    type Input = std::path::PathBuf;
-
-    fn configure(input: &Self::Input) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let contents = std::fs::read(input)?;
-        let config: Self = serde_json::from_slice(&contents)?;
-        tracing::info!("Initializing hyperlane execution config");
-        sov_hyperlane_integration::warp::Warp::<S>::init(&config.hyperlane)
-            .expect("Failed to init Hyperlane Warp module");
-        Ok(())
-    }
-}
-
 impl<S: Spec> sov_modules_stf_blueprint::Runtime<S> for Runtime<S>
 where
     S::Da: DaSpec,
@@ -91,25 +80,62 @@ where
         let axum_router = axum_router.merge(dedup_endpoint.axum_router());
         let axum_router = axum_router.merge(sov_rollup_apis::endpoints::constants::axum_router());
 
-        let schema_endpoint = StandardSchemaEndpoint::new(
-            &serde_json::from_str(__generated::SCHEMA_JSON)
-                .expect("Failed to deserialize schema json"),
-            Self::CHAIN_HASH.into(),
-        )
-        .expect("Failed to initialize StandardSchemaEndpoint");
+        let schema: sov_universal_wallet::schema::Schema =
+            serde_json::from_str(__generated::SCHEMA_JSON)
+                .expect("Failed to deserialize schema json");
+        let schema_endpoint = StandardSchemaEndpoint::new(&schema, Self::CHAIN_HASH.into())
+            .expect("Failed to initialize StandardSchemaEndpoint");
         let axum_router = axum_router.merge(schema_endpoint.axum_router());
 
+        // Provide an endpoint returning the exact EIP-712 typed data and digest for a transaction,
+        // so front-ends don't have to re-implement the encoding on top of the schema.
+        let axum_router = axum_router.merge(
+            crate::eip712_preview::axum_router::<S>(schema, Self::CHAIN_HASH)
+                .expect("Failed to initialize the EIP-712 preview endpoint"),
+        );
+
+        // Serve the locked and claimable amounts of vesting grants, which the module REST API
+        // doesn't evaluate.
+        let axum_router = axum_router.merge(vesting::api::axum_router::<S>(api_state.clone()));
+        // Serve the Hyperlane messages indexed by the node.
+        let axum_router =
+            axum_router.merge(crate::message_index::axum_router::<S>(api_state.clone()));
+        // Quote the IGP payment and protocol fees of a dispatch before it is signed.
+        let axum_router =
+            axum_router.merge(crate::hyperlane_quote::axum_router::<S>(api_state.clone()));
+        // Apply `modules.json` once its routes are registered, and reload it when it changes.
+        let module_config_watcher = crate::module_config::watch::<S>(api_state.clone());
+
+        #[allow(unused_mut)]
+        let mut jsonrpsee_module = stf_starter_declaration::get_rpc_methods::<S>(api_state.clone());
+        // The EVM module only serves state queries. Transaction submission, gas estimation and
+        // the rest of the `eth_*` namespace expected by Ethereum wallets come from `sov-ethereum`.
+        #[cfg(feature = "evm")]
+        jsonrpsee_module
+            .merge(sov_ethereum::get_ethereum_rpc::<S, Self>(api_state))
+            .expect("Ethereum RPC methods must not collide with the runtime RPC methods");
+
         sov_modules_api::NodeEndpoints {
             axum_router,
-            jsonrpsee_module: stf_starter_declaration::get_rpc_methods::<S>(api_state),
-            background_handles: Vec::new(),
+            jsonrpsee_module,
+            background_handles: module_config_watcher.into_iter().collect(),
         }
     }
 
     #[cfg(feature = "native")]
     fn genesis_config(input: &Self::GenesisInput) -> Result<Self::GenesisConfig, anyhow::Error> {
         let contents = std::fs::read(input)?;
-        Ok(serde_json::from_slice(&contents)?)
+        #[allow(unused_mut)]
+        let mut genesis: serde_json::Value = serde_json::from_slice(&contents)?;
+        // The shared genesis files configure every module, including the ones behind features
+        // that are disabled in this build.
+        if let Some(modules) = genesis.as_object_mut() {
+            #[cfg(not(feature = "evm"))]
+            modules.remove("evm");
+            #[cfg(not(feature = "faucet"))]
+            modules.remove("faucet");
+        }
+        Ok(serde_json::from_value(genesis)?)
     }
 
     fn operating_mode(genesis: &Self::GenesisConfig) -> OperatingMode {
@@ -122,9 +148,15 @@ where
         match auth_data {
             EvmAndEip712AuthenticatorInput::Eip712(call) => call,
             EvmAndEip712AuthenticatorInput::Standard(call) => call,
+            EvmAndEip712AuthenticatorInput::SolanaOffchain(call) => call,
+            EvmAndEip712AuthenticatorInput::Multisig(call) => call,
+            EvmAndEip712AuthenticatorInput::SessionKey(call) => call,
+            EvmAndEip712AuthenticatorInput::Evm(call) => call,
         }
     }
 
+    /// Sequencer registration is always accepted from unregistered senders. Additional calls are
+    /// configured through the `forced_inclusion` section of the genesis config.
     fn allow_unregistered_tx(call: &Self::Decodable) -> bool {
         matches!(
             call,
@@ -134,3 +166,110 @@ where
         )
     }
 }
+
+impl<S: Spec> SessionKeyScope for Runtime<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn scope(
+        call: &Self::Decodable,
+        state: &mut impl sov_modules_api::StateReader<sov_state::User>,
+    ) -> anyhow::Result<Option<ScopedCall>> {
+        use sov_bank::Coins;
+        use sov_hyperlane_integration::warp::{CallMessage as WarpCall, TokenKind, Warp};
+        use sov_modules_api::macros::config_value;
+
+        let scoped = |name, spend| Ok(Some(ScopedCall { name, spend }));
+        let gas = |amount| Coins {
+            amount,
+            token_id: config_value!("GAS_TOKEN_ID"),
+        };
+
+        match call {
+            RuntimeCall::Bank(sov_bank::CallMessage::Transfer { coins, .. }) => {
+                scoped("bank.transfer", vec![coins.clone()])
+            }
+            RuntimeCall::Bank(sov_bank::CallMessage::Burn { coins }) => {
+                scoped("bank.burn", vec![coins.clone()])
+            }
+            RuntimeCall::Warp(WarpCall::TransferRemote {
+                warp_route,
+                amount,
+                gas_payment_limit,
+                ..
+            }) => {
+                let Some(route) = Warp::<S>::default().warp_routes.get(warp_route, state)? else {
+                    anyhow::bail!("Warp route {warp_route} is not registered");
+                };
+                let token_id = match route.token_source {
+                    TokenKind::Native => config_value!("GAS_TOKEN_ID"),
+                    TokenKind::Synthetic { local_token_id, .. } => local_token_id,
+                    TokenKind::Collateral { token } => token,
+                };
+                scoped(
+                    "warp.transfer_remote",
+                    vec![
+                        Coins {
+                            amount: *amount,
+                            token_id,
+                        },
+                        gas(*gas_payment_limit),
+                    ],
+                )
+            }
+            RuntimeCall::Vesting(vesting::CallMessage::Create { grant }) => scoped(
+                "vesting.create",
+                vec![Coins {
+                    amount: grant.total,
+                    token_id: grant.token_id,
+                }],
+            ),
+            RuntimeCall::Vesting(vesting::CallMessage::Claim { .. }) => {
+                scoped("vesting.claim", Vec::new())
+            }
+            RuntimeCall::Vesting(vesting::CallMessage::Accept { .. }) => {
+                scoped("vesting.accept", Vec::new())
+            }
+            RuntimeCall::Vesting(vesting::CallMessage::Withdraw { .. }) => {
+                scoped("vesting.withdraw", Vec::new())
+            }
+            RuntimeCall::InterchainAccounts(interchain_accounts::CallMessage::CallRemote {
+                gas_payment_limit,
+                ..
+            }) => scoped(
+                "interchain_accounts.call_remote",
+                vec![gas(*gas_payment_limit)],
+            ),
+            RuntimeCall::PingPong(ping_pong::CallMessage::Ping {
+                gas_payment_limit, ..
+            }) => scoped("ping_pong.ping", vec![gas(*gas_payment_limit)]),
+            // Session keys can't manage session keys, and the other calls either can't be bounded
+            // (stakes, bonds, gas payments, EVM transfers) or administer modules.
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<S: Spec> CallPath for Runtime<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn call_path(call: &Self::Decodable) -> (&'static str, Option<&'static str>) {
+        crate::forced_inclusion::call_path(call)
+    }
+}
+
+impl<S: Spec> EthereumCall for Runtime<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    #[cfg(feature = "evm")]
+    fn evm_call(call: sov_evm::CallMessage) -> Option<Self::Decodable> {
+        Some(RuntimeCall::Evm(call))
+    }
+
+    #[cfg(not(feature = "evm"))]
+    fn evm_call(_call: sov_evm::CallMessage) -> Option<Self::Decodable> {
+        None
+    }
+}
@@ -0,0 +1,77 @@
+//! Outbound Hyperlane messages, sent by the runtime's own applications or dispatched directly
+//! through the mailbox.
+use dispatch_hooks::DispatchHooks;
+use remote_chains::RemoteChains;
+use sov_hyperlane_integration::mailbox::CallMessage as MailboxCallMessage;
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress};
+use sov_modules_api::{Amount, Context, Module, Spec, TxState};
+
+use crate::{Mailbox, Runtime};
+
+/// Dispatches a message from `sender` through the mailbox, paid by `context.sender()`. The
+/// recipient must be valid on the destination, see [`RemoteChains`], and the dispatch hooks of
+/// `sender` run once the message is dispatched. Returns the message ID.
+pub(crate) fn dispatch_message<S: Spec>(
+    destination: u32,
+    sender: HexHash,
+    recipient: HexHash,
+    body: HexString,
+    gas_payment_limit: Amount,
+    context: &Context<S>,
+    state: &mut impl TxState<S>,
+) -> anyhow::Result<HexHash>
+where
+    S::Address: HyperlaneAddress,
+{
+    RemoteChains::<S>::default().check_address(destination, &recipient, state)?;
+    let message_id = Mailbox::<S>::default().dispatch(
+        destination,
+        sender.clone(),
+        recipient,
+        body,
+        None,
+        None,
+        gas_payment_limit,
+        context,
+        state,
+    )?;
+    DispatchHooks::<S>::default().post_dispatch(&sender, destination, context.sender(), state)?;
+    Ok(message_id)
+}
+
+impl<S: Spec> Runtime<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// Executes a mailbox call. Messages dispatched directly by an account go through the same
+    /// checks as the ones of the runtime's applications: the recipient must be valid on the
+    /// destination, and the dispatch hooks of the account run once the message is dispatched.
+    pub fn call_mailbox(
+        &mut self,
+        msg: <Mailbox<S> as Module>::CallMessage,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        let destination = match &msg {
+            MailboxCallMessage::Dispatch {
+                domain, recipient, ..
+            } => {
+                self.remote_chains
+                    .check_address(*domain, recipient, state)?;
+                Some(*domain)
+            }
+            _ => None,
+        };
+        self.mailbox.call(msg, context, state)?;
+
+        if let Some(destination) = destination {
+            self.dispatch_hooks.post_dispatch(
+                &context.sender().to_sender(),
+                destination,
+                context.sender(),
+                state,
+            )?;
+        }
+        Ok(())
+    }
+}
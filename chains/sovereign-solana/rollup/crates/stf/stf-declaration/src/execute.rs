@@ -0,0 +1,141 @@
+//! Runtime calls executed by the runtime's own applications, such as interchain accounts and
+//! governance proposals.
+use sov_hyperlane_integration::HyperlaneAddress;
+use sov_modules_api::{Context, Module, NestedEnumUtils, Spec, TxState};
+
+use crate::{Runtime, RuntimeCall};
+
+impl<S: Spec> Runtime<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// Fails if the chain is paused, unless `call` is a governance or chain pause call, which
+    /// still go through so the chain can be fixed and unpaused.
+    pub fn ensure_unpaused(
+        &self,
+        call: &RuntimeCall<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        let exempt = matches!(
+            call,
+            RuntimeCall::Governance(_) | RuntimeCall::ChainPause(_)
+        );
+        if !exempt && self.chain_pause.is_paused(state)? {
+            anyhow::bail!(
+                "The chain is paused, only governance and chain pause calls are accepted"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Executes `call` from `context.sender()`.
+///
+/// The chain pause applies to these calls as it does to transactions, so a paused chain doesn't
+/// run the calls of governance proposals or interchain accounts either, except the ones exempt
+/// from the pause.
+///
+/// Runtime dispatch needs a working set, which isn't available while a module is handling a
+/// message, so calls are routed to the modules directly. Kernel and incentive modules are not
+/// meant to be driven by applications and are left out.
+pub(crate) fn execute_call<S: Spec>(
+    call: RuntimeCall<S>,
+    context: &Context<S>,
+    state: &mut impl TxState<S>,
+) -> anyhow::Result<()>
+where
+    S::Address: HyperlaneAddress,
+{
+    let mut runtime = Runtime::<S>::default();
+    runtime.ensure_unpaused(&call, state)?;
+
+    match call {
+        RuntimeCall::Accounts(msg) => runtime
+            .accounts
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::Bank(msg) => runtime.bank.call(msg, context, state).map_err(Into::into),
+        RuntimeCall::SequencerRegistry(msg) => runtime
+            .sequencer_registry
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::Paymaster(msg) => runtime
+            .paymaster
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::Mailbox(msg) => runtime.call_mailbox(msg, context, state),
+        RuntimeCall::InterchainGasPaymaster(msg) => runtime
+            .interchain_gas_paymaster
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::Warp(msg) => runtime.call_warp(msg, context, state),
+        RuntimeCall::ValueSetter(msg) => runtime
+            .value_setter
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::SolanaRegistration(msg) => runtime
+            .solana_registration
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::Multisig(msg) => runtime
+            .multisig
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::SessionKeys(msg) => runtime
+            .session_keys
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::ForcedInclusion(msg) => runtime
+            .forced_inclusion
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::InterchainAccounts(msg) => runtime
+            .interchain_accounts
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::WarpLimits(msg) => runtime
+            .warp_limits
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::GasOracle(msg) => runtime
+            .gas_oracle
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::DispatchHooks(msg) => runtime
+            .dispatch_hooks
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::RemoteChains(msg) => runtime
+            .remote_chains
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::PingPong(msg) => runtime
+            .ping_pong
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::Governance(msg) => runtime
+            .governance
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::ChainPause(msg) => runtime
+            .chain_pause
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::ValidatorAnnounce(msg) => runtime
+            .validator_announce
+            .call(msg, context, state)
+            .map_err(Into::into),
+        RuntimeCall::Vesting(msg) => runtime
+            .vesting
+            .call(msg, context, state)
+            .map_err(Into::into),
+        #[cfg(feature = "evm")]
+        RuntimeCall::Evm(msg) => runtime.evm.call(msg, context, state).map_err(Into::into),
+        #[cfg(feature = "faucet")]
+        RuntimeCall::Faucet(msg) => runtime.faucet.call(msg, context, state).map_err(Into::into),
+        other => anyhow::bail!(
+            "{:?} calls can only be sent in transactions",
+            other.discriminant()
+        ),
+    }
+}
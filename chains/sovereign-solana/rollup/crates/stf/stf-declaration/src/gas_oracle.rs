@@ -0,0 +1,53 @@
+//! Wiring of the gas oracle module into the interchain gas paymaster.
+use gas_oracle::{GasOracleRuntime, GasQuote, OracleFeed};
+use sov_hyperlane_integration::igp::{
+    CallMessage as IgpCallMessage, DomainDefaultGas, DomainOracleData, ExchangeRateAndGasPrice,
+};
+use sov_hyperlane_integration::{HyperlaneAddress, InterchainGasPaymaster};
+use sov_modules_api::{Context, Module, Spec, TxState};
+
+/// The gas oracle module, as wired into this runtime.
+pub type GasOracle<S> = gas_oracle::GasOracle<S, RuntimeGasOracle<S>>;
+
+/// Lets the gas oracle module write relayer configs of the interchain gas paymaster.
+#[derive(Clone, Default)]
+pub struct RuntimeGasOracle<S: Spec>(std::marker::PhantomData<S>);
+
+impl<S: Spec> GasOracleRuntime<S> for RuntimeGasOracle<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn set_relayer_config(
+        feed: &OracleFeed<S>,
+        quotes: &[GasQuote],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        let call = IgpCallMessage::SetRelayerConfig {
+            beneficiary: Some(feed.beneficiary.clone()),
+            default_gas: feed.default_gas.into(),
+            domain_default_gas: feed
+                .domain_default_gas
+                .iter()
+                .map(|gas| DomainDefaultGas {
+                    domain: gas.domain,
+                    default_gas: gas.default_gas.into(),
+                })
+                .collect(),
+            domain_oracle_data: quotes
+                .iter()
+                .map(|quote| DomainOracleData {
+                    domain: quote.data.domain,
+                    data_value: ExchangeRateAndGasPrice {
+                        gas_price: quote.data.gas_price.into(),
+                        token_exchange_rate: quote.data.token_exchange_rate.into(),
+                    },
+                })
+                .collect(),
+        };
+
+        InterchainGasPaymaster::<S>::default()
+            .call(call, context, state)
+            .map_err(Into::into)
+    }
+}
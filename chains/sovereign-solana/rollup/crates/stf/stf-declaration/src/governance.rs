@@ -0,0 +1,135 @@
+//! Wiring of the governance module into the runtime.
+use governance::GovernanceRuntime;
+use sov_hyperlane_integration::warp::CallMessage as WarpCallMessage;
+use sov_hyperlane_integration::HyperlaneAddress;
+use sov_modules_api::capabilities::Credentials;
+use sov_modules_api::prelude::tracing;
+use sov_modules_api::{Context, Spec, StateCheckpoint, TxState};
+
+use crate::execute::execute_call;
+use crate::{Runtime, RuntimeCall};
+
+/// The governance module, as wired into this runtime.
+pub type Governance<S> = governance::Governance<S, RuntimeGovernance<S>>;
+
+/// Lets the governance module execute the runtime calls of proposals.
+#[derive(Clone, Default)]
+pub struct RuntimeGovernance<S: Spec>(std::marker::PhantomData<S>);
+
+impl<S: Spec> GovernanceRuntime<S> for RuntimeGovernance<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn execute(
+        call: &[u8],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        let call: RuntimeCall<S> = borsh::from_slice(call)?;
+        execute_call(call, context, state)
+    }
+
+    fn runs_at_end_of_block(call: &[u8]) -> bool {
+        // The message index records the Hyperlane messages of transactions, so the calls that can
+        // dispatch or deliver one run in an `execute` transaction. Calls that can't be decoded
+        // fail either way.
+        borsh::from_slice::<RuntimeCall<S>>(call)
+            .map(|call| !handles_messages(&call))
+            .unwrap_or(true)
+    }
+}
+
+/// Returns true if `call` can dispatch or deliver a Hyperlane message.
+fn handles_messages<S: Spec>(call: &RuntimeCall<S>) -> bool
+where
+    S::Address: HyperlaneAddress,
+{
+    matches!(
+        call,
+        RuntimeCall::Mailbox(_)
+            | RuntimeCall::Warp(WarpCallMessage::TransferRemote { .. })
+            | RuntimeCall::InterchainAccounts(interchain_accounts::CallMessage::CallRemote { .. })
+            | RuntimeCall::WarpLimits(warp_limits::CallMessage::Release { .. })
+            | RuntimeCall::PingPong(ping_pong::CallMessage::Ping { .. })
+            | RuntimeCall::Governance(governance::CallMessage::Execute { .. })
+    )
+}
+
+/// Executes the governance proposals whose timelock has passed. Called at the end of every rollup
+/// block. The calls run with the preferred sequencer as the sequencer of their context, and don't
+/// charge anyone for gas. Each proposal runs in a scratchpad of `state`, which is reverted if one
+/// of its calls fails, so a proposal is executed as a whole or not at all. Proposals with calls
+/// that can send Hyperlane messages stay queued until they are executed by an `execute`
+/// transaction. While the chain is paused, due proposals stay queued: a proposal that unpauses
+/// the chain is executed with a governance `execute` call.
+pub fn execute_due_proposals<S: Spec>(runtime: &mut Runtime<S>, state: &mut StateCheckpoint<S>)
+where
+    S::Address: HyperlaneAddress,
+{
+    match runtime.governance.queued.get(state) {
+        Ok(Some(queued)) if !queued.is_empty() => {}
+        _ => return,
+    }
+    // The calls would fail on the pause, which would mark the proposals as failed for good.
+    if runtime.chain_pause.is_paused(state).unwrap_or(true) {
+        return;
+    }
+
+    let (sequencer, sequencer_da_address) = match (
+        runtime
+            .sequencer_registry
+            .get_preferred_sequencer_rollup_address(state),
+        runtime.sequencer_registry.get_preferred_sequencer(state),
+    ) {
+        (Ok(Some(address)), Ok(Some(da_address))) => (address, da_address),
+        _ => {
+            tracing::warn!("No preferred sequencer, governance proposals are not executed");
+            return;
+        }
+    };
+
+    let address = Governance::<S>::address();
+    let context = Context::new(
+        address.clone(),
+        Credentials::new(address),
+        sequencer,
+        sequencer_da_address,
+    );
+    let due = match runtime.governance.due_proposals(state) {
+        Ok(due) => due,
+        Err(e) => {
+            tracing::error!(
+                error = format!("{e:#}"),
+                "Failed to read the due governance proposals"
+            );
+            return;
+        }
+    };
+    for id in due {
+        // Scratchpads are made from an owned checkpoint, which the hook only borrows. A panic
+        // aborts the node, as a panicking block hook would stop it anyway.
+        replace_with::replace_with_or_abort(state, |checkpoint| {
+            let mut scratchpad = checkpoint.to_tx_scratchpad();
+            match runtime
+                .governance
+                .execute_due_proposal(id, &context, &mut scratchpad)
+            {
+                Ok(()) => scratchpad.commit(),
+                Err(e) => {
+                    let mut checkpoint = scratchpad.revert();
+                    if let Err(e) = runtime
+                        .governance
+                        .fail(id, format!("{e:#}"), &mut checkpoint)
+                    {
+                        tracing::error!(
+                            error = format!("{e:#}"),
+                            proposal = id,
+                            "Failed to mark a governance proposal as failed"
+                        );
+                    }
+                    checkpoint
+                }
+            }
+        });
+    }
+}
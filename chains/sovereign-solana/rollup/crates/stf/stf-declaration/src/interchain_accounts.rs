@@ -0,0 +1,174 @@
+//! Wiring of the Interchain Accounts module into the runtime and the Hyperlane mailbox.
+use interchain_accounts::IcaRuntime;
+use remote_chains::{AddressFormat, RemoteChains};
+use sov_hyperlane_integration::warp::Warp;
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress, Ism, Recipient};
+use sov_hyperlane_register_module::SolanaRegistration;
+use sov_modules_api::prelude::*;
+use sov_modules_api::{Amount, Context, ModuleId, ModuleInfo, Spec, StateReader, TxState};
+use sov_state::User;
+
+use crate::dispatch::dispatch_message;
+use crate::execute::execute_call;
+use crate::{PingPong, RuntimeCall, WarpLimits};
+
+/// The interchain accounts module, as wired into this runtime.
+pub type InterchainAccounts<S> = interchain_accounts::InterchainAccounts<S, RuntimeIca<S>>;
+
+/// Lets the interchain accounts module execute runtime calls and send Hyperlane messages.
+#[derive(Clone, Default)]
+pub struct RuntimeIca<S: Spec>(std::marker::PhantomData<S>);
+
+impl<S: Spec> IcaRuntime<S> for RuntimeIca<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn execute(
+        call: &[u8],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        let call: RuntimeCall<S> = borsh::from_slice(call)?;
+        // Sequencers bond through the sequencer registry, which remote owners have no use for.
+        if let RuntimeCall::SequencerRegistry(_) = call {
+            anyhow::bail!("SequencerRegistry calls can't be executed from an interchain account");
+        }
+        execute_call(call, context, state)
+    }
+
+    fn dispatch(
+        destination: u32,
+        sender: HexHash,
+        recipient: HexHash,
+        body: HexString,
+        gas_payment_limit: Amount,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<HexHash> {
+        dispatch_message(
+            destination,
+            sender,
+            recipient,
+            body,
+            gas_payment_limit,
+            context,
+            state,
+        )
+    }
+}
+
+/// The Hyperlane recipients of the mailbox. Messages from a registered remote chain must have a
+/// sender valid on that chain, see [`RemoteChains`]. Messages for the interchain accounts router
+/// are handled by [`InterchainAccounts`], messages for the ping-pong app by [`PingPong`], and
+/// everything else by [`deliver_to_route`] once [`WarpLimits`] has admitted them.
+#[derive(Clone, ModuleInfo)]
+pub struct HyperlaneRecipients<S: Spec>
+where
+    S::Address: HyperlaneAddress,
+{
+    #[id]
+    pub id: ModuleId,
+
+    #[module]
+    pub solana_registration: SolanaRegistration<S>,
+
+    #[module]
+    pub interchain_accounts: InterchainAccounts<S>,
+
+    #[module]
+    pub warp_limits: WarpLimits<S>,
+
+    #[module]
+    pub remote_chains: RemoteChains<S>,
+
+    #[module]
+    pub ping_pong: PingPong<S>,
+}
+
+impl<S: Spec> HyperlaneRecipients<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn is_ica(recipient: &HexHash) -> bool {
+        recipient == &InterchainAccounts::<S>::router_address()
+    }
+
+    fn is_ping_pong(recipient: &HexHash) -> bool {
+        recipient == &PingPong::<S>::router_address()
+    }
+}
+
+impl<S: Spec> Recipient<S> for HyperlaneRecipients<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn ism(
+        &self,
+        recipient: &HexHash,
+        state: &mut impl StateReader<User>,
+    ) -> anyhow::Result<Option<Ism>> {
+        if Self::is_ica(recipient) {
+            self.interchain_accounts.ism(state)
+        } else if Self::is_ping_pong(recipient) {
+            self.ping_pong.ism(state)
+        } else {
+            self.solana_registration.ism(recipient, state)
+        }
+    }
+
+    fn handle(
+        &mut self,
+        origin: u32,
+        sender: HexHash,
+        recipient: &HexHash,
+        body: HexString,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        self.remote_chains.check_address(origin, &sender, state)?;
+
+        if Self::is_ica(recipient) {
+            self.interchain_accounts
+                .handle(origin, sender, &body.0, context, state)
+        } else if Self::is_ping_pong(recipient) {
+            self.ping_pong
+                .handle(origin, sender, &body.0, context, state)
+        } else if self
+            .warp_limits
+            .admit_inbound(origin, &sender, recipient, &body, state)?
+        {
+            deliver_to_route(origin, sender, recipient, body, context, state)
+        } else {
+            // Queued by the warp limits module, delivered when the transfer is released.
+            Ok(())
+        }
+    }
+}
+
+/// Delivers a message to the warp route `route`, according to the address format of `origin` in
+/// [`RemoteChains`]. Messages from Sealevel chains, and from domains that are not registered, go
+/// through the Solana registration module, which registers Solana accounts and forwards warp
+/// transfers. Messages from EVM and Cosmos chains go to the warp module directly.
+pub(crate) fn deliver_to_route<S: Spec>(
+    origin: u32,
+    sender: HexHash,
+    route: &HexHash,
+    body: HexString,
+    context: &Context<S>,
+    state: &mut impl TxState<S>,
+) -> anyhow::Result<()>
+where
+    S::Address: HyperlaneAddress,
+{
+    let format = RemoteChains::<S>::default()
+        .chain(origin, state)?
+        .map(|chain| chain.format);
+    match format {
+        None | Some(AddressFormat::Sealevel) => {
+            SolanaRegistration::<S>::default().handle(origin, sender, route, body, context, state)
+        }
+        Some(AddressFormat::Evm | AddressFormat::Cosmos { .. }) => {
+            Warp::<S>::default().handle(origin, sender, route, body, context, state)
+        }
+    }
+}
@@ -5,6 +5,14 @@
 //!   2. Add the module to the `Runtime` below
 //!   3. Update `genesis.json` with any additional data required by your new module
 
+mod dispatch;
+mod execute;
+mod gas_oracle;
+mod governance;
+mod interchain_accounts;
+mod ping_pong;
+mod warp_limits;
+
 use sov_hyperlane_integration::{
     warp::Warp, HyperlaneAddress, InterchainGasPaymaster, Mailbox as RawMailbox, MerkleTreeHook,
 };
@@ -14,7 +22,13 @@ use sov_modules_api::macros::{expose_rpc, CliWallet};
 use sov_modules_api::prelude::*;
 use sov_modules_api::{DispatchCall, Event, Genesis, Hooks, MessageCodec, Spec};
 
-pub type Mailbox<S> = RawMailbox<S, SolanaRegistration<S>>;
+pub use gas_oracle::{GasOracle, RuntimeGasOracle};
+pub use governance::{execute_due_proposals, Governance, RuntimeGovernance};
+pub use interchain_accounts::{HyperlaneRecipients, InterchainAccounts, RuntimeIca};
+pub use ping_pong::{PingPong, RuntimePingPong};
+pub use warp_limits::{RuntimeWarp, WarpLimits};
+
+pub type Mailbox<S> = RawMailbox<S, HyperlaneRecipients<S>>;
 
 /// The runtime defines the logic of the rollup.
 ///
@@ -89,4 +103,41 @@ where
     pub state_consistency: sov_test_state_consistency::StateConsistency<S>,
     /// Hyperlane Solana registration module
     pub solana_registration: SolanaRegistration<S>,
+    /// The multisig module, a registry of k-of-n multisig credentials
+    pub multisig: multisig::Multisig<S>,
+    /// The session keys module, which stores scoped and expiring session key grants
+    pub session_keys: session_keys::SessionKeys<S>,
+    /// The forced inclusion module, which lists the calls unregistered senders may post to the DA layer
+    pub forced_inclusion: forced_inclusion::ForcedInclusion<S>,
+    /// The Hyperlane interchain accounts module, which executes calls from owners on remote chains
+    pub interchain_accounts: InterchainAccounts<S>,
+    /// The warp limits module, which rate limits, queues and pauses warp route transfers
+    pub warp_limits: WarpLimits<S>,
+    /// The hyperlane validator announce module, where validators publish their checkpoint storage locations
+    pub validator_announce: validator_announce::ValidatorAnnounce<S>,
+    /// The gas oracle module, which pushes destination gas prices into relayer paymaster configs
+    pub gas_oracle: GasOracle<S>,
+    /// The dispatch hooks module, which runs per-sender hooks after Hyperlane messages are dispatched
+    pub dispatch_hooks: dispatch_hooks::DispatchHooks<S>,
+    /// The remote chains module, which registers the address format of each remote Hyperlane domain
+    pub remote_chains: remote_chains::RemoteChains<S>,
+    /// The ping-pong example module, which answers Hyperlane pings from remote chains
+    pub ping_pong: PingPong<S>,
+    /// The governance module, which executes the runtime calls approved by a council after a timelock
+    pub governance: Governance<S>,
+    /// The vesting module, which locks tokens and releases them to their beneficiaries under a schedule
+    pub vesting: vesting::Vesting<S>,
+    /// The chain pause module, which lets a guardian reject every non-admin call during an incident
+    pub chain_pause: chain_pause::ChainPause<S>,
+    /// The message index module, a node-local index of the Hyperlane messages sent and received by the rollup
+    pub message_index: message_index::MessageIndex<S>,
+    /// The EVM module, which executes Ethereum transactions and serves the `eth_*` RPC namespace.
+    /// Kept last so that enabling it does not shift the call discriminants of the other modules.
+    #[cfg(feature = "evm")]
+    #[cfg_attr(feature = "native", cli_skip)]
+    pub evm: sov_evm::Evm<S>,
+    /// The faucet module, which dispenses tokens on devnets and testnets.
+    /// Kept after the EVM module for the same reason.
+    #[cfg(feature = "faucet")]
+    pub faucet: faucet::Faucet<S>,
 }
@@ -0,0 +1,38 @@
+//! Wiring of the ping-pong example module into the Hyperlane mailbox.
+use ping_pong::PingPongRuntime;
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress};
+use sov_modules_api::{Amount, Context, Spec, TxState};
+
+use crate::dispatch::dispatch_message;
+
+/// The ping-pong module, as wired into this runtime.
+pub type PingPong<S> = ping_pong::PingPong<S, RuntimePingPong<S>>;
+
+/// Lets the ping-pong module send Hyperlane messages.
+#[derive(Clone, Default)]
+pub struct RuntimePingPong<S: Spec>(std::marker::PhantomData<S>);
+
+impl<S: Spec> PingPongRuntime<S> for RuntimePingPong<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn dispatch(
+        destination: u32,
+        sender: HexHash,
+        recipient: HexHash,
+        body: HexString,
+        gas_payment_limit: Amount,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<HexHash> {
+        dispatch_message(
+            destination,
+            sender,
+            recipient,
+            body,
+            gas_payment_limit,
+            context,
+            state,
+        )
+    }
+}
@@ -0,0 +1,119 @@
+//! Wiring of the Warp limits module into the runtime: outbound transfers are checked before the
+//! warp module sees them, inbound messages before the mailbox delivers them. Outbound transfers
+//! that reach the warp module go through the dispatch hooks of their route.
+use dispatch_hooks::DispatchHooks;
+use sov_hyperlane_integration::warp::{CallMessage as WarpCallMessage, Warp};
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress};
+use sov_modules_api::{Context, Module, Spec, TxState};
+use warp_limits::WarpLimitsRuntime;
+
+use crate::interchain_accounts::deliver_to_route;
+use crate::Runtime;
+
+/// The Warp limits module, as wired into this runtime.
+pub type WarpLimits<S> = warp_limits::WarpLimits<S, RuntimeWarp<S>>;
+
+/// Lets the Warp limits module release queued transfers.
+#[derive(Clone, Default)]
+pub struct RuntimeWarp<S: Spec>(std::marker::PhantomData<S>);
+
+impl<S: Spec> WarpLimitsRuntime<S> for RuntimeWarp<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    fn deliver(
+        origin: u32,
+        sender: HexHash,
+        route: &HexHash,
+        body: HexString,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        deliver_to_route(origin, sender, route, body, context, state)
+    }
+
+    fn transfer(
+        call: &[u8],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        let call: <Warp<S> as Module>::CallMessage = borsh::from_slice(call)?;
+        execute_warp(call, context, state)
+    }
+}
+
+/// Executes a warp call that passed the route limits. Remote transfers then run the dispatch
+/// hooks of their route, so that a failing hook reverts the transfer.
+fn execute_warp<S: Spec>(
+    msg: <Warp<S> as Module>::CallMessage,
+    context: &Context<S>,
+    state: &mut impl TxState<S>,
+) -> anyhow::Result<()>
+where
+    S::Address: HyperlaneAddress,
+{
+    let dispatched = match &msg {
+        WarpCallMessage::TransferRemote {
+            warp_route,
+            destination_domain,
+            ..
+        } => Some((warp_route.clone(), *destination_domain)),
+        _ => None,
+    };
+    Warp::<S>::default().call(msg, context, state)?;
+
+    if let Some((route, destination)) = dispatched {
+        DispatchHooks::<S>::default().post_dispatch(
+            &route,
+            destination,
+            context.sender(),
+            state,
+        )?;
+    }
+    Ok(())
+}
+
+impl<S: Spec> Runtime<S>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// Executes a warp call. Outbound transfers must have a recipient valid on their destination
+    /// chain. On a limited route, they are then checked against its limits, and queued in the
+    /// Warp limits module with their tokens and fees in escrow if they don't fit. Transfers that
+    /// are sent run the dispatch hooks of their route.
+    pub fn call_warp(
+        &mut self,
+        msg: <Warp<S> as Module>::CallMessage,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<()> {
+        if let WarpCallMessage::TransferRemote {
+            warp_route,
+            destination_domain,
+            recipient,
+            amount,
+            gas_payment_limit,
+        } = &msg
+        {
+            self.remote_chains
+                .check_address(*destination_domain, recipient, state)?;
+            let protocol_fee =
+                self.dispatch_hooks
+                    .quote_fee(warp_route, *destination_domain, state)?;
+            let admitted = self.warp_limits.admit_outbound(
+                warp_route,
+                *amount,
+                *gas_payment_limit,
+                protocol_fee,
+                context.sender(),
+                borsh::to_vec(&msg)?,
+                state,
+            )?;
+            if !admitted {
+                return Ok(());
+            }
+        }
+
+        execute_warp(msg, context, state)
+    }
+}
@@ -0,0 +1,358 @@
+//! Property tests for the rollup transaction authenticator.
+//!
+//! The node decodes and hashes transactions outside of the state transition (for the sequencer,
+//! the REST API and the ledger), while the state transition runs `authenticate`. Those paths must
+//! agree with each other on every input: whether the transaction is valid, its hash, and the
+//! runtime call it carries. None of them may panic, whatever the bytes.
+mod common;
+use common::{TestRuntime, TestSpec};
+use std::cell::RefCell;
+
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::SigningKey;
+use proptest::prelude::*;
+use sov_address::EthereumAddress;
+use sov_eip712_auth::SchemaProvider;
+use sov_modules_api::capabilities::{
+    calculate_hash, FatalError, TransactionAuthenticator, UniquenessData,
+};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::{Amount, CredentialId, FullyBakedTx, PrivateKey, RawTx, Spec};
+use sov_modules_stf_blueprint::{GenesisParams, Runtime as _};
+use sov_rollup_interface::zk::CryptoSpec;
+use sov_test_utils::runtime::TestRunner;
+use sov_universal_wallet::schema::Schema;
+use stf_starter::authentication::multisig::MultisigTx;
+use stf_starter::authentication::{solana_offchain, EvmAndEip712AuthenticatorInput};
+use stf_starter::eip712_preview::signing_hash;
+use stf_starter::{Runtime, RuntimeCall};
+
+type Auth = <TestRuntime as sov_modules_stf_blueprint::Runtime<TestSpec>>::Auth;
+type TestCrypto = <TestSpec as Spec>::CryptoSpec;
+type PrivKey = <TestCrypto as CryptoSpec>::PrivateKey;
+
+/// The observable outcome of the authenticator entry points for a single payload.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    /// The outer authenticator envelope could not be parsed.
+    Envelope,
+    /// The envelope is valid but the wrapped transaction could not be decoded.
+    Transaction,
+    /// The transaction decodes, but `authenticate` rejects it, e.g. for a bad signature.
+    Rejected,
+    /// `authenticate` accepts the transaction, with this call and hash.
+    Authenticated(RuntimeCall<TestSpec>, [u8; 32]),
+}
+
+/// A runner at the genesis of the mock rollup, providing the state `authenticate` reads.
+fn runner() -> RefCell<TestRunner<TestRuntime, TestSpec>> {
+    let genesis_path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/mock/genesis.json");
+    let genesis_config =
+        TestRuntime::genesis_config(&genesis_path).expect("The mock genesis config is valid");
+    RefCell::new(TestRunner::new_with_genesis(
+        GenesisParams {
+            runtime: genesis_config,
+        },
+        TestRuntime::default(),
+    ))
+}
+
+fn classify(runner: &RefCell<TestRunner<TestRuntime, TestSpec>>, tx: &FullyBakedTx) -> Outcome {
+    let parsed = EvmAndEip712AuthenticatorInput::parse(tx);
+    let hash = Auth::compute_tx_hash(tx);
+    let decoded = Auth::decode_serialized_tx(tx);
+    let authenticated = runner
+        .borrow_mut()
+        .query_state(|state| Auth::authenticate(tx, state));
+
+    let (input, hash) = match (parsed, hash) {
+        (Err(_), Err(_)) => {
+            assert!(
+                matches!(decoded, Err(FatalError::DeserializationFailed(_))),
+                "an unparsable envelope must fail decoding as a deserialization error, got {decoded:?}"
+            );
+            assert!(
+                authenticated.is_err(),
+                "an unparsable envelope must not authenticate"
+            );
+            return Outcome::Envelope;
+        }
+        (Ok(input), Ok(hash)) => (input, hash),
+        (parsed, hash) => panic!(
+            "parsing and hashing disagree on the envelope: parse ok = {}, hash ok = {}",
+            parsed.is_ok(),
+            hash.is_ok()
+        ),
+    };
+    assert_eq!(
+        hash,
+        calculate_hash::<TestSpec>(&input.raw_tx().data),
+        "the hash must only depend on the wrapped raw transaction"
+    );
+
+    let decoded = match decoded {
+        Ok(call) => TestRuntime::wrap_call(call),
+        Err(_) => {
+            assert!(
+                authenticated.is_err(),
+                "a transaction that doesn't decode must not authenticate"
+            );
+            return Outcome::Transaction;
+        }
+    };
+
+    match authenticated {
+        Ok((tx_and_raw_hash, _, call)) => {
+            assert_eq!(
+                tx_and_raw_hash.raw_tx_hash, hash,
+                "authentication and hashing must agree on the hash"
+            );
+            let call = TestRuntime::wrap_call(call);
+            assert_eq!(
+                call, decoded,
+                "authentication and decoding must agree on the call"
+            );
+            Outcome::Authenticated(call, hash.into())
+        }
+        Err(_) => Outcome::Rejected,
+    }
+}
+
+fn transfer(to: [u8; 20], amount: u128) -> RuntimeCall<TestSpec> {
+    RuntimeCall::Bank(sov_bank::CallMessage::Transfer {
+        to: EthereumAddress::from(to),
+        coins: sov_bank::Coins {
+            amount: Amount::new(amount),
+            token_id: config_value!("GAS_TOKEN_ID"),
+        },
+    })
+}
+
+fn unsigned_tx(
+    call: RuntimeCall<TestSpec>,
+    generation: u64,
+) -> UnsignedTransaction<TestRuntime, TestSpec> {
+    UnsignedTransaction::new(
+        call,
+        config_value!("CHAIN_ID"),
+        PriorityFeeBips::ZERO,
+        Amount::new(100_000_000),
+        UniquenessData::Generation(generation),
+        None,
+    )
+}
+
+fn encode(tx: &impl borsh::BorshSerialize) -> RawTx {
+    RawTx::new(borsh::to_vec(tx).expect("Transaction serialization is infallible"))
+}
+
+fn signed_tx(call: RuntimeCall<TestSpec>, generation: u64) -> RawTx {
+    encode(&Transaction::<TestRuntime, TestSpec>::new_signed_tx(
+        &PrivKey::generate(),
+        &TestRuntime::CHAIN_HASH,
+        unsigned_tx(call, generation),
+    ))
+}
+
+/// A transaction signed over its Solana off-chain message envelope.
+fn solana_offchain_tx(call: RuntimeCall<TestSpec>, generation: u64) -> RawTx {
+    let key = PrivKey::generate();
+    let UnsignedTransaction {
+        runtime_call,
+        uniqueness,
+        details,
+        ..
+    } = unsigned_tx(call, generation);
+    // The signature doesn't take part in the signing message, so a placeholder one is replaced
+    // once the message is known.
+    let placeholder = Transaction::<TestRuntime, TestSpec>::new_with_details_v0(
+        key.pub_key(),
+        runtime_call.clone(),
+        key.sign(&[]),
+        uniqueness.clone(),
+        details.clone(),
+    );
+    let message = solana_offchain::signing_message(&placeholder, &TestRuntime::CHAIN_HASH)
+        .expect("The signing message of a valid transaction can be built");
+    encode(&Transaction::<TestRuntime, TestSpec>::new_with_details_v0(
+        key.pub_key(),
+        runtime_call,
+        key.sign(&message),
+        uniqueness,
+        details,
+    ))
+}
+
+/// A transaction signed over its EIP-712 digest, the way a wallet signs typed data.
+fn eip712_tx(call: RuntimeCall<TestSpec>, generation: u64) -> RawTx {
+    let key = PrivKey::generate();
+    let schema: Schema =
+        borsh::from_slice(TestRuntime::SCHEMA_BORSH).expect("The runtime schema is valid");
+    let unsigned = unsigned_tx(call, generation);
+    let digest = signing_hash(&schema, &unsigned).expect("The transaction matches the schema");
+
+    let key_hex: String =
+        serde_json::from_value(serde_json::to_value(&key).expect("Keys serialize to hex"))
+            .expect("Keys serialize to hex");
+    let signing_key = SigningKey::from_slice(&hex::decode(key_hex).expect("Keys are hex encoded"))
+        .expect("The key is a valid secp256k1 key");
+    let signature: k256::ecdsa::Signature = signing_key
+        .sign_prehash(&digest)
+        .expect("Digests are 32 bytes");
+    let signature =
+        <TestCrypto as CryptoSpec>::Signature::try_from(signature.to_bytes().as_slice())
+            .expect("A secp256k1 signature converts to the rollup signature");
+
+    let UnsignedTransaction {
+        runtime_call,
+        uniqueness,
+        details,
+        ..
+    } = unsigned;
+    encode(&Transaction::<TestRuntime, TestSpec>::new_with_details_v0(
+        key.pub_key(),
+        runtime_call,
+        signature,
+        uniqueness,
+        details,
+    ))
+}
+
+/// A multisig transaction for a multisig that isn't registered.
+fn multisig_tx(call: RuntimeCall<TestSpec>, generation: u64) -> RawTx {
+    encode(&MultisigTx::<TestCrypto> {
+        multisig: CredentialId::from([7; 32]),
+        unsigned_tx: encode(&unsigned_tx(call, generation)),
+        signatures: Vec::new(),
+    })
+}
+
+fn bake(input: &EvmAndEip712AuthenticatorInput) -> FullyBakedTx {
+    FullyBakedTx::new(borsh::to_vec(input).expect("Input serialization is infallible"))
+}
+
+/// A validly signed transaction for every variant that doesn't depend on registered state.
+fn authenticated_variants(
+    call: RuntimeCall<TestSpec>,
+    generation: u64,
+) -> [EvmAndEip712AuthenticatorInput; 3] {
+    [
+        EvmAndEip712AuthenticatorInput::Standard(signed_tx(call.clone(), generation)),
+        EvmAndEip712AuthenticatorInput::SolanaOffchain(solana_offchain_tx(
+            call.clone(),
+            generation,
+        )),
+        EvmAndEip712AuthenticatorInput::Eip712(eip712_tx(call, generation)),
+    ]
+}
+
+/// Transactions that decode, but whose signer isn't registered in state.
+fn unregistered_variants(
+    call: RuntimeCall<TestSpec>,
+    generation: u64,
+) -> [EvmAndEip712AuthenticatorInput; 2] {
+    [
+        EvmAndEip712AuthenticatorInput::Multisig(multisig_tx(call.clone(), generation)),
+        EvmAndEip712AuthenticatorInput::SessionKey(signed_tx(call, generation)),
+    ]
+}
+
+/// An envelope with a valid discriminant, wrapping arbitrary bytes.
+fn arbitrary_envelope() -> impl Strategy<Value = EvmAndEip712AuthenticatorInput> {
+    (0u8..6, prop::collection::vec(any::<u8>(), 0..512)).prop_map(|(variant, bytes)| {
+        let tx = RawTx::new(bytes);
+        match variant {
+            0 => EvmAndEip712AuthenticatorInput::Eip712(tx),
+            1 => EvmAndEip712AuthenticatorInput::Standard(tx),
+            2 => EvmAndEip712AuthenticatorInput::SolanaOffchain(tx),
+            3 => EvmAndEip712AuthenticatorInput::Multisig(tx),
+            4 => EvmAndEip712AuthenticatorInput::SessionKey(tx),
+            _ => EvmAndEip712AuthenticatorInput::Evm(tx),
+        }
+    })
+}
+
+#[test]
+fn arbitrary_bytes_never_panic() {
+    let runner = runner();
+    proptest!(ProptestConfig::with_cases(256), |(bytes in prop::collection::vec(any::<u8>(), 0..1024))| {
+        let _ = classify(&runner, &FullyBakedTx::new(bytes));
+    });
+}
+
+#[test]
+fn well_formed_envelopes_always_parse() {
+    let runner = runner();
+    proptest!(ProptestConfig::with_cases(256), |(input in arbitrary_envelope())| {
+        prop_assert_ne!(classify(&runner, &bake(&input)), Outcome::Envelope);
+    });
+}
+
+#[test]
+fn variants_agree_on_validity_call_and_hash() {
+    let runner = runner();
+    proptest!(ProptestConfig::with_cases(64), |(to in any::<[u8; 20]>(), amount in any::<u128>(), generation in any::<u64>())| {
+        let call = transfer(to, amount);
+
+        for input in authenticated_variants(call.clone(), generation) {
+            let expected_hash: [u8; 32] = calculate_hash::<TestSpec>(&input.raw_tx().data).into();
+            prop_assert_eq!(
+                classify(&runner, &bake(&input)),
+                Outcome::Authenticated(call.clone(), expected_hash)
+            );
+        }
+
+        for input in unregistered_variants(call.clone(), generation) {
+            prop_assert_eq!(classify(&runner, &bake(&input)), Outcome::Rejected);
+        }
+    });
+}
+
+#[test]
+fn signatures_are_not_interchangeable_between_variants() {
+    let runner = runner();
+    let call = transfer([1; 20], 1_000);
+
+    // Each variant verifies its signature over a different message, so re-wrapping a signed
+    // transaction in another variant must not authenticate it.
+    let standard = signed_tx(call.clone(), 0);
+    let solana = solana_offchain_tx(call.clone(), 0);
+    let eip712 = eip712_tx(call, 0);
+    for input in [
+        EvmAndEip712AuthenticatorInput::Eip712(standard.clone()),
+        EvmAndEip712AuthenticatorInput::SolanaOffchain(standard),
+        EvmAndEip712AuthenticatorInput::Standard(solana.clone()),
+        EvmAndEip712AuthenticatorInput::Eip712(solana),
+        EvmAndEip712AuthenticatorInput::Standard(eip712.clone()),
+        EvmAndEip712AuthenticatorInput::SolanaOffchain(eip712),
+    ] {
+        assert_eq!(classify(&runner, &bake(&input)), Outcome::Rejected);
+    }
+}
+
+#[test]
+fn corrupted_transactions_never_panic() {
+    let runner = runner();
+    proptest!(ProptestConfig::with_cases(64), |(
+        to in any::<[u8; 20]>(),
+        amount in any::<u128>(),
+        cut in any::<prop::sample::Index>(),
+        flips in prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 0..8),
+    )| {
+        let call = transfer(to, amount);
+        let mut mutations = authenticated_variants(call.clone(), 0).to_vec();
+        mutations.extend(unregistered_variants(call, 0));
+
+        for input in mutations {
+            let mut bytes = borsh::to_vec(&input).expect("Input serialization is infallible");
+            for (index, value) in &flips {
+                let index = index.index(bytes.len());
+                bytes[index] ^= value;
+            }
+            bytes.truncate(cut.index(bytes.len() + 1));
+            let _ = classify(&runner, &FullyBakedTx::new(bytes));
+        }
+    });
+}
@@ -0,0 +1,232 @@
+//! Runs governance proposals through the full runtime: the chain pause covers the calls they
+//! execute, not only the calls of transactions, and a proposal executed at the end of a block is
+//! reverted as a whole when one of its calls fails.
+mod common;
+use common::{PrivKey, TestRuntime, TestSpec};
+use std::str::FromStr;
+
+use governance::ProposalStatus;
+use sov_address::EthereumAddress;
+use sov_bank::{Bank, Coins};
+use sov_hyperlane_integration::HexString;
+use sov_modules_api::capabilities::config_chain_id;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::TxDetails;
+use sov_modules_api::Amount;
+use sov_modules_stf_blueprint::{GenesisParams, Runtime as _};
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{
+    TransactionTestCase, TransactionType, TEST_DEFAULT_MAX_FEE, TEST_DEFAULT_MAX_PRIORITY_FEE,
+};
+use stf_starter::RuntimeCall;
+use stf_starter_declaration::Governance;
+
+const RECIPIENT: [u8; 20] = [1; 20];
+const AMOUNT: u128 = 1_000;
+
+/// The key of the account acting as the council and the chain pause guardian, and its address.
+fn guardian() -> (PrivKey, EthereumAddress) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test-data/keys/tx_signer_private_key.json");
+    let keys: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let key = serde_json::from_value(keys["private_key"].clone()).unwrap();
+    let address = EthereumAddress::from_str(keys["address"].as_str().unwrap()).unwrap();
+    (key, address)
+}
+
+/// A runner at the mock genesis, with `guardian` as the only council member and the chain pause
+/// guardian, no timelock, and a funded governance account.
+fn runner(guardian: &EthereumAddress) -> TestRunner<TestRuntime, TestSpec> {
+    let mock_genesis =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/mock/genesis.json");
+    let mut genesis: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(mock_genesis).unwrap()).unwrap();
+    let balances = genesis["bank"]["gas_token_config"]["address_and_balances"]
+        .as_array_mut()
+        .unwrap();
+    balances.push(serde_json::json!([
+        guardian.to_string(),
+        "1000000000000000"
+    ]));
+    balances.push(serde_json::json!([
+        Governance::<TestSpec>::address().to_string(),
+        AMOUNT.to_string()
+    ]));
+    genesis["governance"] = serde_json::json!({
+        "council": {
+            "multisig": { "members": [guardian.to_string()], "threshold": 1 }
+        },
+        "timelock": 0
+    });
+    genesis["chain_pause"] = serde_json::json!({
+        "admin": guardian.to_string(),
+        "guardian": guardian.to_string()
+    });
+
+    let path =
+        std::env::temp_dir().join(format!("chain-pause-genesis-{}.json", std::process::id()));
+    std::fs::write(&path, serde_json::to_vec(&genesis).unwrap()).unwrap();
+    let genesis_config = TestRuntime::genesis_config(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    TestRunner::new_with_genesis(
+        GenesisParams {
+            runtime: genesis_config.expect("The modified mock genesis config is valid"),
+        },
+        TestRuntime::default(),
+    )
+}
+
+fn plain(key: &PrivKey, call: RuntimeCall<TestSpec>) -> TransactionType<TestRuntime, TestSpec> {
+    TransactionType::Plain {
+        message: call,
+        key: key.clone(),
+        details: TxDetails {
+            max_priority_fee_bips: TEST_DEFAULT_MAX_PRIORITY_FEE,
+            max_fee: TEST_DEFAULT_MAX_FEE,
+            gas_limit: None,
+            chain_id: config_chain_id(),
+        },
+    }
+}
+
+fn transfer() -> RuntimeCall<TestSpec> {
+    RuntimeCall::Bank(sov_bank::CallMessage::Transfer {
+        to: EthereumAddress::from(RECIPIENT),
+        coins: Coins {
+            amount: Amount::new(AMOUNT),
+            token_id: config_value!("GAS_TOKEN_ID"),
+        },
+    })
+}
+
+fn status(state: &mut impl sov_modules_api::StateReader<sov_state::User>) -> ProposalStatus {
+    Governance::<TestSpec>::default()
+        .proposals
+        .get(&0, state)
+        .unwrap()
+        .unwrap()
+        .status
+}
+
+fn recipient_balance(state: &mut impl sov_modules_api::StateReader<sov_state::User>) -> Amount {
+    Bank::<TestSpec>::default()
+        .get_balance_of(
+            &EthereumAddress::from(RECIPIENT),
+            config_value!("GAS_TOKEN_ID"),
+            state,
+        )
+        .unwrap()
+        .unwrap_or(Amount::ZERO)
+}
+
+#[test]
+fn test_paused_chain_does_not_run_proposal_calls() {
+    let (key, address) = guardian();
+    let mut runner = runner(&address);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(
+            &key,
+            RuntimeCall::ChainPause(chain_pause::CallMessage::Pause),
+        ),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    // Transactions are rejected before reaching their module.
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(&key, transfer()),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    // Governance calls still go through, and the proposal is queued without a timelock.
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(
+            &key,
+            RuntimeCall::Governance(governance::CallMessage::Propose {
+                calls: vec![HexString(borsh::to_vec(&transfer()).unwrap())],
+            }),
+        ),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    // The end of the block left the due proposal queued, and executing it is rejected too.
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(
+            &key,
+            RuntimeCall::Governance(governance::CallMessage::Execute { id: 0 }),
+        ),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            assert!(matches!(status(state), ProposalStatus::Queued { .. }));
+            assert_eq!(recipient_balance(state), Amount::ZERO);
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(
+            &key,
+            RuntimeCall::ChainPause(chain_pause::CallMessage::Unpause),
+        ),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    // Once unpaused, the proposal runs at the end of the block, so it can't be executed again.
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(
+            &key,
+            RuntimeCall::Governance(governance::CallMessage::Execute { id: 0 }),
+        ),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            assert!(matches!(status(state), ProposalStatus::Executed { .. }));
+            assert_eq!(recipient_balance(state), Amount::new(AMOUNT));
+        }),
+    });
+}
+
+#[test]
+fn test_failing_proposal_is_reverted_at_the_end_of_the_block() {
+    let (key, address) = guardian();
+    let mut runner = runner(&address);
+
+    // The governance account holds `AMOUNT`, so the second transfer fails after the first one
+    // moved it all.
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(
+            &key,
+            RuntimeCall::Governance(governance::CallMessage::Propose {
+                calls: vec![
+                    HexString(borsh::to_vec(&transfer()).unwrap()),
+                    HexString(borsh::to_vec(&transfer()).unwrap()),
+                ],
+            }),
+        ),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    runner.query_state(|state| {
+        assert!(matches!(status(state), ProposalStatus::Failed { .. }));
+        assert_eq!(recipient_balance(state), Amount::ZERO);
+        assert_eq!(
+            Bank::<TestSpec>::default()
+                .get_balance_of(
+                    &Governance::<TestSpec>::address(),
+                    config_value!("GAS_TOKEN_ID"),
+                    state,
+                )
+                .unwrap(),
+            Some(Amount::new(AMOUNT))
+        );
+    });
+}
@@ -0,0 +1,29 @@
+//! The spec and runtime the integration tests of the rollup STF run against.
+#![allow(dead_code)]
+use sov_address::{EthereumAddress, EvmCryptoSpec};
+use sov_mock_da::MockDaSpec;
+use sov_mock_zkvm::MockZkvm;
+use sov_modules_api::configurable_spec::ConfigurableSpec;
+use sov_modules_api::execution_mode::Native;
+use sov_modules_api::Spec;
+use sov_rollup_interface::da::DaSpec;
+use sov_rollup_interface::zk::CryptoSpec;
+use sov_state::nomt::prover_storage::NomtProverStorage;
+use sov_state::DefaultStorageSpec;
+use stf_starter::Runtime;
+
+type Hasher = <EvmCryptoSpec as CryptoSpec>::Hasher;
+type NomtStorage = NomtProverStorage<DefaultStorageSpec<Hasher>, <MockDaSpec as DaSpec>::SlotHash>;
+
+/// The native spec of the mock rollup.
+pub type TestSpec = ConfigurableSpec<
+    MockDaSpec,
+    MockZkvm,
+    MockZkvm,
+    EthereumAddress,
+    Native,
+    EvmCryptoSpec,
+    NomtStorage,
+>;
+pub type TestRuntime = Runtime<TestSpec>;
+pub type PrivKey = <<TestSpec as Spec>::CryptoSpec as CryptoSpec>::PrivateKey;
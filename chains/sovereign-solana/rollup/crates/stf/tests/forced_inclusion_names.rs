@@ -0,0 +1,72 @@
+//! Tests for the names the forced inclusion allow-list uses for runtime calls.
+mod common;
+use common::TestSpec;
+use sov_address::EthereumAddress;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::Amount;
+use stf_starter::forced_inclusion::{call_path, validate_allowed_calls, CALLS, MODULES};
+use stf_starter::RuntimeCall;
+
+fn coins(amount: u128) -> sov_bank::Coins {
+    sov_bank::Coins {
+        amount: Amount::new(amount),
+        token_id: config_value!("GAS_TOKEN_ID"),
+    }
+}
+
+fn entries(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_names_calls_by_variant() {
+    let transfer = RuntimeCall::<TestSpec>::Bank(sov_bank::CallMessage::Transfer {
+        to: EthereumAddress::from([1; 20]),
+        coins: coins(1),
+    });
+    assert_eq!(call_path(&transfer), ("bank", Some("transfer")));
+
+    let burn = RuntimeCall::<TestSpec>::Bank(sov_bank::CallMessage::Burn { coins: coins(1) });
+    assert_eq!(call_path(&burn), ("bank", Some("burn")));
+
+    let claim = RuntimeCall::<TestSpec>::Vesting(vesting::CallMessage::Claim { id: 0 });
+    assert_eq!(call_path(&claim), ("vesting", Some("claim")));
+}
+
+#[test]
+fn test_calls_belong_to_modules() {
+    for (module, call) in CALLS {
+        assert!(
+            MODULES.contains(module),
+            "`{module}.{call}` names no module"
+        );
+    }
+
+    let unenroll =
+        RuntimeCall::<TestSpec>::PingPong(ping_pong::CallMessage::UnenrollRemoteRouter {
+            domain: 1,
+        });
+    assert_eq!(call_path(&unenroll), ("ping_pong", None));
+    assert!(MODULES.contains(&call_path(&unenroll).0));
+}
+
+#[test]
+fn test_accepts_genesis_allow_lists() {
+    validate_allowed_calls(&entries(&["bank.transfer", "warp.transfer_remote"])).unwrap();
+    validate_allowed_calls(&entries(&["bank", "warp", "vesting.claim"])).unwrap();
+
+    let every_call: Vec<String> = CALLS
+        .iter()
+        .map(|(module, call)| format!("{module}.{call}"))
+        .collect();
+    validate_allowed_calls(&every_call).unwrap();
+}
+
+#[test]
+fn test_rejects_unknown_names() {
+    assert!(validate_allowed_calls(&entries(&["bnak"])).is_err());
+    assert!(validate_allowed_calls(&entries(&["bank.transfr"])).is_err());
+    // Calls outside of `CALLS` are allowed by naming their module.
+    assert!(validate_allowed_calls(&entries(&["bank.mint"])).is_err());
+    assert!(validate_allowed_calls(&entries(&["bank.transfer", ""])).is_err());
+}
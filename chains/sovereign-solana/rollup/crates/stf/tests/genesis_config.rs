@@ -0,0 +1,16 @@
+//! Tests that the genesis configs shipped with the rollup load in every feature combination.
+mod common;
+use common::TestSpec;
+use sov_modules_stf_blueprint::Runtime as _;
+use stf_starter::Runtime;
+
+#[test]
+fn test_mock_genesis_configs_load() {
+    for config in ["mock", "mock_external"] {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join(format!("../../configs/{config}/genesis.json"));
+        if let Err(e) = Runtime::<TestSpec>::genesis_config(&path) {
+            panic!("Failed to load {}: {e:#}", path.display());
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Dispatches messages directly through the mailbox, so that they are held to the checks of the
+//! messages sent by the runtime's applications.
+mod common;
+use common::{PrivKey, TestRuntime, TestSpec};
+use std::str::FromStr;
+
+use message_index::MessageIndex;
+use sov_address::EthereumAddress;
+use sov_hyperlane_integration::mailbox::CallMessage as MailboxCallMessage;
+use sov_hyperlane_integration::HexString;
+use sov_modules_api::capabilities::config_chain_id;
+use sov_modules_api::transaction::TxDetails;
+use sov_modules_api::Amount;
+use sov_modules_stf_blueprint::{GenesisParams, Runtime as _};
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{
+    TransactionTestCase, TransactionType, TEST_DEFAULT_MAX_FEE, TEST_DEFAULT_MAX_PRIORITY_FEE,
+};
+use stf_starter::RuntimeCall;
+
+/// A chain registered with the EVM address format in the mock genesis.
+const EVM_DOMAIN: u32 = 3133790210;
+
+/// The key of the account dispatching messages, and its address.
+fn sender() -> (PrivKey, EthereumAddress) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test-data/keys/tx_signer_private_key.json");
+    let keys: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let key = serde_json::from_value(keys["private_key"].clone()).unwrap();
+    let address = EthereumAddress::from_str(keys["address"].as_str().unwrap()).unwrap();
+    (key, address)
+}
+
+/// A runner at the mock genesis, with `sender` funded.
+fn runner(sender: &EthereumAddress) -> TestRunner<TestRuntime, TestSpec> {
+    let mock_genesis =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/mock/genesis.json");
+    let mut genesis: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(mock_genesis).unwrap()).unwrap();
+    genesis["bank"]["gas_token_config"]["address_and_balances"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!([sender.to_string(), "1000000000000000"]));
+
+    let path = std::env::temp_dir().join(format!(
+        "mailbox-dispatch-genesis-{}.json",
+        std::process::id()
+    ));
+    std::fs::write(&path, serde_json::to_vec(&genesis).unwrap()).unwrap();
+    let genesis_config = TestRuntime::genesis_config(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    TestRunner::new_with_genesis(
+        GenesisParams {
+            runtime: genesis_config.expect("The modified mock genesis config is valid"),
+        },
+        TestRuntime::default(),
+    )
+}
+
+fn dispatch(key: &PrivKey, recipient: [u8; 32]) -> TransactionType<TestRuntime, TestSpec> {
+    TransactionType::Plain {
+        message: RuntimeCall::Mailbox(MailboxCallMessage::Dispatch {
+            domain: EVM_DOMAIN,
+            recipient: HexString(recipient),
+            body: HexString(vec![1, 2, 3]),
+            metadata: None,
+            relayer: None,
+            gas_payment_limit: Amount::ZERO,
+        }),
+        key: key.clone(),
+        details: TxDetails {
+            max_priority_fee_bips: TEST_DEFAULT_MAX_PRIORITY_FEE,
+            max_fee: TEST_DEFAULT_MAX_FEE,
+            gas_limit: None,
+            chain_id: config_chain_id(),
+        },
+    }
+}
+
+#[test]
+fn test_direct_dispatch_checks_the_recipient() {
+    let (key, address) = sender();
+    let mut runner = runner(&address);
+
+    // EVM addresses are 20 bytes, left padded with zeros.
+    runner.execute_transaction(TransactionTestCase {
+        input: dispatch(&key, [0x42; 32]),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            let (_root, count) = MessageIndex::<TestSpec>::default()
+                .hook_tree(state)
+                .unwrap();
+            assert_eq!(count, 0);
+        }),
+    });
+}
@@ -0,0 +1,118 @@
+//! Dispatches messages through the full runtime, so that the merkle tree mirrored by the message
+//! index is compared with the tree of the `MerkleTreeHook` it mirrors.
+mod common;
+use common::{PrivKey, TestRuntime, TestSpec};
+use std::str::FromStr;
+
+use message_index::MessageIndex;
+use ping_pong::CallMessage as PingPongCallMessage;
+use sov_address::EthereumAddress;
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::capabilities::config_chain_id;
+use sov_modules_api::transaction::TxDetails;
+use sov_modules_api::Amount;
+use sov_modules_stf_blueprint::{GenesisParams, Runtime as _};
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{
+    TransactionTestCase, TransactionType, TEST_DEFAULT_MAX_FEE, TEST_DEFAULT_MAX_PRIORITY_FEE,
+};
+use stf_starter::RuntimeCall;
+
+/// The Solana testnet, registered with the Sealevel address format in the mock genesis.
+const REMOTE_DOMAIN: u32 = 1337;
+const REMOTE_ROUTER: HexHash = HexString([0x42; 32]);
+const GAS_PAYMENT_LIMIT: u128 = 1_000_000;
+const PINGS: u32 = 5;
+
+/// The key of the account sending pings, and its address.
+fn sender() -> (PrivKey, EthereumAddress) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test-data/keys/tx_signer_private_key.json");
+    let keys: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let key = serde_json::from_value(keys["private_key"].clone()).unwrap();
+    let address = EthereumAddress::from_str(keys["address"].as_str().unwrap()).unwrap();
+    (key, address)
+}
+
+/// A runner at the mock genesis, with `sender` funded and the ping-pong app enrolled with
+/// `REMOTE_ROUTER` on `REMOTE_DOMAIN`.
+fn runner(sender: &EthereumAddress) -> TestRunner<TestRuntime, TestSpec> {
+    let mock_genesis =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/mock/genesis.json");
+    let mut genesis: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(mock_genesis).unwrap()).unwrap();
+    genesis["bank"]["gas_token_config"]["address_and_balances"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!([sender.to_string(), "1000000000000000"]));
+    genesis["ping_pong"]["routers"] = serde_json::json!([
+        { "domain": REMOTE_DOMAIN, "router": REMOTE_ROUTER.to_string() }
+    ]);
+
+    let path = std::env::temp_dir().join(format!(
+        "message-index-tree-genesis-{}.json",
+        std::process::id()
+    ));
+    std::fs::write(&path, serde_json::to_vec(&genesis).unwrap()).unwrap();
+    let genesis_config = TestRuntime::genesis_config(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    TestRunner::new_with_genesis(
+        GenesisParams {
+            runtime: genesis_config.expect("The modified mock genesis config is valid"),
+        },
+        TestRuntime::default(),
+    )
+}
+
+fn ping(key: &PrivKey, payload: u8) -> TransactionType<TestRuntime, TestSpec> {
+    TransactionType::Plain {
+        message: RuntimeCall::PingPong(PingPongCallMessage::Ping {
+            destination: REMOTE_DOMAIN,
+            payload: HexString(vec![payload]),
+            gas_payment_limit: Amount::new(GAS_PAYMENT_LIMIT),
+        }),
+        key: key.clone(),
+        details: TxDetails {
+            max_priority_fee_bips: TEST_DEFAULT_MAX_PRIORITY_FEE,
+            max_fee: TEST_DEFAULT_MAX_FEE,
+            gas_limit: None,
+            chain_id: config_chain_id(),
+        },
+    }
+}
+
+#[test]
+fn test_mirror_matches_merkle_tree_hook() {
+    let (key, address) = sender();
+    let mut runner = runner(&address);
+
+    for payload in 0..PINGS {
+        runner.execute_transaction(TransactionTestCase {
+            input: ping(&key, payload as u8),
+            assert: Box::new(move |result, _state| {
+                assert!(result.tx_receipt.is_successful());
+            }),
+        });
+
+        runner.query_state(|state| {
+            let index = MessageIndex::<TestSpec>::default();
+            let (root, count) = index.hook_tree(state).unwrap();
+            assert_eq!(count, payload + 1);
+            assert_eq!(index.tree_count(state).unwrap(), count);
+            assert_eq!(index.tree_root(state).unwrap(), root);
+            assert_eq!(
+                index.mirrored_hook_tree(state).unwrap(),
+                (root.clone(), count)
+            );
+
+            // Every leaf is proven against the root of the hook.
+            for leaf_index in 0..count {
+                let proof = index.tree_proof(leaf_index, state).unwrap().unwrap();
+                assert!(proof.verify(&root));
+            }
+            let checkpoint = index.checkpoint(root, count, state).unwrap().unwrap();
+            assert_eq!(checkpoint.checkpoint.index, payload);
+        });
+    }
+}
@@ -0,0 +1,75 @@
+//! Loading and validation of `modules.json`.
+mod common;
+use common::TestSpec;
+use stf_starter::ModuleConfig;
+
+const ROUTE: &str = "0xdea7428f83523ea95c69f33ed7805976f5e75d07c7c095496f184ba02a5361c3";
+
+fn load(name: &str, contents: &str) -> anyhow::Result<ModuleConfig<TestSpec>> {
+    let path =
+        std::env::temp_dir().join(format!("module-config-{}-{name}.json", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    let config = ModuleConfig::<TestSpec>::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    config
+}
+
+#[test]
+fn test_loads_the_mock_config() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/mock/modules.json");
+    let config = ModuleConfig::<TestSpec>::load(&path).unwrap();
+    assert_eq!(config.monitored_route_ids().len(), 1);
+}
+
+#[test]
+fn test_errors_name_the_offending_field() {
+    let error = load(
+        "invalid-route",
+        &format!(r#"{{"hyperlane": {{"monitored_route_ids": ["{ROUTE}", "0x12"]}}}}"#),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        error.contains("hyperlane.monitored_route_ids[1]"),
+        "{error}"
+    );
+
+    let error = load(
+        "unknown-field",
+        r#"{"hyperlane": {"monitored_route_ids": []}, "warp": {}}"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("warp"), "{error}");
+}
+
+#[test]
+fn test_rejects_duplicate_routes() {
+    let error = load(
+        "duplicate-route",
+        &format!(r#"{{"hyperlane": {{"monitored_route_ids": ["{ROUTE}", "{ROUTE}"]}}}}"#),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        error.contains("hyperlane.monitored_route_ids[1]"),
+        "{error}"
+    );
+}
+
+#[test]
+fn test_reload_replaces_the_applied_config() {
+    let first = load(
+        "first",
+        &format!(r#"{{"hyperlane": {{"monitored_route_ids": ["{ROUTE}"]}}}}"#),
+    )
+    .unwrap();
+    let second = load("second", r#"{"hyperlane": {"monitored_route_ids": []}}"#).unwrap();
+
+    // A reload initializes the warp module a second time, which must replace the first config
+    // instead of failing.
+    first.apply().unwrap();
+    second.apply().unwrap();
+    first.apply().unwrap();
+}
@@ -0,0 +1,164 @@
+//! Tests for the verification of k-of-n multisig transactions.
+mod common;
+use common::{TestRuntime, TestSpec};
+use multisig::MultisigConfig;
+use sov_address::EthereumAddress;
+use sov_modules_api::capabilities::{FatalError, UniquenessData};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::{Amount, PrivateKey, PublicKey, RawTx, Spec};
+use sov_modules_stf_blueprint::Runtime as _;
+use sov_rollup_interface::zk::CryptoSpec;
+use stf_starter::authentication::multisig::{
+    signing_payload, verify_signatures, MemberSignature, MultisigTx,
+};
+use stf_starter::RuntimeCall;
+
+type TestCrypto = <TestSpec as Spec>::CryptoSpec;
+type PrivKey = <TestCrypto as CryptoSpec>::PrivateKey;
+
+fn unsigned_tx() -> UnsignedTransaction<TestRuntime, TestSpec> {
+    UnsignedTransaction::new(
+        RuntimeCall::Bank(sov_bank::CallMessage::Transfer {
+            to: EthereumAddress::from([1; 20]),
+            coins: sov_bank::Coins {
+                amount: Amount::new(1_000),
+                token_id: config_value!("GAS_TOKEN_ID"),
+            },
+        }),
+        config_value!("CHAIN_ID"),
+        PriorityFeeBips::ZERO,
+        Amount::new(100_000_000),
+        UniquenessData::Generation(0),
+        None,
+    )
+}
+
+/// A 2-of-3 multisig and the keys of its members.
+fn two_of_three() -> (MultisigConfig, Vec<PrivKey>) {
+    let keys: Vec<PrivKey> = (0..3).map(|_| PrivKey::generate()).collect();
+    let config = MultisigConfig {
+        threshold: 2,
+        members: keys
+            .iter()
+            .map(|key| key.pub_key().credential_id::<Hasher>())
+            .collect(),
+    };
+    (config, keys)
+}
+
+/// A multisig transaction for `config`, signed by `signers` over the multisig signing payload.
+fn multisig_tx(config: &MultisigConfig, signers: &[&PrivKey]) -> MultisigTx<TestCrypto> {
+    let multisig = config.credential_id::<TestSpec>();
+    let unsigned_tx =
+        borsh::to_vec(&unsigned_tx()).expect("Transaction serialization is infallible");
+    let payload = signing_payload(&multisig, &unsigned_tx, &TestRuntime::CHAIN_HASH);
+
+    MultisigTx {
+        multisig,
+        unsigned_tx: RawTx::new(unsigned_tx),
+        signatures: signers
+            .iter()
+            .map(|key| MemberSignature {
+                pub_key: key.pub_key(),
+                signature: key.sign(&payload),
+            })
+            .collect(),
+    }
+}
+
+fn assert_rejected(result: Result<(), FatalError>) {
+    assert!(
+        matches!(result, Err(FatalError::SigVerificationFailed(_))),
+        "expected a signature verification failure, got {result:?}"
+    );
+}
+
+#[test]
+fn test_accepts_threshold_signatures() {
+    let (config, keys) = two_of_three();
+
+    let tx = multisig_tx(&config, &[&keys[0], &keys[2]]);
+    verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH).unwrap();
+
+    let tx = multisig_tx(&config, &[&keys[0], &keys[1], &keys[2]]);
+    verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH).unwrap();
+}
+
+#[test]
+fn test_rejects_fewer_signatures_than_threshold() {
+    let (config, keys) = two_of_three();
+
+    let tx = multisig_tx(&config, &[&keys[1]]);
+    assert_rejected(verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH));
+
+    let tx = multisig_tx(&config, &[]);
+    assert_rejected(verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH));
+}
+
+#[test]
+fn test_rejects_duplicate_signer() {
+    let (config, keys) = two_of_three();
+
+    let tx = multisig_tx(&config, &[&keys[0], &keys[0]]);
+    assert_rejected(verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH));
+
+    // A duplicate is rejected even when the other signatures reach the threshold.
+    let tx = multisig_tx(&config, &[&keys[0], &keys[1], &keys[1]]);
+    assert_rejected(verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH));
+}
+
+#[test]
+fn test_rejects_non_member() {
+    let (config, keys) = two_of_three();
+    let outsider = PrivKey::generate();
+
+    let tx = multisig_tx(&config, &[&keys[0], &outsider]);
+    assert_rejected(verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH));
+}
+
+#[test]
+fn test_rejects_wrong_chain_hash() {
+    let (config, keys) = two_of_three();
+    let tx = multisig_tx(&config, &[&keys[0], &keys[1]]);
+
+    let mut other_chain = TestRuntime::CHAIN_HASH;
+    other_chain[0] ^= 1;
+    assert_rejected(verify_signatures(&tx, &config, &other_chain));
+}
+
+#[test]
+fn test_rejects_standard_signatures() {
+    let (config, keys) = two_of_three();
+    let mut tx = multisig_tx(&config, &[]);
+
+    // Members signing the same transaction with the standard authenticator don't authorize the
+    // multisig.
+    for key in &keys[..2] {
+        let standard = Transaction::<TestRuntime, TestSpec>::new_signed_tx(
+            key,
+            &TestRuntime::CHAIN_HASH,
+            unsigned_tx(),
+        );
+        tx.signatures.push(MemberSignature {
+            pub_key: key.pub_key(),
+            signature: standard.signature().clone(),
+        });
+    }
+    assert_rejected(verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH));
+}
+
+#[test]
+fn test_rejects_signatures_for_another_multisig() {
+    let (config, keys) = two_of_three();
+    let other = MultisigConfig {
+        threshold: 2,
+        members: config.members[..2].to_vec(),
+    };
+
+    // The members shared by both multisigs signed for `other`: the signatures don't hold for
+    // `config`.
+    let mut tx = multisig_tx(&other, &[&keys[0], &keys[1]]);
+    tx.multisig = config.credential_id::<TestSpec>();
+    assert_rejected(verify_signatures(&tx, &config, &TestRuntime::CHAIN_HASH));
+}
@@ -0,0 +1,212 @@
+//! Delivers pings to the ping-pong example through the mailbox of the full runtime, so that the
+//! routing of `HyperlaneRecipients` and the payment of pongs are covered, not only the module.
+mod common;
+use common::{PrivKey, TestRuntime, TestSpec};
+use std::str::FromStr;
+
+use ping_pong::{MessageKind, ReceivedMessage};
+use sov_address::EthereumAddress;
+use sov_bank::{Bank, Coins};
+use sov_hyperlane_integration::mailbox::CallMessage as MailboxCallMessage;
+use sov_hyperlane_integration::{HexHash, HexString};
+use sov_modules_api::capabilities::config_chain_id;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::TxDetails;
+use sov_modules_api::Amount;
+use sov_modules_stf_blueprint::{GenesisParams, Runtime as _};
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{
+    TransactionTestCase, TransactionType, TEST_DEFAULT_MAX_FEE, TEST_DEFAULT_MAX_PRIORITY_FEE,
+};
+use stf_starter::RuntimeCall;
+use stf_starter_declaration::{PingPong, RuntimeEvent};
+
+/// The domain of the rollup, `HYPERLANE_BRIDGE_DOMAIN` in `constants.toml`.
+const LOCAL_DOMAIN: u32 = 5555;
+/// The Solana testnet, registered with the Sealevel address format in the mock genesis.
+const REMOTE_DOMAIN: u32 = 1337;
+const REMOTE_ROUTER: HexHash = HexString([0x42; 32]);
+const PONG_GAS_PAYMENT_LIMIT: u128 = 1_000_000;
+
+/// The key of the relayer submitting `process` calls, and its address.
+fn relayer() -> (PrivKey, EthereumAddress) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test-data/keys/tx_signer_private_key.json");
+    let keys: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let key = serde_json::from_value(keys["private_key"].clone()).unwrap();
+    let address = EthereumAddress::from_str(keys["address"].as_str().unwrap()).unwrap();
+    (key, address)
+}
+
+/// A runner at the mock genesis, with the relayer funded and the ping-pong app trusting
+/// `REMOTE_ROUTER` on `REMOTE_DOMAIN` without checking signatures.
+fn runner(relayer: &EthereumAddress) -> TestRunner<TestRuntime, TestSpec> {
+    let mock_genesis =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/mock/genesis.json");
+    let mut genesis: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(mock_genesis).unwrap()).unwrap();
+    genesis["bank"]["gas_token_config"]["address_and_balances"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!([relayer.to_string(), "1000000000000000"]));
+    genesis["ping_pong"]["routers"] = serde_json::json!([
+        { "domain": REMOTE_DOMAIN, "router": REMOTE_ROUTER.to_string() }
+    ]);
+    genesis["ping_pong"]["ism"] = serde_json::json!("AlwaysTrust");
+    genesis["ping_pong"]["pong_gas_payment_limit"] =
+        serde_json::json!(PONG_GAS_PAYMENT_LIMIT.to_string());
+
+    let path = std::env::temp_dir().join(format!("ping-pong-genesis-{}.json", std::process::id()));
+    std::fs::write(&path, serde_json::to_vec(&genesis).unwrap()).unwrap();
+    let genesis_config = TestRuntime::genesis_config(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    TestRunner::new_with_genesis(
+        GenesisParams {
+            runtime: genesis_config.expect("The modified mock genesis config is valid"),
+        },
+        TestRuntime::default(),
+    )
+}
+
+/// Encodes a Hyperlane message from the remote ping-pong app to the local one.
+fn ping_message(nonce: u32, payload: &[u8]) -> Vec<u8> {
+    let mut message = vec![3];
+    message.extend_from_slice(&nonce.to_be_bytes());
+    message.extend_from_slice(&REMOTE_DOMAIN.to_be_bytes());
+    message.extend_from_slice(&REMOTE_ROUTER.0);
+    message.extend_from_slice(&LOCAL_DOMAIN.to_be_bytes());
+    message.extend_from_slice(&PingPong::<TestSpec>::router_address().0);
+    message.extend_from_slice(&MessageKind::Ping.encode(payload));
+    message
+}
+
+fn plain(key: &PrivKey, call: RuntimeCall<TestSpec>) -> TransactionType<TestRuntime, TestSpec> {
+    TransactionType::Plain {
+        message: call,
+        key: key.clone(),
+        details: TxDetails {
+            max_priority_fee_bips: TEST_DEFAULT_MAX_PRIORITY_FEE,
+            max_fee: TEST_DEFAULT_MAX_FEE,
+            gas_limit: None,
+            chain_id: config_chain_id(),
+        },
+    }
+}
+
+fn process(key: &PrivKey, message: Vec<u8>) -> TransactionType<TestRuntime, TestSpec> {
+    plain(
+        key,
+        RuntimeCall::Mailbox(MailboxCallMessage::Process {
+            metadata: HexString(Vec::new()),
+            message: HexString(message),
+        }),
+    )
+}
+
+fn pong_sent(events: &[RuntimeEvent<TestSpec>]) -> bool {
+    events.iter().any(|event| {
+        matches!(
+            event,
+            RuntimeEvent::PingPong(ping_pong::Event::MessageSent {
+                destination: REMOTE_DOMAIN,
+                kind: MessageKind::Pong,
+                ..
+            })
+        )
+    })
+}
+
+fn module_balance(state: &mut impl sov_modules_api::StateReader<sov_state::User>) -> Amount {
+    Bank::<TestSpec>::default()
+        .get_balance_of(
+            &PingPong::<TestSpec>::address(),
+            config_value!("GAS_TOKEN_ID"),
+            state,
+        )
+        .unwrap()
+        .unwrap_or(Amount::ZERO)
+}
+
+#[test]
+fn test_ping_is_routed_to_the_app_and_pong_is_paid_by_the_module() {
+    let (key, address) = relayer();
+    let mut runner = runner(&address);
+
+    // The module account is empty: the ping is stored, and the relayer doesn't pay for a pong.
+    runner.execute_transaction(TransactionTestCase {
+        input: process(&key, ping_message(0, &[1, 2, 3])),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(!pong_sent(&result.events));
+            assert_eq!(
+                PingPong::<TestSpec>::default()
+                    .received
+                    .get(&0, state)
+                    .unwrap(),
+                Some(ReceivedMessage {
+                    origin: REMOTE_DOMAIN,
+                    kind: MessageKind::Ping,
+                    payload: HexString(vec![1, 2, 3]),
+                })
+            );
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: plain(
+            &key,
+            RuntimeCall::Bank(sov_bank::CallMessage::Transfer {
+                to: PingPong::<TestSpec>::address(),
+                coins: Coins {
+                    amount: Amount::new(PONG_GAS_PAYMENT_LIMIT),
+                    token_id: config_value!("GAS_TOKEN_ID"),
+                },
+            }),
+        ),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+
+    // Once funded, the module answers from its own account.
+    runner.execute_transaction(TransactionTestCase {
+        input: process(&key, ping_message(1, &[4])),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert!(pong_sent(&result.events));
+            assert_eq!(
+                PingPong::<TestSpec>::default()
+                    .received_count
+                    .get(state)
+                    .unwrap(),
+                Some(2)
+            );
+            assert!(module_balance(state) <= Amount::new(PONG_GAS_PAYMENT_LIMIT));
+        }),
+    });
+}
+
+#[test]
+fn test_ping_from_unenrolled_sender_is_rejected() {
+    let (key, address) = relayer();
+    let mut runner = runner(&address);
+
+    let mut message = ping_message(0, &[1]);
+    // Replace the sender with another app on the same domain.
+    message[9..41].copy_from_slice(&[0x43; 32]);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: process(&key, message),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            assert_eq!(
+                PingPong::<TestSpec>::default()
+                    .received_count
+                    .get(state)
+                    .unwrap(),
+                None
+            );
+        }),
+    });
+}
@@ -0,0 +1,247 @@
+//! Tests for the checks the transaction authenticator runs on calls signed by session keys.
+mod common;
+use common::{TestRuntime, TestSpec};
+use session_keys::{SessionKeyGrant, SpendCap};
+use sov_address::EthereumAddress;
+use sov_bank::TokenId;
+use sov_hyperlane_integration::HexString;
+use sov_modules_api::macros::config_value;
+use sov_modules_api::{Amount, CredentialId};
+use sov_modules_stf_blueprint::{GenesisParams, Runtime as _};
+use sov_test_utils::runtime::TestRunner;
+use stf_starter::authentication::session_key::{
+    authorize, delegated_credential_id, validate_allowed_calls, SESSION_KEY_CALLS,
+};
+use stf_starter::RuntimeCall;
+
+const SESSION_KEY: CredentialId = CredentialId([7; 32]);
+const EXPIRES_AT: u64 = 100;
+const OWNER: [u8; 20] = [9; 20];
+const NO_FEE: Amount = Amount::ZERO;
+
+fn gas_token() -> TokenId {
+    config_value!("GAS_TOKEN_ID")
+}
+
+fn other_token() -> TokenId {
+    sov_bank::get_token_id::<TestSpec>("other", Some(6), &EthereumAddress::from(OWNER))
+}
+
+fn transfer_of(token_id: TokenId, amount: u128) -> RuntimeCall<TestSpec> {
+    RuntimeCall::Bank(sov_bank::CallMessage::Transfer {
+        to: EthereumAddress::from([1; 20]),
+        coins: sov_bank::Coins {
+            amount: Amount::new(amount),
+            token_id,
+        },
+    })
+}
+
+fn transfer(amount: u128) -> RuntimeCall<TestSpec> {
+    transfer_of(gas_token(), amount)
+}
+
+fn create_vesting(total: u128) -> RuntimeCall<TestSpec> {
+    RuntimeCall::Vesting(vesting::CallMessage::Create {
+        grant: vesting::NewGrant {
+            beneficiary: EthereumAddress::from([1; 20]),
+            token_id: gas_token(),
+            total: Amount::new(total),
+            clock: vesting::Clock::Height,
+            schedule: vesting::Schedule::Cliff { at: 50 },
+        },
+    })
+}
+
+fn call_remote(gas_payment_limit: u128) -> RuntimeCall<TestSpec> {
+    RuntimeCall::InterchainAccounts(interchain_accounts::CallMessage::CallRemote {
+        destination: 1,
+        calls: Vec::new(),
+        gas_payment_limit: Amount::new(gas_payment_limit),
+    })
+}
+
+fn revoke() -> RuntimeCall<TestSpec> {
+    RuntimeCall::SessionKeys(session_keys::CallMessage::Revoke {
+        session_key: SESSION_KEY,
+    })
+}
+
+/// A grant allowing `calls`, capping the gas token at 1000, of which `spent` were already moved.
+fn grant(calls: &[&str], spent: u128) -> SessionKeyGrant<TestSpec> {
+    SessionKeyGrant {
+        owner: EthereumAddress::from(OWNER),
+        allowed_calls: calls.iter().map(|call| call.to_string()).collect(),
+        spend_caps: vec![SpendCap {
+            token_id: gas_token(),
+            cap: Amount::new(1_000),
+            spent: Amount::new(spent),
+        }],
+        expires_at: EXPIRES_AT,
+    }
+}
+
+/// A runner at the mock genesis, for the warp routes the checks read.
+fn runner() -> TestRunner<TestRuntime, TestSpec> {
+    let mock_genesis =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/mock/genesis.json");
+    TestRunner::new_with_genesis(
+        GenesisParams {
+            runtime: TestRuntime::genesis_config(&mock_genesis)
+                .expect("The mock genesis config is valid"),
+        },
+        TestRuntime::default(),
+    )
+}
+
+fn check(
+    grant: &SessionKeyGrant<TestSpec>,
+    call: &RuntimeCall<TestSpec>,
+    max_fee: Amount,
+    height: u64,
+) -> anyhow::Result<EthereumAddress> {
+    let mut result = None;
+    runner().query_state(|state| {
+        result = Some(authorize::<TestSpec, TestRuntime>(
+            grant,
+            &SESSION_KEY,
+            call,
+            max_fee,
+            height,
+            state,
+        ));
+    });
+    result.unwrap()
+}
+
+#[test]
+fn test_executes_as_owner() {
+    let grant = grant(&["bank.transfer"], 0);
+
+    assert_eq!(
+        check(&grant, &transfer(1_000), NO_FEE, 0).unwrap(),
+        EthereumAddress::from(OWNER)
+    );
+}
+
+#[test]
+fn test_rejects_expired_key() {
+    let grant = grant(&["bank.transfer"], 0);
+
+    assert!(check(&grant, &transfer(1), NO_FEE, EXPIRES_AT - 1).is_ok());
+    assert!(check(&grant, &transfer(1), NO_FEE, EXPIRES_AT).is_err());
+    assert!(check(&grant, &transfer(1), NO_FEE, EXPIRES_AT + 1).is_err());
+}
+
+#[test]
+fn test_rejects_calls_outside_scope() {
+    let grant = grant(&["bank.transfer"], 0);
+
+    assert!(check(&grant, &create_vesting(1), NO_FEE, 0).is_err());
+    assert!(check(&grant, &call_remote(0), NO_FEE, 0).is_err());
+    // Other calls of an allowed call's module are not allowed either.
+    let burn = RuntimeCall::Bank(sov_bank::CallMessage::Burn {
+        coins: sov_bank::Coins {
+            amount: Amount::new(1),
+            token_id: gas_token(),
+        },
+    });
+    assert!(check(&grant, &burn, NO_FEE, 0).is_err());
+}
+
+#[test]
+fn test_rejects_session_key_management() {
+    // Even a grant naming them can't let a key renew or revoke grants.
+    let grant = grant(&["bank.transfer", "session_keys.revoke"], 0);
+
+    assert!(check(&grant, &revoke(), NO_FEE, 0).is_err());
+    assert!(validate_allowed_calls(&["session_keys.revoke".to_string()]).is_err());
+    assert!(validate_allowed_calls(&["session_keys".to_string()]).is_err());
+}
+
+#[test]
+fn test_validates_allowed_calls() {
+    let names: Vec<String> = SESSION_KEY_CALLS.iter().map(|s| s.to_string()).collect();
+    validate_allowed_calls(&names).unwrap();
+
+    // Whole modules can't be allowed, only calls.
+    assert!(validate_allowed_calls(&["warp".to_string()]).is_err());
+    assert!(validate_allowed_calls(&["bank.create_token".to_string()]).is_err());
+}
+
+#[test]
+fn test_spend_cap_counts_previous_spending() {
+    let grant = grant(&["bank.transfer"], 700);
+
+    assert!(check(&grant, &transfer(300), NO_FEE, 0).is_ok());
+    assert!(check(&grant, &transfer(301), NO_FEE, 0).is_err());
+}
+
+#[test]
+fn test_spend_cap_counts_the_fee() {
+    let grant = grant(&["bank.transfer"], 0);
+
+    assert!(check(&grant, &transfer(900), Amount::new(100), 0).is_ok());
+    assert!(check(&grant, &transfer(900), Amount::new(101), 0).is_err());
+}
+
+#[test]
+fn test_spend_caps_are_per_token() {
+    let grant = grant(&["bank.transfer"], 0);
+
+    // A token the grant doesn't cap can't be moved, whatever is left of the gas token cap.
+    assert!(check(&grant, &transfer_of(other_token(), 1), NO_FEE, 0).is_err());
+    assert!(check(&grant, &transfer_of(other_token(), 0), NO_FEE, 0).is_ok());
+}
+
+#[test]
+fn test_spend_cap_covers_every_allowed_call() {
+    let grant = grant(
+        &[
+            "bank.transfer",
+            "vesting.create",
+            "interchain_accounts.call_remote",
+        ],
+        0,
+    );
+
+    // Locking tokens in a vesting grant and paying for interchain calls move tokens too.
+    assert!(check(&grant, &create_vesting(1_000), NO_FEE, 0).is_ok());
+    assert!(check(&grant, &create_vesting(1_001), NO_FEE, 0).is_err());
+    assert!(check(&grant, &call_remote(1_000), NO_FEE, 0).is_ok());
+    assert!(check(&grant, &call_remote(1_001), NO_FEE, 0).is_err());
+}
+
+#[test]
+fn test_transfer_remote_needs_a_registered_route() {
+    let grant = grant(&["warp.transfer_remote"], 0);
+    let transfer_remote = RuntimeCall::Warp(
+        sov_hyperlane_integration::warp::CallMessage::TransferRemote {
+            warp_route: HexString([0x42; 32]),
+            destination_domain: 1,
+            recipient: HexString([1; 32]),
+            amount: Amount::new(1),
+            gas_payment_limit: Amount::ZERO,
+        },
+    );
+
+    // The token of the amount is the route's, so a route that doesn't exist can't be bounded.
+    assert!(check(&grant, &transfer_remote, NO_FEE, 0).is_err());
+}
+
+#[test]
+fn test_delegated_credential_is_bound_to_owner() {
+    let owner = EthereumAddress::from(OWNER);
+    let other_owner = EthereumAddress::from([8; 20]);
+    let delegated = delegated_credential_id::<TestSpec>(&SESSION_KEY, &owner);
+
+    assert_ne!(delegated, SESSION_KEY);
+    assert_eq!(
+        delegated,
+        delegated_credential_id::<TestSpec>(&SESSION_KEY, &owner)
+    );
+    assert_ne!(
+        delegated,
+        delegated_credential_id::<TestSpec>(&SESSION_KEY, &other_owner)
+    );
+}
@@ -0,0 +1,121 @@
+//! Tests for the verification of transactions signed over Solana off-chain messages.
+mod common;
+use common::{PrivKey, TestRuntime, TestSpec};
+use sov_address::EthereumAddress;
+use sov_modules_api::capabilities::{FatalError, UniquenessData};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::{Amount, PrivateKey, Spec};
+use sov_modules_stf_blueprint::Runtime as _;
+use sov_rollup_interface::zk::CryptoSpec;
+use stf_starter::authentication::solana_offchain::{self, SIGNING_DOMAIN};
+use stf_starter::RuntimeCall;
+
+fn unsigned_tx() -> UnsignedTransaction<TestRuntime, TestSpec> {
+    UnsignedTransaction::new(
+        RuntimeCall::Bank(sov_bank::CallMessage::Transfer {
+            to: EthereumAddress::from([1; 20]),
+            coins: sov_bank::Coins {
+                amount: Amount::new(1_000),
+                token_id: config_value!("GAS_TOKEN_ID"),
+            },
+        }),
+        config_value!("CHAIN_ID"),
+        PriorityFeeBips::ZERO,
+        Amount::new(100_000_000),
+        UniquenessData::Generation(0),
+        None,
+    )
+}
+
+/// Builds a transaction declaring `pub_key` as its signer, with `signer` signing `message`.
+fn transaction(
+    signer: &PrivKey,
+    pub_key: <<TestSpec as Spec>::CryptoSpec as CryptoSpec>::PublicKey,
+    message: impl FnOnce(&Transaction<TestRuntime, TestSpec>) -> Vec<u8>,
+) -> Transaction<TestRuntime, TestSpec> {
+    let UnsignedTransaction {
+        runtime_call,
+        uniqueness,
+        details,
+        ..
+    } = unsigned_tx();
+    // The signature doesn't take part in the signing message, so a placeholder one is replaced
+    // once the message is known.
+    let placeholder = Transaction::<TestRuntime, TestSpec>::new_with_details_v0(
+        pub_key.clone(),
+        runtime_call.clone(),
+        signer.sign(&[]),
+        uniqueness.clone(),
+        details.clone(),
+    );
+    Transaction::new_with_details_v0(
+        pub_key,
+        runtime_call,
+        signer.sign(&message(&placeholder)),
+        uniqueness,
+        details,
+    )
+}
+
+fn solana_message(tx: &Transaction<TestRuntime, TestSpec>) -> Vec<u8> {
+    solana_offchain::signing_message(tx, &TestRuntime::CHAIN_HASH).unwrap()
+}
+
+#[test]
+fn test_accepts_solana_signature() {
+    let key = PrivKey::generate();
+    let tx = transaction(&key, key.pub_key(), solana_message);
+
+    solana_offchain::verify(&tx, &TestRuntime::CHAIN_HASH).unwrap();
+}
+
+#[test]
+fn test_rejects_wrong_signer() {
+    let key = PrivKey::generate();
+    let other = PrivKey::generate();
+    let tx = transaction(&other, key.pub_key(), solana_message);
+
+    assert!(matches!(
+        solana_offchain::verify(&tx, &TestRuntime::CHAIN_HASH),
+        Err(FatalError::SigVerificationFailed(_))
+    ));
+}
+
+#[test]
+fn test_rejects_wrong_chain_hash() {
+    let key = PrivKey::generate();
+    let tx = transaction(&key, key.pub_key(), solana_message);
+
+    let mut other_chain = TestRuntime::CHAIN_HASH;
+    other_chain[0] ^= 1;
+    assert!(matches!(
+        solana_offchain::verify(&tx, &other_chain),
+        Err(FatalError::SigVerificationFailed(_))
+    ));
+}
+
+#[test]
+fn test_rejects_message_outside_signing_domain() {
+    let key = PrivKey::generate();
+
+    // The bare hex message, as a wallet without the off-chain envelope would sign it.
+    let tx = transaction(&key, key.pub_key(), |tx| {
+        solana_message(tx)[SIGNING_DOMAIN.len() + 4..].to_vec()
+    });
+    assert!(matches!(
+        solana_offchain::verify(&tx, &TestRuntime::CHAIN_HASH),
+        Err(FatalError::SigVerificationFailed(_))
+    ));
+
+    // The standard signing payload is not accepted by the Solana variant either.
+    let tx = Transaction::<TestRuntime, TestSpec>::new_signed_tx(
+        &key,
+        &TestRuntime::CHAIN_HASH,
+        unsigned_tx(),
+    );
+    assert!(matches!(
+        solana_offchain::verify(&tx, &TestRuntime::CHAIN_HASH),
+        Err(FatalError::SigVerificationFailed(_))
+    ));
+}
@@ -0,0 +1,434 @@
+//! A Hyperlane "ping-pong" application: an example of a cross-chain app that isn't a warp route.
+//!
+//! Anyone can send a ping to the ping-pong app enrolled for a remote domain. When a ping arrives
+//! from an enrolled app, the module stores it, emits an event and replies with a pong carrying
+//! the same payload. Pongs are stored as well, and not answered. Pongs are paid from
+//! [`PingPong::address`], which anyone can fund with a bank transfer.
+//!
+//! A message body is one byte for the kind, [`PING`] or [`PONG`], followed by the payload, so the
+//! remote side is easy to write in any language.
+use anyhow::{bail, Context as _, Result};
+use schemars::JsonSchema;
+use sov_bank::Bank;
+use sov_hyperlane_integration::{HexHash, HexString, HyperlaneAddress, Ism};
+use sov_modules_api::capabilities::Credentials;
+use sov_modules_api::macros::{config_value, serialize, UniversalWallet};
+use sov_modules_api::{
+    Amount, Context, CredentialId, CryptoSpec, EventEmitter, GenesisState, Module, ModuleId,
+    ModuleInfo, ModuleRestApi, Spec, StateMap, StateReader, StateValue, TxState,
+};
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+use std::marker::PhantomData;
+
+/// The first byte of a ping.
+pub const PING: u8 = 0;
+
+/// The first byte of a pong.
+pub const PONG: u8 = 1;
+
+/// The maximum payload length, in bytes.
+pub const MAX_PAYLOAD_LEN: usize = 1024;
+
+/// Runtime hooks used by [`PingPong`]. The module can't depend on the runtime it is part of, so
+/// dispatching Hyperlane messages through the mailbox is delegated.
+pub trait PingPongRuntime<S: Spec>: Clone + Default + Send + Sync + 'static {
+    /// Dispatches a Hyperlane message from `sender`, paying for delivery from `context.sender()`.
+    /// Returns the message ID.
+    fn dispatch(
+        destination: u32,
+        sender: HexHash,
+        recipient: HexHash,
+        body: HexString,
+        gas_payment_limit: Amount,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<HexHash>;
+}
+
+/// The kind of a ping-pong message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    /// A message expecting a reply.
+    Ping,
+    /// The reply to a ping.
+    Pong,
+}
+
+impl MessageKind {
+    /// Encodes a message body.
+    pub fn encode(self, payload: &[u8]) -> Vec<u8> {
+        let kind = match self {
+            MessageKind::Ping => PING,
+            MessageKind::Pong => PONG,
+        };
+        [&[kind], payload].concat()
+    }
+
+    /// Decodes a message body into its kind and payload.
+    pub fn decode(body: &[u8]) -> Result<(Self, &[u8])> {
+        let (kind, payload) = body.split_first().context("Empty ping-pong message")?;
+        let kind = match *kind {
+            PING => MessageKind::Ping,
+            PONG => MessageKind::Pong,
+            other => bail!("Unknown ping-pong message kind {other}"),
+        };
+        Ok((kind, payload))
+    }
+}
+
+/// A remote ping-pong app.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct RemoteRouter {
+    /// The Hyperlane domain of the remote chain.
+    pub domain: u32,
+    /// The address of the app on the remote chain.
+    pub router: HexHash,
+}
+
+/// A message received from a remote app.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub struct ReceivedMessage {
+    /// The Hyperlane domain the message comes from.
+    pub origin: u32,
+    /// Whether the message is a ping or a pong.
+    pub kind: MessageKind,
+    /// The payload of the message.
+    pub payload: HexString,
+}
+
+/// Genesis configuration of the [`PingPong`] module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "PingPongConfig")]
+pub struct PingPongConfig<S: Spec> {
+    /// The account allowed to enroll and unenroll remote apps.
+    pub admin: S::Address,
+    /// The remote apps trusted at genesis.
+    pub routers: Vec<RemoteRouter>,
+    /// The ISM verifying inbound messages.
+    pub ism: Ism,
+    /// The maximum gas payment of a pong, paid from [`PingPong::address`].
+    pub pong_gas_payment_limit: Amount,
+}
+
+/// Receives pings and pongs from remote apps, and answers pings.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct PingPong<S: Spec, R: PingPongRuntime<S>> {
+    /// Id of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The account allowed to manage remote apps.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The trusted app of each remote domain.
+    #[state]
+    pub routers: StateMap<u32, HexHash>,
+
+    /// The ISM verifying inbound messages.
+    #[state]
+    pub ism: StateValue<Ism>,
+
+    /// The maximum gas payment of a pong.
+    #[state]
+    pub pong_gas_payment_limit: StateValue<Amount>,
+
+    /// Every message received, by arrival order.
+    #[state]
+    pub received: StateMap<u64, ReceivedMessage>,
+
+    /// The number of messages received.
+    #[state]
+    pub received_count: StateValue<u64>,
+
+    /// Pays for pongs from the module account.
+    #[module]
+    pub bank: Bank<S>,
+
+    #[phantom]
+    phantom: PhantomData<R>,
+}
+
+impl<S: Spec, R: PingPongRuntime<S>> PingPong<S, R>
+where
+    S::Address: HyperlaneAddress,
+{
+    /// The Hyperlane address of the local app. Remote apps send messages to it, and pings and
+    /// pongs are sent from it.
+    pub fn router_address() -> HexHash {
+        use sov_modules_api::digest::Digest;
+
+        let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+        hasher.update(b"ping_pong_router");
+        HexString(hasher.finalize().into())
+    }
+
+    /// The account paying for pongs. It isn't controlled by any key: it is funded with bank
+    /// transfers, and only spent by the module.
+    pub fn address() -> S::Address {
+        use sov_modules_api::digest::Digest;
+
+        let mut hasher = <S::CryptoSpec as CryptoSpec>::Hasher::new();
+        hasher.update(b"ping_pong");
+        CredentialId(hasher.finalize().into()).into()
+    }
+
+    /// Returns the ISM that must verify messages sent to the local app.
+    pub fn ism(&self, state: &mut impl StateReader<User>) -> Result<Option<Ism>> {
+        Ok(self.ism.get(state)?)
+    }
+
+    /// Handles an inbound message: checks that it comes from the app enrolled for `origin`,
+    /// stores it, and answers pings with a pong paid from [`Self::address`].
+    ///
+    /// The relayer delivering the ping doesn't pay for the pong. If the module account can't
+    /// cover `pong_gas_payment_limit`, the ping is still stored and the pong is skipped, so that
+    /// an unfunded module doesn't block the delivery of pings.
+    pub fn handle(
+        &mut self,
+        origin: u32,
+        sender: HexHash,
+        body: &[u8],
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let router = self.enrolled_router(origin, state)?;
+        if router != sender {
+            bail!("Sender {sender} is not the ping-pong app enrolled for domain {origin}");
+        }
+
+        let (kind, payload) = MessageKind::decode(body)?;
+        let index = self.received_count.get(state)?.unwrap_or_default();
+        let message = ReceivedMessage {
+            origin,
+            kind,
+            payload: HexString(payload.to_vec()),
+        };
+        self.received.set(&index, &message, state)?;
+        self.received_count.set(&(index + 1), state)?;
+        self.emit_event(
+            state,
+            Event::MessageReceived {
+                index,
+                origin,
+                kind,
+                payload: message.payload.clone(),
+            },
+        );
+
+        if kind == MessageKind::Ping {
+            let gas_payment_limit = self
+                .pong_gas_payment_limit
+                .get(state)?
+                .unwrap_or(Amount::ZERO);
+            let balance = self
+                .bank
+                .get_balance_of(&Self::address(), config_value!("GAS_TOKEN_ID"), state)?
+                .unwrap_or(Amount::ZERO);
+            if balance < gas_payment_limit {
+                self.emit_event(
+                    state,
+                    Event::PongSkipped {
+                        index,
+                        balance,
+                        gas_payment_limit,
+                    },
+                );
+                return Ok(());
+            }
+
+            let module_context = Context::new(
+                Self::address(),
+                Credentials::new(Self::address()),
+                context.sequencer().clone(),
+                context.sequencer_da_address().clone(),
+            );
+            self.send(
+                origin,
+                router,
+                MessageKind::Pong,
+                payload,
+                gas_payment_limit,
+                &module_context,
+                state,
+            )?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn send(
+        &mut self,
+        destination: u32,
+        router: HexHash,
+        kind: MessageKind,
+        payload: &[u8],
+        gas_payment_limit: Amount,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        let message_id = R::dispatch(
+            destination,
+            Self::router_address(),
+            router,
+            HexString(kind.encode(payload)),
+            gas_payment_limit,
+            context,
+            state,
+        )?;
+        self.emit_event(
+            state,
+            Event::MessageSent {
+                destination,
+                kind,
+                message_id,
+            },
+        );
+        Ok(())
+    }
+
+    fn enrolled_router(&self, domain: u32, state: &mut impl StateReader<User>) -> Result<HexHash> {
+        self.routers
+            .get(&domain, state)?
+            .with_context(|| format!("No ping-pong app enrolled for domain {domain}"))
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .context("The ping-pong admin is not set")?;
+        if &admin != context.sender() {
+            bail!("Only the admin {admin} can manage ping-pong apps");
+        }
+        Ok(())
+    }
+}
+
+impl<S: Spec, R: PingPongRuntime<S>> Module for PingPong<S, R>
+where
+    S::Address: HyperlaneAddress,
+{
+    type Spec = S;
+
+    type Config = PingPongConfig<S>;
+
+    type CallMessage = CallMessage;
+
+    type Event = Event;
+
+    type Error = anyhow::Error;
+
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.admin.set(&config.admin, state)?;
+        self.ism.set(&config.ism, state)?;
+        self.pong_gas_payment_limit
+            .set(&config.pong_gas_payment_limit, state)?;
+        for RemoteRouter { domain, router } in &config.routers {
+            self.routers.set(domain, router, state)?;
+        }
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        state: &mut impl TxState<S>,
+    ) -> Result<()> {
+        match msg {
+            CallMessage::EnrollRemoteRouter(RemoteRouter { domain, router }) => {
+                self.ensure_admin(context, state)?;
+                self.routers.set(&domain, &router, state)?;
+                self.emit_event(state, Event::RouterEnrolled { domain, router });
+                Ok(())
+            }
+            CallMessage::UnenrollRemoteRouter { domain } => {
+                self.ensure_admin(context, state)?;
+                self.enrolled_router(domain, state)?;
+                self.routers.remove(&domain, state)?;
+                self.emit_event(state, Event::RouterUnenrolled { domain });
+                Ok(())
+            }
+            CallMessage::Ping {
+                destination,
+                payload,
+                gas_payment_limit,
+            } => {
+                if payload.0.len() > MAX_PAYLOAD_LEN {
+                    bail!("A payload can be at most {MAX_PAYLOAD_LEN} bytes long");
+                }
+                let router = self.enrolled_router(destination, state)?;
+                self.send(
+                    destination,
+                    router,
+                    MessageKind::Ping,
+                    &payload.0,
+                    gas_payment_limit,
+                    context,
+                    state,
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum CallMessage {
+    /// Trusts `router` as the ping-pong app of `domain`. Admin only.
+    EnrollRemoteRouter(RemoteRouter),
+    /// Stops exchanging messages with `domain`. Admin only.
+    UnenrollRemoteRouter { domain: u32 },
+    /// Sends a ping with `payload` to the app enrolled for `destination`. The sender pays for
+    /// delivery, up to `gas_payment_limit`.
+    Ping {
+        destination: u32,
+        payload: HexString,
+        gas_payment_limit: Amount,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    /// A remote app was enrolled or replaced.
+    RouterEnrolled { domain: u32, router: HexHash },
+    /// A remote app was unenrolled.
+    RouterUnenrolled { domain: u32 },
+    /// A ping or a pong was dispatched.
+    MessageSent {
+        destination: u32,
+        kind: MessageKind,
+        message_id: HexHash,
+    },
+    /// A ping or a pong was received and stored at `index`.
+    MessageReceived {
+        index: u64,
+        origin: u32,
+        kind: MessageKind,
+        payload: HexString,
+    },
+    /// The ping stored at `index` wasn't answered, because the module account holds less than
+    /// the pong gas payment limit.
+    PongSkipped {
+        index: u64,
+        balance: Amount,
+        gas_payment_limit: Amount,
+    },
+}
@@ -0,0 +1,359 @@
+use ping_pong::{
+    CallMessage, Event, MessageKind, PingPongConfig, PingPongRuntime, ReceivedMessage,
+    RemoteRouter, MAX_PAYLOAD_LEN,
+};
+use sov_bank::{Bank, Coins};
+use sov_hyperlane_integration::{HexHash, HexString, Ism};
+use sov_modules_api::macros::config_value;
+use sov_modules_api::{Amount, Context, Spec, TxState};
+use sov_test_utils::{generate_optimistic_runtime, TestSpec};
+
+type S = TestSpec;
+
+const LOCAL_DOMAIN: u32 = 5555;
+const SOLANA_DOMAIN: u32 = 1337;
+const PONG_GAS_PAYMENT_LIMIT: Amount = Amount::new(1_000);
+
+/// A mailbox stub delivering every message back to the local app, as if it came from
+/// `LOCAL_DOMAIN`. Enrolling the local app for `LOCAL_DOMAIN` lets a ping travel both ways in a
+/// single transaction.
+#[derive(Clone, Default)]
+pub struct Loopback;
+
+impl PingPongRuntime<S> for Loopback {
+    fn dispatch(
+        _destination: u32,
+        sender: HexHash,
+        _recipient: HexHash,
+        body: HexString,
+        _gas_payment_limit: Amount,
+        context: &Context<S>,
+        state: &mut impl TxState<S>,
+    ) -> anyhow::Result<HexHash> {
+        PingPong::default().handle(LOCAL_DOMAIN, sender, &body.0, context, state)?;
+        // Message IDs are only reported in events.
+        Ok(HexString([body.0[0]; 32]))
+    }
+}
+
+type PingPong = ping_pong::PingPong<S, Loopback>;
+
+generate_optimistic_runtime!(
+    TestRuntime <=
+    ping_pong: PingPong
+);
+
+use sov_test_utils::runtime::genesis::optimistic::HighLevelOptimisticGenesisConfig;
+use sov_test_utils::runtime::TestRunner;
+use sov_test_utils::{AsUser, TestUser, TransactionTestCase};
+
+pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
+    pub other_user: TestUser<S>,
+}
+
+fn ism() -> Ism {
+    serde_json::from_value(serde_json::json!({
+        "MessageIdMultisig": {
+            "threshold": 1,
+            "validators": ["0x2c25Ab04F9cD2beC3D98921b02AFBE54B792cad0"]
+        }
+    }))
+    .unwrap()
+}
+
+pub fn setup(routers: Vec<RemoteRouter>) -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
+    let genesis_config =
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
+
+    let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
+    let other_user = users.pop().unwrap();
+
+    let genesis = GenesisConfig::from_minimal_config(
+        genesis_config.into(),
+        PingPongConfig {
+            admin: admin.address(),
+            routers,
+            ism: ism(),
+            pong_gas_payment_limit: PONG_GAS_PAYMENT_LIMIT,
+        },
+    );
+
+    let runner =
+        TestRunner::new_with_genesis(genesis.into_genesis_params(), TestRuntime::default());
+
+    (TestData { admin, other_user }, runner)
+}
+
+/// Enrolls the local app for the local domain, so that pings are delivered back to it.
+fn loopback() -> Vec<RemoteRouter> {
+    vec![RemoteRouter {
+        domain: LOCAL_DOMAIN,
+        router: PingPong::router_address(),
+    }]
+}
+
+/// Funds the module account with enough gas tokens for one pong.
+fn fund_pongs(user: &TestUser<S>, runner: &mut TestRunner<TestRuntime<S>, S>) {
+    runner.execute_transaction(TransactionTestCase {
+        input: user.create_plain_message::<TestRuntime<S>, Bank<S>>(
+            sov_bank::CallMessage::Transfer {
+                to: PingPong::address(),
+                coins: Coins {
+                    amount: PONG_GAS_PAYMENT_LIMIT,
+                    token_id: config_value!("GAS_TOKEN_ID"),
+                },
+            },
+        ),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+}
+
+fn ping(destination: u32, payload: Vec<u8>) -> CallMessage {
+    CallMessage::Ping {
+        destination,
+        payload: HexString(payload),
+        gas_payment_limit: Amount::new(1_000),
+    }
+}
+
+#[test]
+fn test_ping_is_answered_with_pong() {
+    let (test_data, mut runner) = setup(loopback());
+    fund_pongs(&test_data.admin, &mut runner);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, PingPong>(ping(LOCAL_DOMAIN, vec![1, 2, 3])),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+
+            // The ping is received first, and its pong is received while the ping is answered.
+            let payload = HexString(vec![1, 2, 3]);
+            assert_eq!(
+                result.events,
+                vec![
+                    TestRuntimeEvent::PingPong(Event::MessageReceived {
+                        index: 0,
+                        origin: LOCAL_DOMAIN,
+                        kind: MessageKind::Ping,
+                        payload: payload.clone(),
+                    }),
+                    TestRuntimeEvent::PingPong(Event::MessageReceived {
+                        index: 1,
+                        origin: LOCAL_DOMAIN,
+                        kind: MessageKind::Pong,
+                        payload: payload.clone(),
+                    }),
+                    TestRuntimeEvent::PingPong(Event::MessageSent {
+                        destination: LOCAL_DOMAIN,
+                        kind: MessageKind::Pong,
+                        message_id: HexString([ping_pong::PONG; 32]),
+                    }),
+                    TestRuntimeEvent::PingPong(Event::MessageSent {
+                        destination: LOCAL_DOMAIN,
+                        kind: MessageKind::Ping,
+                        message_id: HexString([ping_pong::PING; 32]),
+                    }),
+                ]
+            );
+
+            let ping_pong = PingPong::default();
+            assert_eq!(ping_pong.received_count.get(state).unwrap(), Some(2));
+            assert_eq!(
+                ping_pong.received.get(&0, state).unwrap(),
+                Some(ReceivedMessage {
+                    origin: LOCAL_DOMAIN,
+                    kind: MessageKind::Ping,
+                    payload: payload.clone(),
+                })
+            );
+            assert_eq!(
+                ping_pong.received.get(&1, state).unwrap(),
+                Some(ReceivedMessage {
+                    origin: LOCAL_DOMAIN,
+                    kind: MessageKind::Pong,
+                    payload,
+                })
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_pong_is_skipped_when_module_account_is_unfunded() {
+    let (test_data, mut runner) = setup(loopback());
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, PingPong>(ping(LOCAL_DOMAIN, vec![1])),
+        assert: Box::new(move |result, state| {
+            // The ping is stored, but the sender of the ping delivery doesn't pay for the pong.
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![
+                    TestRuntimeEvent::PingPong(Event::MessageReceived {
+                        index: 0,
+                        origin: LOCAL_DOMAIN,
+                        kind: MessageKind::Ping,
+                        payload: HexString(vec![1]),
+                    }),
+                    TestRuntimeEvent::PingPong(Event::PongSkipped {
+                        index: 0,
+                        balance: Amount::ZERO,
+                        gas_payment_limit: PONG_GAS_PAYMENT_LIMIT,
+                    }),
+                    TestRuntimeEvent::PingPong(Event::MessageSent {
+                        destination: LOCAL_DOMAIN,
+                        kind: MessageKind::Ping,
+                        message_id: HexString([ping_pong::PING; 32]),
+                    }),
+                ]
+            );
+            assert_eq!(
+                PingPong::default().received_count.get(state).unwrap(),
+                Some(1)
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_messages_from_other_senders_are_rejected() {
+    // The loopback delivers the ping from the local app, which isn't the enrolled one.
+    let (test_data, mut runner) = setup(vec![RemoteRouter {
+        domain: LOCAL_DOMAIN,
+        router: HexString([9; 32]),
+    }]);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, PingPong>(ping(LOCAL_DOMAIN, vec![1])),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_reverted());
+            assert_eq!(PingPong::default().received_count.get(state).unwrap(), None);
+        }),
+    });
+}
+
+#[test]
+fn test_ping_requires_enrolled_router() {
+    let (test_data, mut runner) = setup(loopback());
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, PingPong>(ping(SOLANA_DOMAIN, vec![1])),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_ping_payload_is_bounded() {
+    let (test_data, mut runner) = setup(loopback());
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, PingPong>(ping(
+                LOCAL_DOMAIN,
+                vec![0; MAX_PAYLOAD_LEN + 1],
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_admin_enrolls_and_unenrolls_router() {
+    let (test_data, mut runner) = setup(Vec::new());
+    let router = HexString([9; 32]);
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, PingPong>(CallMessage::EnrollRemoteRouter(
+                RemoteRouter {
+                    domain: SOLANA_DOMAIN,
+                    router: router.clone(),
+                },
+            )),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::PingPong(Event::RouterEnrolled {
+                    domain: SOLANA_DOMAIN,
+                    router: router.clone(),
+                })]
+            );
+            assert_eq!(
+                PingPong::default()
+                    .routers
+                    .get(&SOLANA_DOMAIN, state)
+                    .unwrap(),
+                Some(router)
+            );
+        }),
+    });
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, PingPong>(CallMessage::UnenrollRemoteRouter {
+                domain: SOLANA_DOMAIN,
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                PingPong::default()
+                    .routers
+                    .get(&SOLANA_DOMAIN, state)
+                    .unwrap(),
+                None
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_only_admin_enrolls_routers() {
+    let (test_data, mut runner) = setup(Vec::new());
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .other_user
+            .create_plain_message::<TestRuntime<S>, PingPong>(CallMessage::EnrollRemoteRouter(
+                RemoteRouter {
+                    domain: SOLANA_DOMAIN,
+                    router: HexString([9; 32]),
+                },
+            )),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+}
+
+#[test]
+fn test_message_encoding() {
+    let body = MessageKind::Pong.encode(&[4, 5]);
+    assert_eq!(body, vec![ping_pong::PONG, 4, 5]);
+    assert_eq!(
+        MessageKind::decode(&body).unwrap(),
+        (MessageKind::Pong, &[4, 5][..])
+    );
+
+    assert!(MessageKind::decode(&[]).is_err());
+    assert!(MessageKind::decode(&[2, 4, 5]).is_err());
+}
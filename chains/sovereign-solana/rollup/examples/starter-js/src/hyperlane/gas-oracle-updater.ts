@@ -0,0 +1,50 @@
+// Pushes destination gas prices from a JSON feed to the rollup gas oracle on a schedule.
+//
+// The feed must return an array of `{ domain, gas_price, token_exchange_rate }` objects. The relayer
+// must have registered a feed with this updater first (`gas_oracle.register_feed`).
+import {RuntimeCall} from "../types";
+import {deployerAddress, minterAddress, minterPrivateKey} from "./consts";
+import {Secp256k1Signer} from "@sovereign-sdk/signers";
+import {createStandardRollup} from "@sovereign-sdk/web3";
+
+const FEED_URL = process.env.GAS_ORACLE_FEED_URL ?? "http://127.0.0.1:8080/gas-prices";
+const INTERVAL_MS = Number(process.env.GAS_ORACLE_INTERVAL_MS ?? 60_000);
+const RELAYER = process.env.GAS_ORACLE_RELAYER ?? deployerAddress;
+
+type FeedEntry = { domain: number; gas_price: number; token_exchange_rate: number };
+
+async function fetchPrices(): Promise<FeedEntry[]> {
+    const response = await fetch(FEED_URL);
+    if (!response.ok) {
+        throw new Error(`Feed returned ${response.status}`);
+    }
+    return await response.json() as FeedEntry[];
+}
+
+const signer = new Secp256k1Signer(minterPrivateKey);
+const rollup = await createStandardRollup({
+    url: "http://127.0.0.1:12346",
+});
+console.log(`Pushing prices from ${FEED_URL} for ${RELAYER} as ${minterAddress} every ${INTERVAL_MS}ms`);
+
+async function push() {
+    try {
+        const prices = await fetchPrices();
+        const call: RuntimeCall = {
+            gas_oracle: {
+                update_prices: {
+                    relayer: RELAYER,
+                    prices,
+                },
+            },
+        };
+        const {response} = await rollup.call(call, {signer});
+        console.log(`[✓] Pushed ${prices.length} prices: ${response.receipt.result}`);
+    } catch (error) {
+        // Keep the previous prices on chain and retry at the next tick.
+        console.error(`[✗] Failed to push prices: ${error}`);
+    }
+}
+
+await push();
+setInterval(push, INTERVAL_MS);
@@ -1,12 +1,22 @@
-#![allow(unused_imports)]
 use anyhow::Result;
 use schemars::JsonSchema;
 use sov_modules_api::macros::{serialize, UniversalWallet};
 use sov_modules_api::{
     Context, EventEmitter, GenesisState, Module, ModuleId, ModuleInfo, ModuleRestApi, Spec,
-    StateValue, TxState,
+    StateMap, StateReader, StateValue, TxState,
 };
-use std::marker::PhantomData;
+use sov_rollup_interface::da::DaSpec;
+use sov_state::User;
+
+/// Genesis configuration of the [`ValueSetter`] module, read from the `value_setter` section of
+/// `genesis.json`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(bound = "S: Spec")]
+#[schemars(bound = "S: Spec", rename = "ValueSetterConfig")]
+pub struct ValueSetterConfig<S: Spec> {
+    /// The only account allowed to change the value.
+    pub admin: S::Address,
+}
 
 /// A new module:
 /// - Must derive `ModuleInfo`
@@ -23,42 +33,174 @@ pub struct ValueSetter<S: Spec> {
     #[state]
     pub value: StateValue<u32>,
 
-    /// You can disregard this, as its only used to satisfy
-    /// the compiler for the type parameter `S` not being used.
-    #[phantom]
-    pub phantom: PhantomData<S>,
+    /// The account allowed to change the value. Set at genesis.
+    #[state]
+    pub admin: StateValue<S::Address>,
+
+    /// The value at the end of every rollup height where it changed; `None` if it was cleared.
+    /// Served at `/modules/value-setter/state/history/items/{height}`.
+    #[state]
+    pub history: StateMap<u64, Option<u32>>,
+
+    /// Other modules are accessed through `#[module]` fields. Here, it is used to read the
+    /// current rollup height.
+    #[module]
+    pub chain_state: sov_chain_state::ChainState<S>,
+}
+
+/// The errors returned by [`ValueSetter::call`]. Modules return `anyhow::Error`, so these are
+/// converted with `.into()`; tests and callers can get them back with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ValueSetterError<S: Spec> {
+    /// The sender is not the admin.
+    #[error("Only the admin {admin} can change the value, not {sender}")]
+    NotAdmin {
+        admin: S::Address,
+        sender: S::Address,
+    },
+    /// The genesis config didn't set an admin.
+    #[error("The value setter admin is not set")]
+    AdminNotSet,
+    /// There is no value to clear.
+    #[error("The value is not set")]
+    ValueNotSet,
+}
+
+impl<S: Spec> ValueSetter<S> {
+    /// Returns the value at the end of `height`, if it changed at that height. `Some(None)`
+    /// means it was cleared.
+    pub fn value_at(
+        &self,
+        height: u64,
+        state: &mut impl StateReader<User>,
+    ) -> Result<Option<Option<u32>>> {
+        Ok(self.history.get(&height, state)?)
+    }
+
+    fn ensure_admin(&self, context: &Context<S>, state: &mut impl TxState<S>) -> Result<()> {
+        let admin = self
+            .admin
+            .get(state)?
+            .ok_or(ValueSetterError::<S>::AdminNotSet)?;
+        if &admin != context.sender() {
+            return Err(ValueSetterError::<S>::NotAdmin {
+                admin,
+                sender: context.sender().clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Records `value` as the value at the current rollup height and returns the height.
+    fn record(&mut self, value: Option<u32>, state: &mut impl TxState<S>) -> Result<u64> {
+        let height = self.chain_state.rollup_height(state)?.get();
+        self.history.set(&height, &value, state)?;
+        Ok(height)
+    }
 }
 
 impl<S: Spec> Module for ValueSetter<S> {
     type Spec = S;
 
-    type Config = ();
+    type Config = ValueSetterConfig<S>;
 
-    type CallMessage = CallMessage;
+    type CallMessage = CallMessage<S>;
 
-    type Event = ();
+    type Event = Event<S>;
 
     type Error = anyhow::Error;
 
+    fn genesis(
+        &mut self,
+        _genesis_rollup_header: &<<S as Spec>::Da as DaSpec>::BlockHeader,
+        config: &Self::Config,
+        state: &mut impl GenesisState<S>,
+    ) -> Result<()> {
+        self.admin.set(&config.admin, state)?;
+        Ok(())
+    }
+
     fn call(
         &mut self,
         msg: Self::CallMessage,
-        _context: &Context<Self::Spec>,
+        context: &Context<Self::Spec>,
         state: &mut impl TxState<S>,
     ) -> Result<()> {
+        // Every call changes the state, so they are all restricted to the admin. If the check
+        // fails, the transaction is reverted and none of its changes are kept.
+        self.ensure_admin(context, state)?;
+
         match msg {
-            CallMessage::SetValue(new_value) => {
-                self.value.set(&new_value, state)?;
+            CallMessage::SetValue(value) => {
+                let previous = self.value.get(state)?;
+                self.value.set(&value, state)?;
+                let height = self.record(Some(value), state)?;
+
+                self.emit_event(
+                    state,
+                    Event::ValueSet {
+                        value,
+                        previous,
+                        height,
+                    },
+                );
+                Ok(())
+            }
+            CallMessage::ClearValue => {
+                let previous = self
+                    .value
+                    .get(state)?
+                    .ok_or(ValueSetterError::<S>::ValueNotSet)?;
+                self.value.delete(state)?;
+                let height = self.record(None, state)?;
+
+                self.emit_event(state, Event::ValueCleared { previous, height });
+                Ok(())
+            }
+            CallMessage::SetAdmin { admin } => {
+                let previous = context.sender().clone();
+                self.admin.set(&admin, state)?;
 
+                self.emit_event(state, Event::AdminChanged { previous, admin });
                 Ok(())
             }
         }
     }
 }
 
+/// The calls accepted by the module. In JSON, the variants are written in snake_case, e.g.
+/// `{"value_setter": {"set_value": 10}}`.
 #[derive(Debug, Clone, PartialEq, Eq, JsonSchema, UniversalWallet)]
 #[serialize(Borsh, Serde)]
 #[serde(rename_all = "snake_case")]
-pub enum CallMessage {
+#[schemars(bound = "S: Spec", rename = "CallMessage")]
+pub enum CallMessage<S: Spec> {
+    /// Sets the value. Admin only.
     SetValue(u32),
+    /// Unsets the value. Admin only.
+    ClearValue,
+    /// Hands the module over to `admin`. Admin only.
+    SetAdmin { admin: S::Address },
+}
+
+/// The events emitted by the module, one for every change.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serialize(Borsh, Serde)]
+#[serde(rename_all = "snake_case")]
+#[schemars(bound = "S: Spec", rename = "Event")]
+pub enum Event<S: Spec> {
+    /// The value was set at rollup height `height`.
+    ValueSet {
+        value: u32,
+        previous: Option<u32>,
+        height: u64,
+    },
+    /// The value was cleared at rollup height `height`.
+    ValueCleared { previous: u32, height: u64 },
+    /// The admin was replaced.
+    AdminChanged {
+        previous: S::Address,
+        admin: S::Address,
+    },
 }
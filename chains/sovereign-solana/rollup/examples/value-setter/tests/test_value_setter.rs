@@ -16,23 +16,30 @@ use sov_test_utils::TestUser;
 
 // A helper struct to hold our test users, for convenience.
 pub struct TestData<S: Spec> {
+    pub admin: TestUser<S>,
     pub regular_user: TestUser<S>,
 }
 
 pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
-    // Create a regular user.
+    // Create an admin and a regular user.
     // (The `HighLevelOptimisticGenesisConfig` builder is a convenient way
     // to set up the initial state for core modules.)
     let genesis_config =
-        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(1);
+        HighLevelOptimisticGenesisConfig::generate().add_accounts_with_default_balance(2);
 
     let mut users = genesis_config.additional_accounts().to_vec();
+    let admin = users.pop().unwrap();
     let regular_user = users.pop().unwrap();
 
-    let test_data = TestData { regular_user };
-
     // Configure the genesis state for our ValueSetter module.
-    let value_setter_config = ValueSetterConfig {};
+    let value_setter_config = ValueSetterConfig {
+        admin: admin.address(),
+    };
+
+    let test_data = TestData {
+        admin,
+        regular_user,
+    };
 
     // Build the final genesis config by combining
     // the core config with our module's specific config.
@@ -47,31 +54,180 @@ pub fn setup() -> (TestData<S>, TestRunner<TestRuntime<S>, S>) {
 }
 
 use sov_test_utils::{AsUser, TransactionTestCase};
-use value_setter::{CallMessage, Event};
+use value_setter::{CallMessage, Event, ValueSetterError};
+
+/// Sets the value as the admin and checks the event, the value and its history.
+fn set_value(test_data: &TestData<S>, runner: &mut TestRunner<TestRuntime<S>, S>, value: u32) {
+    let previous =
+        runner.query_state(|state| ValueSetter::<S>::default().value.get(state).unwrap());
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, ValueSetter<S>>(CallMessage::SetValue(value)),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+
+            // Every change emits a typed event, which indexers and front-ends can rely on.
+            let [TestRuntimeEvent::ValueSetter(Event::ValueSet {
+                value: event_value,
+                previous: event_previous,
+                height,
+            })] = result.events.as_slice()
+            else {
+                panic!("Unexpected events: {:?}", result.events);
+            };
+            assert_eq!(*event_value, value);
+            assert_eq!(*event_previous, previous);
+
+            let value_setter = ValueSetter::<S>::default();
+            assert_eq!(value_setter.value.get(state).unwrap(), Some(value));
+            assert_eq!(
+                value_setter.value_at(*height, state).unwrap(),
+                Some(Some(value))
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_genesis_sets_admin() {
+    let (test_data, mut runner) = setup();
+    let admin = test_data.admin.address();
+
+    runner.query_state(|state| {
+        let value_setter = ValueSetter::<S>::default();
+        assert_eq!(value_setter.admin.get(state).unwrap(), Some(admin));
+        assert_eq!(value_setter.value.get(state).unwrap(), None);
+    });
+}
+
+#[test]
+fn test_admin_can_set_value() {
+    let (test_data, mut runner) = setup();
+
+    set_value(&test_data, &mut runner, 42);
+    // The event of the second change carries the first value.
+    set_value(&test_data, &mut runner, 43);
+}
 
 #[test]
-fn test_can_set_value() {
-    // 1. Setup
+fn test_regular_user_cannot_set_value() {
     let (test_data, mut runner) = setup();
-    let regular_user = &test_data.regular_user;
+    set_value(&test_data, &mut runner, 42);
 
-    let new_value = 42;
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .regular_user
+            .create_plain_message::<TestRuntime<S>, ValueSetter<S>>(CallMessage::SetValue(7)),
+        assert: Box::new(move |result, state| {
+            // The transaction is reverted: no event, and the value is unchanged.
+            assert!(result.tx_receipt.is_reverted());
+            assert!(result.events.is_empty());
+            assert_eq!(
+                ValueSetter::<S>::default().value.get(state).unwrap(),
+                Some(42)
+            );
+        }),
+    });
+}
+
+#[test]
+fn test_admin_can_clear_value() {
+    let (test_data, mut runner) = setup();
+    set_value(&test_data, &mut runner, 42);
 
-    // 2. Execute the transaction
     runner.execute_transaction(TransactionTestCase {
-        // The transaction input created by a regular user.
-        input: regular_user.create_plain_message::<TestRuntime<S>, ValueSetter<S>>(
-            CallMessage::SetValue(new_value),
-        ),
-        // The assertions to run after execution.
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, ValueSetter<S>>(CallMessage::ClearValue),
         assert: Box::new(move |result, state| {
-            // 3. Assert the outcome
             assert!(result.tx_receipt.is_successful());
 
-            // Assert that the state was updated correctly by querying the module.
+            let [TestRuntimeEvent::ValueSetter(Event::ValueCleared { previous, height })] =
+                result.events.as_slice()
+            else {
+                panic!("Unexpected events: {:?}", result.events);
+            };
+            assert_eq!(*previous, 42);
+
             let value_setter = ValueSetter::<S>::default();
-            let current_value = value_setter.value.get(state).unwrap();
-            assert_eq!(current_value, Some(new_value));
+            assert_eq!(value_setter.value.get(state).unwrap(), None);
+            assert_eq!(value_setter.value_at(*height, state).unwrap(), Some(None));
+        }),
+    });
+}
+
+#[test]
+fn test_clearing_an_unset_value_fails() {
+    let (test_data, mut runner) = setup();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, ValueSetter<S>>(CallMessage::ClearValue),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
         }),
     });
 }
+
+#[test]
+fn test_admin_can_hand_over() {
+    let (test_data, mut runner) = setup();
+    let admin = test_data.admin.address();
+    let new_admin = test_data.regular_user.address();
+
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, ValueSetter<S>>(CallMessage::SetAdmin {
+                admin: new_admin.clone(),
+            }),
+        assert: Box::new(move |result, state| {
+            assert!(result.tx_receipt.is_successful());
+            assert_eq!(
+                result.events,
+                vec![TestRuntimeEvent::ValueSetter(Event::AdminChanged {
+                    previous: admin,
+                    admin: new_admin.clone(),
+                })]
+            );
+            assert_eq!(
+                ValueSetter::<S>::default().admin.get(state).unwrap(),
+                Some(new_admin)
+            );
+        }),
+    });
+
+    // The previous admin lost its rights...
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .admin
+            .create_plain_message::<TestRuntime<S>, ValueSetter<S>>(CallMessage::SetValue(1)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_reverted());
+        }),
+    });
+
+    // ...and the new one can set the value.
+    runner.execute_transaction(TransactionTestCase {
+        input: test_data
+            .regular_user
+            .create_plain_message::<TestRuntime<S>, ValueSetter<S>>(CallMessage::SetValue(2)),
+        assert: Box::new(move |result, _state| {
+            assert!(result.tx_receipt.is_successful());
+        }),
+    });
+}
+
+#[test]
+fn test_errors_are_typed() {
+    // Errors are returned as `anyhow::Error`, and can be matched on after a downcast.
+    let error: anyhow::Error = ValueSetterError::<S>::ValueNotSet.into();
+    assert_eq!(
+        error.downcast_ref::<ValueSetterError<S>>(),
+        Some(&ValueSetterError::ValueNotSet)
+    );
+    assert_eq!(error.to_string(), "The value is not set");
+}
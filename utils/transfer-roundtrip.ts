@@ -16,7 +16,8 @@ import tokenConfig from "../chains/solana/environments/local/warp-routes/sealeve
 import deployedWarpRoutes from "../chains/solana/environments/local/warp-routes/sealevel-sovereignsolana/program-ids.json";
 import solanaKeypair from "../chains/solana/environments/local/accounts/signer_keypair.json";
 import { Ed25519Signer } from "@sovereign-sdk/signers";
-import { UnsignedTransaction } from "@sovereign-sdk/types";
+import { createStandardRollup } from "@sovereign-sdk/web3";
+import bs58 from "bs58";
 
 interface Account {
   privateKey: Uint8Array;
@@ -175,33 +176,92 @@ await waitForBalanceSovereign(transferAmount);
 
 console.log("Token successfully transferred to sovereign!");
 
-// Currently failing due to quote remote dispatch, we can probably just return 0 for remote gas estimate for now
-// https://github.com/Sovereign-Labs/hyperlane-monorepo/issues/19
-// console.log("Sending back to solana..");
-//
-// const preTransferBalance = await solanaToken.getBalance(
-//   multiProvider,
-//   solanaAccount.address
-// );
-// console.log(`Pre-transfer solana balance: ${preTransferBalance.amount}`);
-// const warpBackTxns = await warpCore.getTransferRemoteTxs({
-//   originTokenAmount: sovereignToken.amount(transferAmount),
-//   destination: "sealevel",
-//   sender: sovereignAccount.address,
-//   recipient: solanaAccount.address,
-// });
-// const sovereignProvider = await multiProvider.getSovereignProvider("sovereign");
-// const sovereignSigner = new Ed25519Signer(sovereignAccount.privateKey);
-//
-// for (const tx of warpBackTxns) {
-//   console.log(`Processing ${tx.category} transaction...`);
-//
-//   const { response } = await sovereignProvider.signAndSubmitTransaction(
-//     tx.transaction as UnsignedTransaction<{}>,
-//     { signer: sovereignSigner }
-//   );
-//
-//   console.log(`Sovereign tx: ${response.id} (status: ${response.status})`);
-// }
-//
-// console.log("Waiting for balance on solana...");
+console.log("Sending back to solana..");
+
+const sovereignRpcUrl = chainMetadata.sovereign.rpcUrls[0].http;
+// The relayer configured by `chains/sovereign-solana/index.ts` is the deployer itself.
+const sovereignRelayer =
+  process.env.SOVEREIGN_RELAYER_ADDRESS ?? sovereignAccount.address;
+
+interface Quote {
+  igp_payment: number;
+  hook_fee: number;
+  total: number;
+}
+
+// Asks the rollup for the IGP payment and protocol fees of the transfer, instead of guessing them.
+const quoteTransferRemote = async (): Promise<Quote> => {
+  const params = new URLSearchParams({
+    destination: String(chainMetadata.sealevel.domainId),
+    relayer: sovereignRelayer,
+    sender: sovereignTokenId,
+  });
+  const response = await fetch(`${sovereignRpcUrl}/hyperlane/quote?${params}`);
+  if (!response.ok) {
+    throw new Error(
+      `Failed to quote transfer: ${response.status} ${await response.text()}`
+    );
+  }
+  return (await response.json()) as Quote;
+};
+
+const waitForBalanceSolana = async (
+  expectedBalance: bigint,
+  timeoutSeconds = 120
+) => {
+  const start = Date.now();
+  while (Date.now() - start < timeoutSeconds * 1000) {
+    const balance = await solanaToken.getBalance(
+      multiProvider,
+      solanaAccount.address
+    );
+    if (balance.amount >= expectedBalance) return;
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+  }
+  throw new Error("Timeout waiting for balance on solana");
+};
+
+const preTransferBalance = await solanaToken.getBalance(
+  multiProvider,
+  solanaAccount.address
+);
+console.log(`Pre-transfer solana balance: ${preTransferBalance.amount}`);
+
+const quote = await quoteTransferRemote();
+console.log(
+  `Quoted IGP payment: ${quote.igp_payment}, protocol fees: ${quote.hook_fee}`
+);
+
+// Warp recipients are 32 byte hex strings, and solana addresses are their base58 encoding.
+const solanaRecipient = `0x${Buffer.from(
+  bs58.decode(solanaAccount.address)
+).toString("hex")}`;
+const sovereignRollup = await createStandardRollup<any>({
+  url: sovereignRpcUrl,
+});
+const sovereignSigner = new Ed25519Signer(sovereignAccount.privateKey);
+const { response } = await sovereignRollup.call(
+  {
+    warp: {
+      transfer_remote: {
+        warp_route: sovereignTokenId,
+        destination_domain: chainMetadata.sealevel.domainId,
+        recipient: solanaRecipient,
+        amount: transferAmount,
+        relayer: sovereignRelayer,
+        // The protocol fees are charged on top of the IGP payment, so only it is capped.
+        gas_payment_limit: quote.igp_payment,
+      },
+    },
+  },
+  { signer: sovereignSigner }
+);
+console.log(`Sovereign tx: ${response.id} (status: ${response.status})`);
+
+console.log("Waiting for balance on solana...");
+
+await waitForBalanceSolana(
+  BigInt(preTransferBalance.amount) + BigInt(transferAmount)
+);
+
+console.log("Token successfully transferred back to solana!");